@@ -1,4 +1,5 @@
 pub mod PassRunner;
 pub mod Replacer;
 pub mod SSATransformer;
+pub mod SsaBody;
 pub mod ssa;
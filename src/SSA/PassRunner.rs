@@ -2,8 +2,7 @@ use rustc_middle::mir::pretty::{write_mir_fn, PrettyPrintMirOptions};
 use std::collections::HashMap;
 #[allow(unused)]
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::{self, Cursor};
+use std::io::Cursor;
 // use std::fs::File;
 // use std::io::{self, Write};
 // use rustc_index::bit_set::BitSet;
@@ -28,19 +27,6 @@ impl<'tcx> PassRunner<'tcx> {
     pub fn new(tcx: TyCtxt<'tcx>) -> Self {
         Self { tcx }
     }
-    pub fn print_diff(&self, body: &Body<'tcx>) {
-        let dir_path = "passrunner_mir";
-        // PassRunner::new(self.tcx);
-        let mir_file_path = format!("{}/origin_mir.txt", dir_path);
-        let phi_mir_file_path = format!("{}/after_rename_mir.txt", dir_path);
-        let mut file = File::create(&mir_file_path).unwrap();
-        let mut w = io::BufWriter::new(&mut file);
-        write_mir_pretty(self.tcx, None, &mut w).unwrap();
-        let mut file2 = File::create(&phi_mir_file_path).unwrap();
-        let mut w2 = io::BufWriter::new(&mut file2);
-        let options = PrettyPrintMirOptions::from_cli(self.tcx);
-        write_mir_fn(self.tcx, body, &mut |_, _| Ok(()), &mut w2, options).unwrap();
-    }
     pub fn get_final_ssa_as_string(&self, body: &Body<'tcx>) -> String {
         // origin_mir
         // let mut buffer1 = Cursor::new(Vec::new());
@@ -76,7 +62,19 @@ impl<'tcx> PassRunner<'tcx> {
 
     //     !has_duplicate
     // }
-    pub fn run_pass(&self, body: &mut Body<'tcx>) {
+    /// Rewrites `body` into e-SSA form and returns the variable each renamed
+    /// local versions.
+    pub fn run_pass(&self, body: &mut Body<'tcx>) -> HashMap<Local, Local> {
+        self.run_pass_with_stages(body, |_, _| {})
+    }
+    /// Same as `run_pass`, but hands the body to `on_stage` after each step:
+    /// `"phi"` once phis are inserted, `"essa"` once sigmas are inserted and
+    /// `"renamed"` once every definition has its own local.
+    pub fn run_pass_with_stages(
+        &self,
+        body: &mut Body<'tcx>,
+        mut on_stage: impl FnMut(&str, &Body<'tcx>),
+    ) -> HashMap<Local, Local> {
         let ssatransformer =
             SSATransformer::new(self.tcx, body, body.source.def_id().expect_local());
         let mut replacer = Replacer {
@@ -86,9 +84,11 @@ impl<'tcx> PassRunner<'tcx> {
             new_local_collection: HashSet::default(),
         };
         replacer.insert_phi_statment(body);
+        on_stage("phi", body);
         replacer.insert_essa_statement(body);
+        on_stage("essa", body);
         replacer.rename_variables(body);
-        print!("====After SSA====\n");
-        let essa_mir_string = self.get_final_ssa_as_string(body);
+        on_stage("renamed", body);
+        replacer.ssatransformer.ssa_origin
     }
 }
@@ -12,6 +12,9 @@ use std::collections::{HashMap, HashSet, VecDeque};
 // // use rustc_middle::mir::visit::*;
 // // use rustc_index::IndexSlice;
 
+/// Last operand of a sigma statement whose bound is another variable.
+pub const ESSA_MAGIC_NUMBER: u64 = 213134123;
+
 pub struct Replacer<'tcx> {
     pub(crate) tcx: TyCtxt<'tcx>,
     pub(crate) ssatransformer: super::SSATransformer::SSATransformer<'tcx>,
@@ -54,7 +57,11 @@ impl<'tcx> Replacer<'tcx> {
         }
 
         for (block, vars) in phi_functions {
-            for var in vars.clone() {
+            // Each phi goes to the head of the block, so inserting them in
+            // descending order leaves them sorted by variable.
+            let mut vars: Vec<Local> = vars.into_iter().collect();
+            vars.sort_unstable_by(|a, b| b.cmp(a));
+            for &var in &vars {
                 let decl = body.local_decls[var].clone();
                 // let new_var = body.local_decls.push(decl);
 
@@ -177,7 +184,7 @@ impl<'tcx> Replacer<'tcx> {
         let switch_block_data = &body.basic_blocks[*switch_block];
 
         // let mut essa_operands: IndexVec<_, _> = IndexVec::with_capacity(2);
-        let magic_number = 213134123 as u64;
+        let magic_number = ESSA_MAGIC_NUMBER;
         let magic_number_operand = Operand::Constant(Box::new(ConstOperand {
             span: rustc_span::DUMMY_SP,
             user_ty: None,
@@ -207,8 +214,6 @@ impl<'tcx> Replacer<'tcx> {
             if let Some((op1, op2, cmp_op)) =
                 self.extract_condition(switch_place, switch_block_data)
             {
-                                        print!("op1: {:?}, op2: {:?}, cmp_op: {:?}\n", op1, op2, cmp_op);
-
                 let block_data: &mut BasicBlockData<'tcx> = &mut body.basic_blocks.as_mut()[*bb];
 
                 let const_op1: Option<&ConstOperand<'_>> = op1.constant();
@@ -350,6 +355,25 @@ impl<'tcx> Replacer<'tcx> {
         for bb in order {
             self.process_basic_block(bb, body);
         }
+        self.declare_new_locals(body);
+    }
+
+    /// Renamed locals are allocated past `local_decls`, give each one the
+    /// declaration of the variable it versions so the body stays well-typed.
+    fn declare_new_locals(&mut self, body: &mut Body<'tcx>) {
+        let mut new_locals: Vec<(Local, Local)> = self
+            .ssatransformer
+            .ssa_origin
+            .iter()
+            .map(|(&new_local, &old_local)| (new_local, old_local))
+            .collect();
+        new_locals.sort_by_key(|(new_local, _)| *new_local);
+        for (new_local, old_local) in new_locals {
+            let decl = body.local_decls[old_local].clone();
+            let pushed = body.local_decls.push(decl);
+            debug_assert_eq!(pushed, new_local);
+            self.new_local_collection.insert(new_local);
+        }
     }
 
     fn process_basic_block(&mut self, bb: BasicBlock, body: &mut Body<'tcx>) {
@@ -380,7 +404,6 @@ impl<'tcx> Replacer<'tcx> {
                     if let Some((op1, op2, cmp_op)) =
                         self.extract_condition(switch_place, switch_block_data)
                     {
-                        print!("op1: {:?}, op2: {:?}, cmp_op: {:?}\n", op1, op2, cmp_op);
                         if op2.constant().is_none() {
                             let essa_statement = body.basic_blocks.as_mut()[succ_bb]
                                 .statements
@@ -523,8 +546,24 @@ impl<'tcx> Replacer<'tcx> {
                 //     self.rename_def(place);
                 // }
             }
-            TerminatorKind::Assert { cond, .. } => {
+            TerminatorKind::Assert { cond, msg, .. } => {
                 self.replace_operand(cond, &bb);
+                match &mut **msg {
+                    AssertKind::BoundsCheck { len, index } => {
+                        self.replace_operand(len, &bb);
+                        self.replace_operand(index, &bb);
+                    }
+                    AssertKind::Overflow(_, lhs, rhs) => {
+                        self.replace_operand(lhs, &bb);
+                        self.replace_operand(rhs, &bb);
+                    }
+                    AssertKind::OverflowNeg(operand)
+                    | AssertKind::DivisionByZero(operand)
+                    | AssertKind::RemainderByZero(operand) => {
+                        self.replace_operand(operand, &bb);
+                    }
+                    _ => {}
+                }
             }
             TerminatorKind::Drop { place, .. } => {
                 self.replace_place(place, &bb);
@@ -573,9 +612,9 @@ impl<'tcx> Replacer<'tcx> {
         self.update_reachinf_def(&place.local, &bb);
 
         if let Some(Some(reaching_local)) = self.ssatransformer.reaching_def.get(&place.local) {
-            let local = reaching_local.clone();
-            *place = Place::from(local);
-        } else {
+            // Only the local is renamed: a use of `(_4.0)` reads a field of
+            // the version of `_4` that reaches it.
+            place.local = *reaching_local;
         }
     }
 
@@ -591,6 +630,11 @@ impl<'tcx> Replacer<'tcx> {
             self.ssatransformer
                 .reaching_def
                 .insert(*old_local, Some(*old_local));
+            // An argument first defined by a sigma has no assignment in the
+            // original body, so its definition block is only known here.
+            self.ssatransformer
+                .local_defination_block
+                .insert(*old_local, *bb);
 
             return;
         }
@@ -599,6 +643,7 @@ impl<'tcx> Replacer<'tcx> {
 
         let _old_local = old_local.clone();
         *place = Place::from(new_local);
+        self.ssatransformer.ssa_origin.insert(new_local, _old_local);
         self.ssatransformer
             .local_defination_block
             .insert(new_local.clone(), bb.clone());
@@ -44,6 +44,8 @@ pub struct SSATransformer<'tcx> {
     pub phi_statements: HashMap<*const Statement<'tcx>, bool>,
     pub essa_statements: HashMap<*const Statement<'tcx>, bool>,
     pub phi_def_id: DefId,
+    /// Maps every local introduced by renaming to the variable it versions.
+    pub ssa_origin: HashMap<Local, Local>,
 }
 
 impl<'tcx> SSATransformer<'tcx> {
@@ -123,6 +125,7 @@ impl<'tcx> SSATransformer<'tcx> {
             phi_statements: HashMap::default(),
             essa_statements: HashMap::default(),
             phi_def_id: def_id.to_def_id(),
+            ssa_origin: HashMap::default(),
             // phi_defid: Self::find_phi_placeholder(tcx, "RAP-interval-demo").unwrap(),
         }
     }
@@ -249,7 +252,9 @@ impl<'tcx> SSATransformer<'tcx> {
 
         local_to_blocks
     }
-    fn construct_dominance_tree(body: &Body<'_>) -> HashMap<BasicBlock, Vec<BasicBlock>> {
+    pub(crate) fn construct_dominance_tree(
+        body: &Body<'_>,
+    ) -> HashMap<BasicBlock, Vec<BasicBlock>> {
         let mut dom_tree: HashMap<BasicBlock, Vec<BasicBlock>> = HashMap::new();
         let dominators = body.basic_blocks.dominators();
         for (block, _) in body.basic_blocks.iter_enumerated() {
@@ -260,7 +265,7 @@ impl<'tcx> SSATransformer<'tcx> {
 
         dom_tree
     }
-    fn compute_dominance_frontier(
+    pub(crate) fn compute_dominance_frontier(
         body: &Body<'_>,
         dom_tree: &HashMap<BasicBlock, Vec<BasicBlock>>,
     ) -> HashMap<BasicBlock, HashSet<BasicBlock>> {
@@ -276,6 +281,11 @@ impl<'tcx> SSATransformer<'tcx> {
                 let preds = body.basic_blocks.predecessors()[block].clone();
 
                 for &pred in &preds {
+                    // An edit can leave a predecessor unreachable, and such a
+                    // block has no dominators to walk.
+                    if !dominators.is_reachable(pred) {
+                        continue;
+                    }
                     let mut runner = pred;
                     while runner != dominators.immediate_dominator(block).unwrap() {
                         dominance_frontier.entry(runner).or_default().insert(block);
@@ -287,7 +297,9 @@ impl<'tcx> SSATransformer<'tcx> {
 
         dominance_frontier
     }
-    fn extract_cfg_from_predecessors(body: &Body<'_>) -> HashMap<BasicBlock, Vec<BasicBlock>> {
+    pub(crate) fn extract_cfg_from_predecessors(
+        body: &Body<'_>,
+    ) -> HashMap<BasicBlock, Vec<BasicBlock>> {
         let mut cfg: HashMap<BasicBlock, Vec<BasicBlock>> = HashMap::new();
 
         for (block, _) in body.basic_blocks.iter_enumerated() {
@@ -0,0 +1,724 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+use super::PassRunner::PassRunner;
+use super::SSATransformer::SSATransformer;
+use rustc_index::IndexVec;
+use rustc_middle::mir::visit::{MutVisitor, PlaceContext, Visitor};
+use rustc_middle::mir::*;
+use rustc_middle::ty::{self, TyCtxt};
+use std::collections::{HashMap, HashSet};
+
+/// How `Replacer` encoded a statement. Phis and sigmas are both tuple
+/// aggregates assigned to a non-tuple local; sigmas carry constant operands
+/// (the comparison code and the bound or magic number).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SsaStatementKind {
+    Phi,
+    Sigma,
+    Plain,
+}
+
+pub fn classify_statement<'tcx>(body: &Body<'tcx>, stmt: &Statement<'tcx>) -> SsaStatementKind {
+    if let StatementKind::Assign(box (
+        place,
+        Rvalue::Aggregate(box AggregateKind::Tuple, operands),
+    )) = &stmt.kind
+    {
+        let is_tuple_local = body
+            .local_decls
+            .get(place.local)
+            .map_or(true, |decl| matches!(decl.ty.kind(), ty::Tuple(_)));
+        if place.projection.is_empty() && !is_tuple_local {
+            if operands.iter().any(|op| op.constant().is_some()) {
+                return SsaStatementKind::Sigma;
+            }
+            return SsaStatementKind::Phi;
+        }
+    }
+    SsaStatementKind::Plain
+}
+
+fn assigned_local(stmt: &Statement<'_>) -> Option<Local> {
+    match &stmt.kind {
+        StatementKind::Assign(box (place, _)) => Some(place.local),
+        _ => None,
+    }
+}
+
+/// Returns the version of `var` whose definition reaches `loc` in `body`,
+/// where `ssa_origin` maps every version to the variable it versions. The
+/// statement at `loc` itself has not run yet.
+pub fn reaching_def(
+    body: &Body<'_>,
+    ssa_origin: &HashMap<Local, Local>,
+    var: Local,
+    loc: Location,
+) -> Local {
+    let original_local = |local: Local| ssa_origin.get(&local).copied().unwrap_or(local);
+    let var = original_local(var);
+    let dominators = body.basic_blocks.dominators();
+    let mut block = loc.block;
+    let mut end = loc.statement_index;
+    loop {
+        let data = &body.basic_blocks[block];
+        if end > data.statements.len() {
+            if let Some(Terminator {
+                kind: TerminatorKind::Call { destination, .. },
+                ..
+            }) = &data.terminator
+            {
+                if original_local(destination.local) == var {
+                    return destination.local;
+                }
+            }
+        }
+        let end_index = end.min(data.statements.len());
+        for stmt in data.statements[..end_index].iter().rev() {
+            if let Some(local) = assigned_local(stmt) {
+                if original_local(local) == var {
+                    return local;
+                }
+            }
+        }
+        match dominators.immediate_dominator(block) {
+            Some(idom) => {
+                block = idom;
+                end = usize::MAX;
+            }
+            None => return var,
+        }
+    }
+}
+
+/// A body in e-SSA form that can be edited without rerunning `PassRunner`.
+///
+/// Every edit repairs the variables it touches: missing phis are inserted on
+/// the iterated dominance frontier, every use of a version is rebound to the
+/// definition that now reaches it, and phis left with a single incoming value
+/// are folded away.
+///
+/// The definitions and uses of every variable are indexed, so that a repair
+/// only visits the blocks holding them. After an edit of the definitions in
+/// a few blocks, only the iterated dominance frontier of those blocks can
+/// need a phi, and only the uses in the blocks it or they dominate can see
+/// another definition.
+pub struct SsaBody<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub body: Body<'tcx>,
+    /// Maps every local introduced by renaming to the variable it versions.
+    pub ssa_origin: HashMap<Local, Local>,
+    pub dom_tree: HashMap<BasicBlock, Vec<BasicBlock>>,
+    pub df: HashMap<BasicBlock, HashSet<BasicBlock>>,
+    pub local_defination_block: HashMap<Local, BasicBlock>,
+    // The versions of each variable that a statement defines.
+    defs: HashMap<Local, HashSet<Local>>,
+    // The versions of each variable that a phi defines.
+    phis: HashMap<Local, HashSet<Local>>,
+    // The blocks reading a version of each variable.
+    uses: HashMap<Local, HashSet<BasicBlock>>,
+}
+
+impl<'tcx> SsaBody<'tcx> {
+    /// Runs the e-SSA pipeline on `body` and wraps the result.
+    pub fn new(tcx: TyCtxt<'tcx>, mut body: Body<'tcx>) -> Self {
+        let ssa_origin = PassRunner::new(tcx).run_pass(&mut body);
+        Self::from_ssa(tcx, body, ssa_origin)
+    }
+
+    /// Wraps a body that is already in e-SSA form.
+    pub fn from_ssa(
+        tcx: TyCtxt<'tcx>,
+        body: Body<'tcx>,
+        ssa_origin: HashMap<Local, Local>,
+    ) -> Self {
+        let mut ssa = SsaBody {
+            tcx,
+            body,
+            ssa_origin,
+            dom_tree: HashMap::new(),
+            df: HashMap::new(),
+            local_defination_block: HashMap::new(),
+            defs: HashMap::new(),
+            phis: HashMap::new(),
+            uses: HashMap::new(),
+        };
+        ssa.recompute_dominance();
+        for bb in ssa.body.basic_blocks.indices() {
+            for index in 0..ssa.body.basic_blocks[bb].statements.len() {
+                ssa.index_definition(Location {
+                    block: bb,
+                    statement_index: index,
+                });
+            }
+            for var in ssa.vars_used_in(bb) {
+                ssa.uses.entry(var).or_default().insert(bb);
+            }
+        }
+        ssa
+    }
+
+    pub fn body(&self) -> &Body<'tcx> {
+        &self.body
+    }
+
+    pub fn into_body(self) -> Body<'tcx> {
+        self.body
+    }
+
+    /// Returns the source variable `local` is a version of.
+    pub fn original_local(&self, local: Local) -> Local {
+        self.ssa_origin.get(&local).copied().unwrap_or(local)
+    }
+
+    /// Returns every version of `var`, including `var` itself.
+    pub fn versions(&self, var: Local) -> HashSet<Local> {
+        let var = self.original_local(var);
+        let mut versions: HashSet<Local> = self
+            .ssa_origin
+            .iter()
+            .filter(|(_, &origin)| origin == var)
+            .map(|(&local, _)| local)
+            .collect();
+        versions.insert(var);
+        versions
+    }
+
+    /// Returns the version of `var` whose definition reaches `loc`.
+    pub fn reaching_def(&self, var: Local, loc: Location) -> Local {
+        reaching_def(&self.body, &self.ssa_origin, var, loc)
+    }
+
+    /// Inserts `var = rvalue` before `loc` as a new version of `var` and
+    /// returns the new local.
+    pub fn add_definition(&mut self, loc: Location, var: Local, rvalue: Rvalue<'tcx>) -> Local {
+        let var = self.original_local(var);
+        let new_local = self.new_version(var);
+        let stmt = Statement {
+            source_info: SourceInfo::outermost(self.body.span),
+            kind: StatementKind::Assign(Box::new((Place::from(new_local), rvalue))),
+        };
+        self.body.basic_blocks.as_mut_preserves_cfg()[loc.block]
+            .statements
+            .insert(loc.statement_index, stmt);
+        self.index_definition(loc);
+        self.reindex_uses(&[loc.block]);
+        self.repair(var, &HashSet::from([loc.block]), false);
+        new_local
+    }
+
+    /// Removes the statement at `loc`. Uses of the version it defined are
+    /// rebound to the definition reaching `loc`.
+    pub fn remove_statement(&mut self, loc: Location) -> Statement<'tcx> {
+        let stmt = self.body.basic_blocks.as_mut_preserves_cfg()[loc.block]
+            .statements
+            .remove(loc.statement_index);
+        self.reindex_uses(&[loc.block]);
+        if let Some(local) = assigned_local(&stmt) {
+            self.unindex_definition(local);
+            let var = self.original_local(local);
+            self.repair(var, &HashSet::from([loc.block]), false);
+        }
+        stmt
+    }
+
+    /// Makes the edge `from -> old_target` point at `new_target`.
+    ///
+    /// Sigmas at the head of `old_target` describe the redirected edge, so they
+    /// move to a fresh landing block between `from` and `new_target`. Returns
+    /// that landing block if one was created.
+    pub fn redirect_edge(
+        &mut self,
+        from: BasicBlock,
+        old_target: BasicBlock,
+        new_target: BasicBlock,
+    ) -> Option<BasicBlock> {
+        let sigma_count = self.body.basic_blocks[old_target]
+            .statements
+            .iter()
+            .take_while(|stmt| classify_statement(&self.body, stmt) == SsaStatementKind::Sigma)
+            .count();
+        let is_switch = matches!(
+            self.body.basic_blocks[from].terminator().kind,
+            TerminatorKind::SwitchInt { .. }
+        );
+        let vars = self.vars_across_edge(from, old_target, new_target, sigma_count);
+        let mut target = new_target;
+        let mut landing = None;
+        if is_switch && sigma_count > 0 {
+            let span = self.body.span;
+            let blocks = self.body.basic_blocks_mut();
+            let is_cleanup = blocks[from].is_cleanup;
+            let sigmas: Vec<Statement<'tcx>> =
+                blocks[old_target].statements.drain(..sigma_count).collect();
+            let landing_bb = blocks.push(BasicBlockData {
+                statements: sigmas,
+                terminator: Some(Terminator {
+                    source_info: SourceInfo::outermost(span),
+                    kind: TerminatorKind::Goto { target: new_target },
+                }),
+                is_cleanup,
+            });
+            for stmt in &blocks[landing_bb].statements {
+                if let Some(local) = assigned_local(stmt) {
+                    self.local_defination_block.insert(local, landing_bb);
+                }
+            }
+            self.reindex_uses(&[old_target, landing_bb]);
+            target = landing_bb;
+            landing = Some(landing_bb);
+        }
+        for succ in self.body.basic_blocks_mut()[from]
+            .terminator_mut()
+            .successors_mut()
+        {
+            if *succ == old_target {
+                *succ = target;
+            }
+        }
+        self.recompute_dominance();
+        for var in vars {
+            self.repair_variable(var);
+        }
+        landing
+    }
+
+    /// Returns the variables whose definitions reach across the edge that
+    /// `redirect_edge` moves, computed before the CFG changes.
+    ///
+    /// A variable needs repairing when the new edge brings `new_target` a
+    /// version other than the one already reaching it, when a phi of it at
+    /// either target loses or gains an operand, or when one of the
+    /// `sigma_count` sigmas at the head of `old_target` defines it.
+    fn vars_across_edge(
+        &self,
+        from: BasicBlock,
+        old_target: BasicBlock,
+        new_target: BasicBlock,
+        sigma_count: usize,
+    ) -> Vec<Local> {
+        let from_end = Location {
+            block: from,
+            statement_index: usize::MAX,
+        };
+        let target_head = Location {
+            block: new_target,
+            statement_index: 0,
+        };
+        let mut vars: Vec<Local> = self
+            .ssa_origin
+            .values()
+            .copied()
+            .filter(|&var| self.reaching_def(var, from_end) != self.reaching_def(var, target_head))
+            .collect();
+        for block in [old_target, new_target] {
+            for (index, stmt) in self.body.basic_blocks[block].statements.iter().enumerate() {
+                let kind = classify_statement(&self.body, stmt);
+                let moved_sigma = block == old_target && index < sigma_count;
+                if kind == SsaStatementKind::Phi || moved_sigma {
+                    vars.push(self.original_local(assigned_local(stmt).unwrap()));
+                }
+            }
+        }
+        vars.sort();
+        vars.dedup();
+        vars
+    }
+
+    /// Splits the block of `loc` so that statement `loc` and everything after
+    /// it move to a new block, which is returned.
+    pub fn split_block(&mut self, loc: Location) -> BasicBlock {
+        let bb = loc.block;
+        let span = self.body.span;
+        let blocks = self.body.basic_blocks_mut();
+        let tail = blocks[bb].statements.split_off(loc.statement_index);
+        let terminator = blocks[bb].terminator.take();
+        let is_cleanup = blocks[bb].is_cleanup;
+        let new_bb = blocks.push(BasicBlockData {
+            statements: tail,
+            terminator,
+            is_cleanup,
+        });
+        blocks[bb].terminator = Some(Terminator {
+            source_info: SourceInfo::outermost(span),
+            kind: TerminatorKind::Goto { target: new_bb },
+        });
+        for stmt in &blocks[new_bb].statements {
+            if let Some(local) = assigned_local(stmt) {
+                self.local_defination_block.insert(local, new_bb);
+            }
+        }
+        self.reindex_uses(&[bb, new_bb]);
+
+        // `new_bb` takes over the children of `bb` in the dominator tree, and
+        // both blocks share the old dominance frontier of `bb`.
+        let children = self.dom_tree.remove(&bb).unwrap_or_default();
+        self.dom_tree.insert(new_bb, children);
+        self.dom_tree.insert(bb, vec![new_bb]);
+        let frontier = self.df.get(&bb).cloned().unwrap_or_default();
+        self.df.insert(new_bb, frontier);
+
+        // Phis in the successors now see `new_bb` as the predecessor, and no
+        // other use can see another definition.
+        let successors: HashSet<BasicBlock> = self.body.basic_blocks[new_bb]
+            .terminator()
+            .successors()
+            .collect();
+        let mut vars: Vec<Local> = self
+            .phis
+            .iter()
+            .filter(|(_, phis)| {
+                phis.iter()
+                    .any(|phi| successors.contains(&self.local_defination_block[phi]))
+            })
+            .map(|(&var, _)| var)
+            .collect();
+        vars.sort();
+        for var in vars {
+            self.rewrite_uses(var, Some(&successors));
+        }
+        new_bb
+    }
+
+    fn new_version(&mut self, var: Local) -> Local {
+        let decl = self.body.local_decls[var].clone();
+        let new_local = self.body.local_decls.push(decl);
+        self.ssa_origin.insert(new_local, var);
+        new_local
+    }
+
+    fn recompute_dominance(&mut self) {
+        self.dom_tree = SSATransformer::construct_dominance_tree(&self.body);
+        self.df = SSATransformer::compute_dominance_frontier(&self.body, &self.dom_tree);
+    }
+
+    fn def_blocks(&self, var: Local) -> HashSet<BasicBlock> {
+        self.defs
+            .get(&var)
+            .into_iter()
+            .flatten()
+            .map(|local| self.local_defination_block[local])
+            .collect()
+    }
+
+    fn phi_blocks(&self, var: Local) -> HashSet<BasicBlock> {
+        self.phis
+            .get(&var)
+            .into_iter()
+            .flatten()
+            .map(|local| self.local_defination_block[local])
+            .collect()
+    }
+
+    /// Records the definition made by the statement at `loc`, if any.
+    fn index_definition(&mut self, loc: Location) {
+        let stmt = &self.body.basic_blocks[loc.block].statements[loc.statement_index];
+        let Some(local) = assigned_local(stmt) else {
+            return;
+        };
+        let var = self.original_local(local);
+        if classify_statement(&self.body, stmt) == SsaStatementKind::Phi {
+            self.phis.entry(var).or_default().insert(local);
+        }
+        self.defs.entry(var).or_default().insert(local);
+        self.local_defination_block.insert(local, loc.block);
+    }
+
+    /// Forgets the definition of `local`, whose statement was removed.
+    fn unindex_definition(&mut self, local: Local) {
+        let var = self.original_local(local);
+        for index in [&mut self.defs, &mut self.phis] {
+            if let Some(locals) = index.get_mut(&var) {
+                locals.remove(&local);
+            }
+        }
+        self.local_defination_block.remove(&local);
+    }
+
+    /// The variables that the statements or the terminator of `block` read.
+    fn vars_used_in(&self, block: BasicBlock) -> HashSet<Local> {
+        let mut collector = UseCollector {
+            locals: HashSet::new(),
+        };
+        collector.visit_basic_block_data(block, &self.body.basic_blocks[block]);
+        collector
+            .locals
+            .into_iter()
+            .map(|local| self.original_local(local))
+            .collect()
+    }
+
+    /// Brings the uses of `blocks` in the index up to date after statements
+    /// were added to, removed from or moved between them.
+    fn reindex_uses(&mut self, blocks: &[BasicBlock]) {
+        for &block in blocks {
+            let used = self.vars_used_in(block);
+            for (var, use_blocks) in self.uses.iter_mut() {
+                if !used.contains(var) {
+                    use_blocks.remove(&block);
+                }
+            }
+            for var in used {
+                self.uses.entry(var).or_default().insert(block);
+            }
+        }
+    }
+
+    /// The iterated dominance frontier of `blocks`.
+    fn iterated_frontier(&self, blocks: &HashSet<BasicBlock>) -> HashSet<BasicBlock> {
+        let mut frontier = HashSet::new();
+        let mut worklist: Vec<BasicBlock> = blocks.iter().copied().collect();
+        while let Some(block) = worklist.pop() {
+            for &df_block in self.df.get(&block).into_iter().flatten() {
+                if frontier.insert(df_block) {
+                    worklist.push(df_block);
+                }
+            }
+        }
+        frontier
+    }
+
+    /// `blocks` and every block they dominate.
+    fn dominated_by(&self, blocks: impl IntoIterator<Item = BasicBlock>) -> HashSet<BasicBlock> {
+        let mut dominated = HashSet::new();
+        let mut worklist: Vec<BasicBlock> = blocks.into_iter().collect();
+        while let Some(block) = worklist.pop() {
+            if dominated.insert(block) {
+                worklist.extend(self.dom_tree.get(&block).into_iter().flatten());
+            }
+        }
+        dominated
+    }
+
+    /// Restores the SSA property for `var` after one of its definitions or the
+    /// CFG changed.
+    pub fn repair_variable(&mut self, var: Local) {
+        let mut def_blocks = self.def_blocks(var);
+        def_blocks.insert(START_BLOCK);
+        self.repair(var, &def_blocks, true);
+    }
+
+    /// Restores the SSA property for `var` after its definitions in `changed`
+    /// did. Missing phis are inserted on the iterated dominance frontier of
+    /// `changed`. Uses are rebound in the whole body if `whole_body`, else
+    /// only where a block of `changed` or of that frontier dominates them.
+    fn repair(&mut self, var: Local, changed: &HashSet<BasicBlock>, whole_body: bool) {
+        let frontier = self.iterated_frontier(changed);
+        let mut has_phi = self.phi_blocks(var);
+        let mut missing: Vec<BasicBlock> = frontier
+            .iter()
+            .copied()
+            .filter(|&block| has_phi.insert(block))
+            .collect();
+        missing.sort();
+        for block in missing {
+            self.insert_phi(var, block);
+        }
+        let region = if whole_body {
+            None
+        } else {
+            Some(self.dominated_by(changed.union(&frontier).copied()))
+        };
+        self.rewrite_uses(var, region.as_ref());
+        while self.fold_trivial_phi_in(var, region.as_ref()) {
+            self.rewrite_uses(var, region.as_ref());
+        }
+    }
+
+    /// Inserts a phi of `var` at the head of `block` and returns its local.
+    /// Every operand reads `var` until `rewrite_uses` binds operand `i` to the
+    /// version flowing in from predecessor `i`.
+    pub fn insert_phi(&mut self, var: Local, block: BasicBlock) -> Local {
+        let pred_count = self.body.basic_blocks.predecessors()[block].len();
+        let new_local = self.new_version(var);
+        let mut operands = IndexVec::with_capacity(pred_count);
+        for _ in 0..pred_count {
+            operands.push(Operand::Copy(Place::from(var)));
+        }
+        let phi_stmt = Statement {
+            source_info: SourceInfo::outermost(self.body.span),
+            kind: StatementKind::Assign(Box::new((
+                Place::from(new_local),
+                Rvalue::Aggregate(Box::new(AggregateKind::Tuple), operands),
+            ))),
+        };
+        self.body.basic_blocks.as_mut_preserves_cfg()[block]
+            .statements
+            .insert(0, phi_stmt);
+        self.index_definition(Location {
+            block,
+            statement_index: 0,
+        });
+        self.uses.entry(var).or_default().insert(block);
+        new_local
+    }
+
+    /// Removes one phi of `var` whose incoming values (ignoring itself) are all
+    /// the same version. Returns whether a phi was removed.
+    pub fn fold_trivial_phi(&mut self, var: Local) -> bool {
+        self.fold_trivial_phi_in(var, None)
+    }
+
+    /// Like `fold_trivial_phi`, but only looks at the phis in `region`, if
+    /// given.
+    fn fold_trivial_phi_in(&mut self, var: Local, region: Option<&HashSet<BasicBlock>>) -> bool {
+        let mut phis: Vec<(BasicBlock, Local)> = self
+            .phis
+            .get(&var)
+            .into_iter()
+            .flatten()
+            .map(|&local| (self.local_defination_block[&local], local))
+            .filter(|(block, _)| region.map_or(true, |region| region.contains(block)))
+            .collect();
+        phis.sort();
+        let trivial = phis.into_iter().find_map(|(block, phi_local)| {
+            let statements = &self.body.basic_blocks[block].statements;
+            let index = statements
+                .iter()
+                .position(|stmt| assigned_local(stmt) == Some(phi_local))?;
+            let StatementKind::Assign(box (_, Rvalue::Aggregate(_, operands))) =
+                &statements[index].kind
+            else {
+                return None;
+            };
+            let incoming: HashSet<Local> = operands
+                .iter()
+                .filter_map(|op| op.place())
+                .map(|place| place.local)
+                .filter(|&local| local != phi_local)
+                .collect();
+            (incoming.len() <= 1).then_some((
+                phi_local,
+                Location {
+                    block,
+                    statement_index: index,
+                },
+            ))
+        });
+        match trivial {
+            Some((phi_local, loc)) => {
+                self.body.basic_blocks.as_mut_preserves_cfg()[loc.block]
+                    .statements
+                    .remove(loc.statement_index);
+                self.unindex_definition(phi_local);
+                self.reindex_uses(&[loc.block]);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Rebinds every use of a version of `var` to the definition reaching it,
+    /// in the blocks of `region` only if given. Phi operands are rebuilt so
+    /// that operand `i` is the value flowing in from predecessor `i`.
+    fn rewrite_uses(&mut self, var: Local, region: Option<&HashSet<BasicBlock>>) {
+        let versions = self.versions(var);
+        let mut blocks: Vec<BasicBlock> = self
+            .uses
+            .get(&var)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|block| region.map_or(true, |region| region.contains(block)))
+            .collect();
+        blocks.sort();
+        let mut stmt_edits: Vec<(Location, Statement<'tcx>)> = Vec::new();
+        let mut term_edits: Vec<(BasicBlock, Terminator<'tcx>)> = Vec::new();
+        for bb in blocks {
+            let data = &self.body.basic_blocks[bb];
+            for (index, stmt) in data.statements.iter().enumerate() {
+                let loc = Location {
+                    block: bb,
+                    statement_index: index,
+                };
+                let mut new_stmt = stmt.clone();
+                if classify_statement(&self.body, stmt) == SsaStatementKind::Phi {
+                    let phi_local = assigned_local(stmt).unwrap();
+                    if self.original_local(phi_local) != var {
+                        continue;
+                    }
+                    let operands: IndexVec<_, _> = self.body.basic_blocks.predecessors()[bb]
+                        .iter()
+                        .map(|&pred| {
+                            let end = Location {
+                                block: pred,
+                                statement_index: usize::MAX,
+                            };
+                            Operand::Copy(Place::from(self.reaching_def(var, end)))
+                        })
+                        .collect();
+                    new_stmt.kind = StatementKind::Assign(Box::new((
+                        Place::from(phi_local),
+                        Rvalue::Aggregate(Box::new(AggregateKind::Tuple), operands),
+                    )));
+                } else {
+                    let mut renamer = UseRenamer {
+                        ssa: self,
+                        var,
+                        versions: &versions,
+                    };
+                    renamer.visit_statement(&mut new_stmt, loc);
+                }
+                if new_stmt.kind != stmt.kind {
+                    stmt_edits.push((loc, new_stmt));
+                }
+            }
+            if let Some(terminator) = &data.terminator {
+                let loc = Location {
+                    block: bb,
+                    statement_index: data.statements.len(),
+                };
+                let mut new_terminator = terminator.clone();
+                let mut renamer = UseRenamer {
+                    ssa: self,
+                    var,
+                    versions: &versions,
+                };
+                renamer.visit_terminator(&mut new_terminator, loc);
+                if new_terminator.kind != terminator.kind {
+                    term_edits.push((bb, new_terminator));
+                }
+            }
+        }
+        let blocks = self.body.basic_blocks.as_mut_preserves_cfg();
+        for (loc, stmt) in stmt_edits {
+            blocks[loc.block].statements[loc.statement_index] = stmt;
+        }
+        for (bb, terminator) in term_edits {
+            blocks[bb].terminator = Some(terminator);
+        }
+    }
+}
+
+/// Collects the locals that a statement or terminator reads.
+struct UseCollector {
+    locals: HashSet<Local>,
+}
+
+impl Visitor<'_> for UseCollector {
+    fn visit_local(&mut self, local: Local, ctxt: PlaceContext, _: Location) {
+        if let PlaceContext::NonMutatingUse(_) = ctxt {
+            self.locals.insert(local);
+        }
+    }
+}
+
+struct UseRenamer<'a, 'tcx> {
+    ssa: &'a SsaBody<'tcx>,
+    var: Local,
+    versions: &'a HashSet<Local>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for UseRenamer<'_, 'tcx> {
+    fn tcx<'b>(&'b self) -> TyCtxt<'tcx> {
+        self.ssa.tcx
+    }
+
+    fn visit_local(&mut self, local: &mut Local, ctxt: PlaceContext, loc: Location) {
+        if let PlaceContext::NonMutatingUse(_) = ctxt {
+            if self.versions.contains(local) {
+                *local = self.ssa.reaching_def(self.var, loc);
+            }
+        }
+    }
+}
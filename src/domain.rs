@@ -1,3 +1,14 @@
 pub mod ConstraintGraph;
+pub mod const_eval;
+pub mod congruence;
 pub mod domain;
+pub mod dot;
+pub mod export;
+#[cfg(feature = "float")]
+pub mod float;
+pub mod knownbits;
+pub mod lattice;
 pub mod range;
+pub mod results;
+#[cfg(feature = "smt")]
+pub mod smt;
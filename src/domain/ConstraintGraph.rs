@@ -1,35 +1,70 @@
-use super::{domain::*, range::RangeType, range::*};
+#[cfg(feature = "float")]
+use super::float::*;
+use super::{
+    congruence::Congruence,
+    const_eval::{const_range, const_value},
+    domain::*,
+    dot,
+    lattice::Lattice,
+    range::RangeType,
+    range::*,
+    results::RangeResults,
+};
+use crate::SSA::SsaBody::{classify_statement, SsaStatementKind};
 
-use num_traits::Bounded;
+use num_traits::{Bounded, PrimInt};
 use rand::Rng;
+#[cfg(feature = "float")]
+use rustc_index::IndexSlice;
 use rustc_middle::{
     mir::*,
-    ty::{self, Const, ScalarInt, TyCtxt},
+    ty::{self, TyCtxt},
 };
-use rustc_mir_transform::*;
-use rustc_span::sym::var;
+use rustc_target::abi::FieldIdx;
 
-use std::collections::{HashMap, HashSet};
-pub struct  ConstraintGraph<'tcx, T: PartialOrd + Clone + Bounded> {
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::io::{self, Write};
+#[derive(Clone)]
+pub struct  ConstraintGraph<'a, 'tcx, T: PartialOrd + Clone + Bounded> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>, // The function being analyzed
     // Protected fields
     pub vars: VarNodes<'tcx, T>, // The variables of the source program
-    pub oprs: GenOprs<'tcx, T>,  // The operations of the source program
+    pub oprs: GenOprs<T>,        // The operations of the source program
 
     // func: Option<Function>,             // Save the last Function analyzed
-    pub defmap: DefMap<'tcx, T>, // Map from variables to the operations that define them
-    pub usemap: UseMap<'tcx, T>, // Map from variables to operations where variables are used
-    pub symbmap: SymbMap<'tcx, T>, // Map from variables to operations where they appear as bounds
-    pub values_branchmap: ValuesBranchMap<'tcx, T>, // Store intervals, basic blocks, and branches
+    pub defmap: DefMap,   // Map from variables to the operations that define them
+    pub usemap: UseMap,   // Map from variables to operations where variables are used
+    pub symbmap: SymbMap, // Map from variables to operations where they appear as bounds
+    pub values_branchmap: ValuesBranchMap<T>, // Store intervals, basic blocks, and branches
     // values_switchmap: ValuesSwitchMap<'tcx, T>, // Store intervals for switch branches
-    constant_vector: Vec<T>, // Vector for constants from an SCC
+    constant_vector: Vec<T>,        // Vector for constants from an SCC
+    pub num_unsupported: usize,     // Assignments to integers that are not modelled
+    pub num_sccs: usize,            // Components solved by `find_intervals`
+    pub num_alone_sccs: usize,      // Components of a single variable
+    pub size_max_scc: usize,        // Variables in the largest component
+    pub num_widening_steps: usize,  // Evaluations of the widening phases
+    pub num_narrowing_steps: usize, // Evaluations of the narrowing phases
+    #[cfg(feature = "float")]
+    pub float_vars: FloatVarNodes<'tcx>, // The f32 and f64 variables
+    #[cfg(feature = "float")]
+    float_ids: HashMap<Place<'tcx>, FloatVarId>, // The node of each float variable
+    #[cfg(feature = "float")]
+    pub float_oprs: FloatOprs, // Arithmetic on the float variables
+    #[cfg(feature = "float")]
+    pub float_branchmap: FloatBranchMaps, // Float comparisons deciding branches
 }
 
-impl<'tcx, T> ConstraintGraph<'tcx, T>
+impl<'a, 'tcx, T> ConstraintGraph<'a, 'tcx, T>
 where
-    T: PartialOrd + Clone + Bounded + From<ScalarInt>,
+    T: PrimInt,
 {
-    pub fn new() -> Self {
+    pub fn new(tcx: TyCtxt<'tcx>, body: &'a Body<'tcx>) -> Self {
         Self {
+            tcx,
+            body,
             vars: VarNodes::new(),
             oprs: GenOprs::new(),
             // func: None,
@@ -39,6 +74,20 @@ where
             values_branchmap: ValuesBranchMap::new(),
             // values_switchmap: ValuesSwitchMap::new(),
             constant_vector: Vec::new(),
+            num_unsupported: 0,
+            num_sccs: 0,
+            num_alone_sccs: 0,
+            size_max_scc: 0,
+            num_widening_steps: 0,
+            num_narrowing_steps: 0,
+            #[cfg(feature = "float")]
+            float_vars: FloatVarNodes::new(),
+            #[cfg(feature = "float")]
+            float_ids: HashMap::new(),
+            #[cfg(feature = "float")]
+            float_oprs: FloatOprs::new(),
+            #[cfg(feature = "float")]
+            float_branchmap: FloatBranchMaps::new(),
         }
     }
     //     fn create_random_place() -> Place<'tcx> {
@@ -65,18 +114,17 @@ where
             projection: ty::List::empty(),
         }
     }
-    pub fn add_varnode(&mut self, v: &'tcx Place<'tcx>) -> &VarNode<'tcx, T> {
+    pub fn add_varnode(&mut self, v: &Place<'tcx>) -> VarId {
         // 如果变量已存在，则直接返回
 
         // 插入新的 VarNode
-        let node = VarNode::new(v);
-        // 先进行不可变借用，确保没有冲突
-        let node_ref = self.vars.entry(v).or_insert(node);
+        let ty = IntType::from_ty(self.tcx, v.ty(self.body, self.tcx).ty);
+        let id = self.vars.id_or_insert_with(*v, || VarNode::new(*v, ty));
 
         // 确保 usemap 也更新
-        self.usemap.entry(v).or_insert(HashSet::new());
+        self.usemap.entry(id).or_default();
 
-        node_ref
+        id
     }
 
     // pub fn get_oprs(&self) -> &GenOprs {
@@ -104,19 +152,21 @@ where
     //     }
     //     graph
     // }
-    pub fn build_graph(&mut self, body: &'tcx Body<'tcx>) {
-        self.build_value_maps(body);
-        print!("Building graph...\n");
+    pub fn build_graph(&mut self) {
+        let body = self.body;
+        self.build_value_maps();
         for block in body.basic_blocks.indices() {
             let block_data = &body[block];
             // Traverse statements
             for statement in block_data.statements.iter() {
-                // self.build_operations(statement);
+                self.build_operations(statement, block);
             }
         }
+        self.build_symbolic_intersect_map();
     }
 
-    pub fn build_value_maps(&mut self, body: &'tcx Body<'tcx>) {
+    pub fn build_value_maps(&mut self) {
+        let body = self.body;
         for bb in body.basic_blocks.indices() {
             let block_data = &body[bb];
             if let Some(terminator) = &block_data.terminator {
@@ -141,16 +191,23 @@ where
     pub fn build_value_branch_map(
         &mut self,
         body: &Body<'tcx>,
-        discr: &'tcx Operand<'tcx>,
-        targets: &'tcx SwitchTargets,
-        block: &'tcx BasicBlockData<'tcx>,
+        discr: &'a Operand<'tcx>,
+        targets: &'a SwitchTargets,
+        block: &'a BasicBlockData<'tcx>,
     ) {
         // let place1: &Place<'tcx>;
         // 确保分支条件是二元比较
         if let Operand::Copy(place) | Operand::Move(place) = discr {
             if let Some((op1, op2, cmp_op)) = self.extract_condition(place, block) {
+                #[cfg(feature = "float")]
+                if self.build_float_branch_map(op1, op2, cmp_op, targets) {
+                    return;
+                }
                 let const_op1 = op1.constant();
                 let const_op2 = op2.constant();
+                // The condition is a `bool`: 0 is false, anything else is true.
+                let bb_false = targets.target_for_value(0);
+                let bb_true = targets.otherwise();
 
                 match (const_op1, const_op2) {
                     (Some(c1), Some(c2)) => {}
@@ -171,10 +228,11 @@ where
                             };
                         }
                         // 此处应根据T进行选取，设定为scalarInt
-                        self.add_varnode(variable);
-                        print!("{:?}\n", variable);
-                        let scalar = c.const_.try_to_scalar_int().unwrap();
-                        let scalar_value: T = scalar.into();
+                        let variable = self.add_varnode(variable);
+                        let Some(scalar_value) = const_value::<T>(self.tcx, c) else {
+                            // Unevaluated or generic constants leave the variable at top.
+                            return;
+                        };
 
                         let const_range = Range::new(
                             scalar_value.clone(),
@@ -182,27 +240,26 @@ where
                             RangeType::Regular,
                         );
 
-                        let true_range = self.apply_comparison(
+                        let true_range = Self::apply_comparison(
                             scalar_value.clone(),
                             cmp_op,
                             true,
                             const_in_left,
                         );
-                        let false_range = self.apply_comparison(
+                        let false_range = Self::apply_comparison(
                             scalar_value.clone(),
                             cmp_op,
                             false,
                             const_in_left,
                         );
-                        let target_vec = targets.all_targets();
                         let vbm = ValueBranchMap::new(
                             variable,
-                            &target_vec[0],
-                            &target_vec[1],
+                            bb_true,
+                            bb_false,
                             IntervalType::Basic(BasicInterval::new(true_range)),
                             IntervalType::Basic(BasicInterval::new(false_range)),
                         );
-                        self.values_branchmap.insert(place, vbm);
+                        self.values_branchmap.insert(variable, vbm);
                     }
                     (None, None) => {
                         // 两个变量之间的比较
@@ -217,17 +274,26 @@ where
                             Operand::Copy(p) | Operand::Move(p) => p,
                             _ => panic!("Expected a place"),
                         };
-                        let target_vec = targets.all_targets();
-                        self.add_varnode(p1);
-                        self.add_varnode(p2);
-                        let STOp1 = IntervalType::Symb(SymbInterval::new(CR.clone(), &p2, true));
-                        let SFOp1 = IntervalType::Symb(SymbInterval::new(CR.clone(), &p2, false));
-                        let STOp2 = IntervalType::Symb(SymbInterval::new(CR.clone(), &p1, true));
-                        let SFOp2 = IntervalType::Symb(SymbInterval::new(CR.clone(), &p1, false));
-                        let vbm_1 =
-                            ValueBranchMap::new(p1, &target_vec[0], &target_vec[1], STOp1, SFOp1);
-                        let vbm_2 =
-                            ValueBranchMap::new(p2, &target_vec[0], &target_vec[1], STOp2, SFOp2);
+                        let (p1, p2) = (self.add_varnode(p1), self.add_varnode(p2));
+                        // `p1 op p2` bounds p1 by p2 and, read the other way
+                        // round, p2 by p1.
+                        let swapped = swap_predicate(cmp_op);
+                        let STOp1 =
+                            IntervalType::Symb(SymbInterval::new(CR.clone(), p2, cmp_op));
+                        let SFOp1 = IntervalType::Symb(SymbInterval::new(
+                            CR.clone(),
+                            p2,
+                            negate_predicate(cmp_op),
+                        ));
+                        let STOp2 =
+                            IntervalType::Symb(SymbInterval::new(CR.clone(), p1, swapped));
+                        let SFOp2 = IntervalType::Symb(SymbInterval::new(
+                            CR.clone(),
+                            p1,
+                            negate_predicate(swapped),
+                        ));
+                        let vbm_1 = ValueBranchMap::new(p1, bb_true, bb_false, STOp1, SFOp1);
+                        let vbm_2 = ValueBranchMap::new(p2, bb_true, bb_false, STOp2, SFOp2);
                         self.values_branchmap.insert(p1, vbm_1);
                         self.values_branchmap.insert(p2, vbm_2);
                     }
@@ -236,21 +302,165 @@ where
         }
     }
 
+    /// Records a comparison of floats, from the point of view of each
+    /// variable it compares. Returns false if the operands are not floats.
+    #[cfg(feature = "float")]
+    fn build_float_branch_map(
+        &mut self,
+        op1: &Operand<'tcx>,
+        op2: &Operand<'tcx>,
+        cmp_op: BinOp,
+        targets: &SwitchTargets,
+    ) -> bool {
+        let Some(ty) = FloatType::from_ty(op1.ty(self.body, self.tcx)) else {
+            return false;
+        };
+        let (Some(lhs), Some(rhs)) = (self.float_operand(op1, ty), self.float_operand(op2, ty))
+        else {
+            return true;
+        };
+        // The condition is a `bool`: 0 is false, anything else is true.
+        let bb_false = targets.target_for_value(0);
+        let bb_true = targets.otherwise();
+        for (v, bound, op) in [(lhs, rhs, cmp_op), (rhs, lhs, swap_predicate(cmp_op))] {
+            let FloatOperand::Var(v) = v else {
+                continue;
+            };
+            self.float_branchmap
+                .entry(v)
+                .or_default()
+                .push(FloatBranchMap {
+                    v,
+                    bb_true,
+                    bb_false,
+                    op,
+                    bound,
+                    ty,
+                });
+        }
+        true
+    }
+
+    #[cfg(feature = "float")]
+    fn add_float_varnode(&mut self, v: &Place<'tcx>, ty: FloatType) -> FloatVarId {
+        let float_vars = &mut self.float_vars;
+        *self
+            .float_ids
+            .entry(*v)
+            .or_insert_with(|| float_vars.push(FloatVarNode::new(*v, ty)))
+    }
+
+    /// The operand of a float operation reading `operand`, adding a node for
+    /// a variable, or `None` for constants that cannot be evaluated.
+    #[cfg(feature = "float")]
+    fn float_operand(&mut self, operand: &Operand<'tcx>, ty: FloatType) -> Option<FloatOperand> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => {
+                Some(FloatOperand::Var(self.add_float_varnode(place, ty)))
+            }
+            Operand::Constant(c) => const_float(self.tcx, c).map(FloatOperand::Const),
+        }
+    }
+
+    /// The variables among `operands`, adding a node for each.
+    #[cfg(feature = "float")]
+    fn float_places(
+        &mut self,
+        operands: &IndexSlice<FieldIdx, Operand<'tcx>>,
+        ty: FloatType,
+    ) -> Vec<FloatVarId> {
+        operands
+            .iter()
+            .filter_map(|operand| operand.place())
+            .map(|place| self.add_float_varnode(&place, ty))
+            .collect()
+    }
+
+    /// Records the operation defining a float variable. Returns false if the
+    /// statement does not assign a float. Assignments the analysis cannot
+    /// model leave their sink without a definition, at the full range.
+    #[cfg(feature = "float")]
+    fn add_float_op(&mut self, inst: &Statement<'tcx>, block: BasicBlock) -> bool {
+        let StatementKind::Assign(box (sink, rvalue)) = &inst.kind else {
+            return false;
+        };
+        let Some(ty) = FloatType::from_ty(sink.ty(self.body, self.tcx).ty) else {
+            return false;
+        };
+        let sink = self.add_float_varnode(sink, ty);
+        let kind = match (classify_statement(self.body, inst), rvalue) {
+            (SsaStatementKind::Phi, Rvalue::Aggregate(_, operands)) => {
+                Some(FloatOpKind::Phi(self.float_places(operands, ty)))
+            }
+            // The first operand of a sigma is the variable the branch into
+            // `block` compares.
+            (SsaStatementKind::Sigma, Rvalue::Aggregate(_, operands)) => {
+                self.float_places(operands, ty).first().map(|&source| {
+                    self.float_branchmap
+                        .get(&source)
+                        .into_iter()
+                        .flatten()
+                        .find(|map| (map.bb_true == block) != (map.bb_false == block))
+                        .map_or(FloatOpKind::Copy(FloatOperand::Var(source)), |map| {
+                            FloatOpKind::Sigma {
+                                source,
+                                op: map.op,
+                                bound: map.bound,
+                                holds: map.bb_true == block,
+                            }
+                        })
+                })
+            }
+            (_, Rvalue::Use(operand)) => self.float_operand(operand, ty).map(FloatOpKind::Copy),
+            (
+                _,
+                Rvalue::BinaryOp(
+                    op @ (BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div),
+                    box (op1, op2),
+                ),
+            ) => match (self.float_operand(op1, ty), self.float_operand(op2, ty)) {
+                (Some(lhs), Some(rhs)) => Some(FloatOpKind::Binary { op: *op, lhs, rhs }),
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some(kind) = kind else {
+            return true;
+        };
+        self.float_oprs.push(FloatOp { sink, kind, ty });
+        true
+    }
+
     fn extract_condition(
         &self,
-        place: &'tcx Place<'tcx>,
-        block: &'tcx BasicBlockData<'tcx>,
-    ) -> Option<(&'tcx Operand<'tcx>, &'tcx Operand<'tcx>, BinOp)> {
+        place: &Place<'tcx>,
+        block: &'a BasicBlockData<'tcx>,
+    ) -> Option<(&'a Operand<'tcx>, &'a Operand<'tcx>, BinOp)> {
         for stmt in &block.statements {
             if let StatementKind::Assign(box (lhs, Rvalue::BinaryOp(bin_op, box (op1, op2)))) =
                 &stmt.kind
             {
                 if lhs == place {
-                    print!("!!!!!!");
-                    print!("{:?}\n", bin_op);
-                    print!("{:?}\n", op1);
-                    print!("{:?}\n", op2);
-                    return Some((op1, op2, *bin_op));
+                    // Like `Replacer`, compare the variables a temporary
+                    // copies, which are what the sigmas refine.
+                    let resolve = |operand: &'a Operand<'tcx>| {
+                        let Some(copied) = operand.place() else {
+                            return operand;
+                        };
+                        block
+                            .statements
+                            .iter()
+                            .find_map(|stmt| match &stmt.kind {
+                                StatementKind::Assign(box (lhs, Rvalue::Use(source)))
+                                    if *lhs == copied =>
+                                {
+                                    Some(source)
+                                }
+                                _ => None,
+                            })
+                            .unwrap_or(operand)
+                    };
+                    return Some((resolve(op1), resolve(op2), *bin_op));
                 }
             }
         }
@@ -296,209 +506,845 @@ where
     //     }
     // }
 
-    fn apply_comparison<U: PartialOrd + Clone + Bounded>(
-        &self,
-        constant: U,
+    /// The range of a variable compared with `constant` on the `true` or
+    /// `false` edge of the branch, with the constant on the left of `cmp_op`
+    /// if `const_in_left`.
+    pub fn apply_comparison(
+        constant: T,
         cmp_op: BinOp,
         is_true_branch: bool,
         const_in_left: bool,
-    ) -> Range<U> {
-        match cmp_op {
-            BinOp::Lt => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant, RangeType::Regular)
-                } else {
-                    Range::new(constant, U::max_value(), RangeType::Regular)
+    ) -> Range<T> {
+        // Rewrite the condition as `variable op constant`.
+        let op = if const_in_left {
+            swap_predicate(cmp_op)
+        } else {
+            cmp_op
+        };
+        let op = if is_true_branch {
+            op
+        } else {
+            negate_predicate(op)
+        };
+        let constant = Range::new(constant, constant, RangeType::Regular);
+        predicate_range(op, &constant)
+    }
+
+    fn build_value_goto_map(&self, block_index: BasicBlock, target: BasicBlock) {
+        println!(
+            "Building value map for Goto in block {:?} targeting block {:?}",
+            block_index, target
+        );
+        // 在这里实现具体的 Goto 处理逻辑
+    }
+    /// Collects the constants of an SCC into `constant_vector`, sorted and
+    /// without duplicates, for jump-set widening: the bounds of the branch
+    /// conditions on its variables, the literal operands of the statements
+    /// that define them, and the lengths of the arrays they touch.
+    pub fn build_constant_vector(&mut self, component: &HashSet<VarId>) {
+        let (tcx, body) = (self.tcx, self.body);
+        let mut constants = Vec::new();
+        for (var, vbm) in self.values_branchmap.iter() {
+            if !component.contains(var) {
+                continue;
+            }
+            for itv in [vbm.get_itv_t(), vbm.get_itv_f()] {
+                if let IntervalType::Basic(basic) = itv {
+                    let range = basic.get_range();
+                    if range.is_regular() {
+                        constants.push(range.get_lower());
+                        constants.push(range.get_upper());
+                    }
                 }
             }
-
-            BinOp::Le => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant, RangeType::Regular)
-                } else {
-                    Range::new(constant, U::max_value(), RangeType::Regular)
+        }
+        for block_data in body.basic_blocks.iter() {
+            for statement in block_data.statements.iter() {
+                if let StatementKind::Assign(box (place, rvalue)) = &statement.kind {
+                    let id = self.vars.id_of(place);
+                    if id.is_some_and(|id| component.contains(&id)) {
+                        Self::collect_rvalue_constants(tcx, body, rvalue, &mut constants);
+                    }
                 }
             }
+        }
+        // The ends of `T` are where plain widening jumps anyway.
+        constants.retain(|c| *c != T::min_value() && *c != T::max_value());
+        constants.sort();
+        constants.dedup();
+        self.constant_vector = constants;
+    }
 
-            BinOp::Gt => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant, RangeType::Regular)
-                } else {
-                    Range::new(constant, U::max_value(), RangeType::Regular)
+    fn collect_rvalue_constants(
+        tcx: TyCtxt<'tcx>,
+        body: &Body<'tcx>,
+        rvalue: &Rvalue<'tcx>,
+        constants: &mut Vec<T>,
+    ) {
+        let operands: Vec<&Operand<'tcx>> = match rvalue {
+            Rvalue::Use(op) | Rvalue::Repeat(op, _) | Rvalue::Cast(_, op, _) => vec![op],
+            Rvalue::UnaryOp(_, op) => vec![op],
+            Rvalue::BinaryOp(_, box (op1, op2)) => vec![op1, op2],
+            Rvalue::Aggregate(_, operands) => operands.iter().collect(),
+            _ => Vec::new(),
+        };
+        for op in operands {
+            match op {
+                Operand::Constant(c) => {
+                    if let Some(value) = const_value(tcx, c) {
+                        constants.push(value);
+                    }
+                }
+                Operand::Copy(p) | Operand::Move(p) => {
+                    Self::collect_array_length(tcx, p.ty(body, tcx).ty, constants);
                 }
             }
-
-            BinOp::Ge => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant, RangeType::Regular)
-                } else {
-                    Range::new(constant, U::max_value(), RangeType::Regular)
+        }
+        match rvalue {
+            Rvalue::Repeat(_, len) => {
+                if let Some(len) = len.try_to_target_usize(tcx) {
+                    Self::push_usize(len, constants);
                 }
             }
+            Rvalue::Len(p) | Rvalue::Ref(_, _, p) => {
+                Self::collect_array_length(tcx, p.ty(body, tcx).ty, constants);
+            }
+            Rvalue::Aggregate(box AggregateKind::Array(_), operands) => {
+                Self::push_usize(operands.len() as u64, constants);
+            }
+            _ => {}
+        }
+    }
 
-            BinOp::Eq => {
-                if is_true_branch ^ const_in_left {
-                    Range::new(U::min_value(), constant, RangeType::Regular)
-                } else {
-                    Range::new(constant, U::max_value(), RangeType::Regular)
-                }
+    fn collect_array_length(tcx: TyCtxt<'tcx>, ty: ty::Ty<'tcx>, constants: &mut Vec<T>) {
+        let ty = match ty.kind() {
+            ty::Ref(_, inner, _) | ty::RawPtr(inner, _) => *inner,
+            _ => ty,
+        };
+        if let ty::Array(_, len) = ty.kind() {
+            if let Some(len) = len.try_to_target_usize(tcx) {
+                Self::push_usize(len, constants);
             }
+        }
+    }
 
-            _ => Range::new(constant.clone(), constant.clone(), RangeType::Empty),
+    fn push_usize(value: u64, constants: &mut Vec<T>) {
+        if let Some(value) = T::from(value) {
+            constants.push(value);
         }
     }
 
-    fn build_value_goto_map(&self, block_index: BasicBlock, target: BasicBlock) {
-        println!(
-            "Building value map for Goto in block {:?} targeting block {:?}",
-            block_index, target
-        );
-        // 在这里实现具体的 Goto 处理逻辑
+    /// Widens `old` towards `new` using the constants of the current SCC.
+    pub fn widen(&self, old: &Range<T>, new: &Range<T>) -> Range<T> {
+        old.widen_with_thresholds(new, &self.constant_vector)
     }
+
     pub fn build_varnodes(&mut self) {
         // Builds VarNodes
-        for (name, node) in self.vars.iter_mut() {
-            let is_undefined = !self.defmap.contains_key(name);
+        for (id, node) in self.vars.iter_mut() {
+            let is_undefined = !self.defmap.contains_key(&id);
             node.init(is_undefined);
         }
     }
-    pub fn build_operations(&mut self, inst: &'tcx Statement<'tcx>) {
-        // Handle binary instructions
-        if let StatementKind::Assign(box (place, rvalue)) = &inst.kind {
-            match rvalue {
-                Rvalue::BinaryOp(op, box (op1, op2)) => {
-                    let p1 = match op1 {
-                        Operand::Copy(p) | Operand::Move(p) => p,
-                        _ => panic!("Expected a place"),
-                    };
-
-                    self.add_varnode(p1);
-                    let p2 = match op2 {
-                        Operand::Copy(p) | Operand::Move(p) => p,
-                        _ => panic!("Expected a place"),
-                    };
-                    self.add_varnode(p1);
-                    self.add_varnode(p2);
-                    // self.add_varnode(place);
-                    self.add_binary_op(inst);
+    /// Adds the operation defining the integer variable an assignment of
+    /// `block` writes. Assignments the analysis cannot model leave their sink
+    /// without a definition, so it starts at the full range of its type, and
+    /// are counted in `num_unsupported`.
+    pub fn build_operations(&mut self, inst: &Statement<'tcx>, block: BasicBlock) {
+        #[cfg(feature = "float")]
+        if self.add_float_op(inst, block) {
+            return;
+        }
+        match classify_statement(self.body, inst) {
+            SsaStatementKind::Phi => {
+                self.add_phi_op(inst);
+                return;
+            }
+            SsaStatementKind::Sigma => {
+                self.add_sigma_op(inst, block);
+                return;
+            }
+            SsaStatementKind::Plain => {}
+        }
+        let StatementKind::Assign(box (place, rvalue)) = &inst.kind else {
+            return;
+        };
+        let (tcx, body) = (self.tcx, self.body);
+        // A checked operation assigns its result together with an overflow
+        // flag; the result is field 0.
+        let sink = match rvalue {
+            Rvalue::BinaryOp(
+                BinOp::AddWithOverflow | BinOp::SubWithOverflow | BinOp::MulWithOverflow,
+                box (lhs, _),
+            ) => tcx.mk_place_field(*place, FieldIdx::ZERO, lhs.ty(body, tcx)),
+            _ => *place,
+        };
+        let Some(sink_ty) = IntType::from_ty(tcx, sink.ty(body, tcx).ty) else {
+            return;
+        };
+        let sink = self.add_varnode(&sink);
+        let unary =
+            |source: OpSource<T>, kind| Some(BasicOpKind::Unary(UnaryOp::new(sink, source, kind)));
+        let constant = |range: Range<T>| unary(OpSource::Const(range), UnaryOpKind::Copy);
+        let op = match rvalue {
+            Rvalue::Use(operand) => unary(self.add_source(operand), UnaryOpKind::Copy),
+            Rvalue::CopyForDeref(source) => {
+                unary(OpSource::Var(self.add_varnode(source)), UnaryOpKind::Copy)
+            }
+            Rvalue::Cast(CastKind::IntToInt, operand, _) => {
+                unary(self.add_source(operand), UnaryOpKind::Cast(sink_ty))
+            }
+            Rvalue::UnaryOp(UnOp::Neg, operand) => {
+                unary(self.add_source(operand), UnaryOpKind::Neg)
+            }
+            Rvalue::UnaryOp(UnOp::Not, operand) => {
+                unary(self.add_source(operand), UnaryOpKind::Not)
+            }
+            Rvalue::UnaryOp(UnOp::PtrMetadata, _) => constant(self.size_range()),
+            Rvalue::BinaryOp(op, box (lhs, rhs)) => {
+                match IntType::from_ty(tcx, lhs.ty(body, tcx)) {
+                    Some(ty) => {
+                        let (lhs, rhs) = (self.add_source(lhs), self.add_source(rhs));
+                        Some(BasicOpKind::Binary(BinaryOp::new(sink, lhs, rhs, *op, ty)))
+                    }
+                    // Comparisons of floats and pointers.
+                    None => None,
                 }
-                Rvalue::UnaryOp(op, op1) => {
-                    let p = match op1 {
-                        Operand::Copy(p) | Operand::Move(p) => p,
-                        _ => panic!("Expected a place"),
-                    };
+            }
+            Rvalue::Len(source) => constant(self.length_range(source)),
+            Rvalue::Discriminant(source) => constant(self.discriminant_range(source, sink_ty)),
+            Rvalue::NullaryOp(op, ty) => constant(self.nullary_range(op, *ty)),
+            // Other casts, reads of pointers and thread locals. `Aggregate`
+            // builds integers only as phis and sigmas.
+            _ => None,
+        };
+        match op {
+            Some(op) => {
+                self.add_operation(op);
+            }
+            None => self.num_unsupported += 1,
+        }
+    }
+
+    /// The source of an operation reading `operand`, adding a node for a
+    /// variable.
+    fn add_source(&mut self, operand: &Operand<'tcx>) -> OpSource<T> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => OpSource::Var(self.add_varnode(place)),
+            Operand::Constant(c) => OpSource::Const(const_range(self.tcx, c)),
+        }
+    }
 
-                    self.add_varnode(p);
-                    // self.add_varnode(place);
-                    self.add_unary_op(inst);
+    /// The range of a size or a length, which is at most `isize::MAX`.
+    fn size_range(&self) -> Range<T> {
+        let isize_ty = IntType::from_ty(self.tcx, self.tcx.types.isize).unwrap();
+        Range::new(T::zero(), isize_ty.max_value(), RangeType::Regular)
+    }
+
+    /// The length of the array or slice `place`.
+    fn length_range(&self, place: &Place<'tcx>) -> Range<T> {
+        if let ty::Array(_, len) = place.ty(self.body, self.tcx).ty.kind() {
+            if let Some(len) = len.try_to_target_usize(self.tcx).and_then(T::from) {
+                return Range::new(len, len, RangeType::Regular);
+            }
+        }
+        self.size_range()
+    }
+
+    /// The hull of the discriminants of the variants of `place`, of type `ty`.
+    fn discriminant_range(&self, place: &Place<'tcx>, ty: IntType) -> Range<T> {
+        match place.ty(self.body, self.tcx).ty.kind() {
+            ty::Adt(def, _) if def.is_enum() => {
+                let values: Vec<T> = def
+                    .discriminants(self.tcx)
+                    .filter_map(|(_, discr)| ty.value_from_bits(discr.val))
+                    .collect();
+                match (values.iter().min(), values.iter().max()) {
+                    (Some(&lb), Some(&ub)) if values.len() == def.variants().len() => {
+                        Range::new(lb, ub, RangeType::Regular)
+                    }
+                    _ => ty.full_range(),
                 }
-                _ => {}
             }
+            ty::Coroutine(..) => ty.full_range(),
+            // Every other type has the single discriminant 0.
+            _ => Range::new(T::zero(), T::zero(), RangeType::Regular),
         }
     }
-    fn add_unary_op(&mut self, stmt: &'tcx Statement<'tcx>) {
-        let rand_place: Place<'tcx> = Self::create_random_place();
-        let stmt_varnode = self.add_varnode(Box::leak(Box::new(rand_place)));
+
+    fn nullary_range(&self, op: &NullOp<'tcx>, ty: ty::Ty<'tcx>) -> Range<T> {
+        let layout = || self.tcx.layout_of(ty::ParamEnv::reveal_all().and(ty)).ok();
+        let value = match op {
+            NullOp::SizeOf => layout().map(|layout| layout.size.bytes()),
+            NullOp::AlignOf => layout().map(|layout| layout.align.abi.bytes()),
+            NullOp::OffsetOf(_) => None,
+            NullOp::UbChecks => return Range::new(T::zero(), T::one(), RangeType::Regular),
+        };
+        match value.and_then(T::from) {
+            Some(value) => Range::new(value, value, RangeType::Regular),
+            // The layout of a generic type is not known.
+            None => self.size_range(),
+        }
     }
 
-    fn add_binary_op(&mut self, inst: &'tcx Statement<'tcx>) {
-        // Implementation for adding binary operation
-        // ...
+    /// Stores an operation and records it as the definition of its sink and
+    /// a use of each of its sources.
+    fn add_operation(&mut self, op: BasicOpKind<T>) -> OpId {
+        let (sink, sources) = (op.get_sink(), op.get_sources());
+        let id = self.oprs.push(op);
+        self.defmap.insert(sink, id);
+        for source in sources {
+            self.usemap.entry(source).or_default().insert(id);
+        }
+        id
     }
 
-    // fn add_phi_op(&mut self, phi: &'tcx PHINode<'tcx>) {
-    //     // Implementation for adding phi operation
-    //     // ...
-    // }
+    /// The places an aggregate of the e-SSA form reads and the integer
+    /// variable it defines, or `None` if the variable is not an integer.
+    fn ssa_operands<'s>(
+        &self,
+        inst: &'s Statement<'tcx>,
+    ) -> Option<(&'s Place<'tcx>, Vec<&'s Place<'tcx>>)> {
+        let StatementKind::Assign(box (sink, Rvalue::Aggregate(_, operands))) = &inst.kind else {
+            return None;
+        };
+        IntType::from_ty(self.tcx, sink.ty(self.body, self.tcx).ty)?;
+        let places = operands
+            .iter()
+            .filter_map(|operand| match operand {
+                Operand::Copy(place) | Operand::Move(place) => Some(place),
+                Operand::Constant(_) => None,
+            })
+            .collect();
+        Some((sink, places))
+    }
 
-    // fn add_sigma_op(&mut self, phi: &'tcx PHINode<'tcx>) {
-    //     // Implementation for adding sigma operation
-    //     // ...
-    // }
-    // pub fn find_intervals(&mut self) {
-    //     // 构建符号交集映射
-    //     self.build_symbolic_intersect_map();
-
-    //     // 查找强连通分量（SCC）
-    //     let scc_list = Nuutila::new(&self.vars, &self.usemap, &self.symbmap);
-    //     self.num_sccs += scc_list.worklist.len();
-
-    //     // 遍历每个 SCC
-    //     for component in scc_list.components() {
-    //         if component.len() == 1 {
-    //             // 处理单节点的 SCC
-    //             self.num_alone_sccs += 1;
-    //             self.fix_intersects(&component);
-
-    //             let var = component.iter().next().unwrap();
-    //             if var.get_range().is_unknown() {
-    //                 var.set_range(allue {
-    //                     min: i32::MIN,
-    //                     max: i32::MAX,
-    //                 });
-    //             }
-    //         } else {
-    //             // 更新最大 SCC 大小
-    //             if component.len() > self.size_max_scc {
-    //                 self.size_max_scc = component.len();
-    //             }
+    /// Adds `sink = φ(sources)` for a phi, whose operands are the versions
+    /// reaching it from each predecessor.
+    fn add_phi_op(&mut self, inst: &Statement<'tcx>) {
+        let Some((sink, sources)) = self.ssa_operands(inst) else {
+            return;
+        };
+        let sink = self.add_varnode(sink);
+        let sources = sources
+            .into_iter()
+            .map(|source| self.add_varnode(source))
+            .collect();
+        self.add_operation(BasicOpKind::Phi(PhiOp::new(sink, sources)));
+    }
 
-    //             // 为该 SCC 构建使用映射
-    //             let comp_use_map = self.build_use_map(&component);
-
-    //             // 获取 SCC 的入口点
-    //             let mut entry_points = HashSet::new();
-    //             self.generate_entry_points(&component, &mut entry_points);
-
-    //             // 固定点迭代，更新范围
-    //             self.pre_update(&comp_use_map, &entry_points);
-    //             self.fix_intersects(&component);
-
-    //             // 为未知范围的变量设置默认范围
-    //             for var in &component {
-    //                 if var.get_range().is_unknown() {
-    //                     var.set_range(Range {
-    //                         min: i32::MIN,
-    //                         max: i32::MAX,
-    //                     });
-    //                 }
-    //             }
+    /// Adds `sink = source ∩ itv` for a sigma of `block`, whose first operand
+    /// is the variable compared by the branch into `block`. The other
+    /// operands only encode the comparison, which `values_branchmap` already
+    /// holds as the interval `itv`.
+    fn add_sigma_op(&mut self, inst: &Statement<'tcx>, block: BasicBlock) {
+        let Some((sink, places)) = self.ssa_operands(inst) else {
+            return;
+        };
+        let Some(&source) = places.first() else {
+            return;
+        };
+        let (sink, source) = (self.add_varnode(sink), self.add_varnode(source));
+        // A sigma for an edge the branch map does not know, as for a
+        // comparison with an unevaluated constant, refines nothing.
+        let intersect = self
+            .values_branchmap
+            .get(&source)
+            .and_then(|vbm| vbm.get_itv_to(block))
+            .cloned()
+            .unwrap_or(IntervalType::Basic(BasicInterval::new(Range::default())));
+        let op = SigmaOp::new(sink, source, intersect);
+        self.add_operation(BasicOpKind::Sigma(op));
+    }
 
-    //             // 二次迭代，更新活动变量
-    //             let mut active_vars = HashSet::new();
-    //             self.generate_active_vars(&component, &mut active_vars);
-    //             self.pos_update(&comp_use_map, &active_vars, &component);
-    //         }
+    /// Computes the range of every variable and leaves it in its node. The
+    /// components of the graph are solved in topological order, see
+    /// `Solver`, so each one starts from the final ranges of its inputs.
+    pub fn find_intervals(&mut self) {
+        self.build_varnodes();
+        let components = self.find_sccs();
+        self.num_sccs += components.len();
+        for component in &components {
+            self.size_max_scc = self.size_max_scc.max(component.len());
+            if component.len() == 1 {
+                self.num_alone_sccs += 1;
+                // A lone variable is evaluated once unless it uses itself,
+                // which is not worth a pass over the body for constants.
+                self.constant_vector.clear();
+            } else {
+                self.build_constant_vector(component);
+            }
+            let mut solver = self.solver();
+            solver.solve(component);
+            let (widening, narrowing) = (solver.widening_steps, solver.narrowing_steps);
+            self.num_widening_steps += widening;
+            self.num_narrowing_steps += narrowing;
+        }
+        #[cfg(feature = "float")]
+        self.find_float_intervals();
+    }
 
-    //         // 将结果传播到下一个 SCC
-    //         self.propagate_to_next_scc(&component);
-    //     }
-    // }
+    /// Computes the range of every float variable, solving the components of
+    /// the float operations in topological order as `find_intervals` does.
+    #[cfg(feature = "float")]
+    fn find_float_intervals(&mut self) {
+        let mut defs: HashMap<FloatVarId, usize> = HashMap::new();
+        let mut users: HashMap<FloatVarId, Vec<usize>> = HashMap::new();
+        for (id, op) in self.float_oprs.iter().enumerate() {
+            defs.insert(op.sink, id);
+            for source in op.sources() {
+                users.entry(source).or_default().push(id);
+            }
+        }
+        // Defined variables start empty and grow; the others keep the full
+        // range.
+        for (id, node) in self.float_vars.iter_enumerated_mut() {
+            if defs.contains_key(&id) {
+                node.set_range(FloatRange::bottom());
+            }
+        }
+        let oprs = &self.float_oprs;
+        let successors = |v: FloatVarId| -> Vec<FloatVarId> {
+            users
+                .get(&v)
+                .into_iter()
+                .flatten()
+                .map(|&id| oprs[id].sink)
+                .collect()
+        };
+        let starts = self.float_vars.indices().collect();
+        let components = Nuutila::with_successors(starts, successors).into_components();
+        for component in &components {
+            let ops: BTreeSet<usize> = component
+                .iter()
+                .filter_map(|v| defs.get(v))
+                .copied()
+                .collect();
+            solve_float_component(&mut self.float_vars, &self.float_oprs, &users, &ops);
+        }
+    }
+
+    /// Writes the graph in the DOT language of Graphviz, see `dot::write_dot`.
+    pub fn print_dot(&self, os: &mut dyn Write, name: &str) -> io::Result<()>
+    where
+        T: fmt::Display,
+    {
+        let components = self.find_sccs();
+        dot::write_dot(os, name, &self.vars, &self.oprs, &self.symbmap, &components)
+    }
+
+    /// The ranges found by `find_intervals`, to be queried by location.
+    /// `ssa_origin` maps every renamed local to the local it was split from,
+    /// as returned by `PassRunner::run_pass`.
+    pub fn results(&self, ssa_origin: HashMap<Local, Local>) -> RangeResults<'a, 'tcx, T> {
+        let ranges = self
+            .vars
+            .iter()
+            .map(|(_, node)| (*node.get_value(), node.get_range().clone()))
+            .collect();
+        RangeResults::new(self.tcx, self.body, ssa_origin, ranges)
+    }
+
+    /// A solver of the component `constant_vector` was built for.
+    fn solver(&mut self) -> Solver<'_, 'tcx, T> {
+        Solver::new(
+            &mut self.vars,
+            &mut self.oprs,
+            &self.defmap,
+            &self.usemap,
+            &self.symbmap,
+            &self.constant_vector,
+        )
+    }
+
+    /// The strongly connected components of the variables, in topological
+    /// order.
+    pub fn find_sccs(&self) -> Vec<HashSet<VarId>> {
+        Nuutila::new(&self.vars, &self.oprs, &self.usemap, &self.symbmap).into_components()
+    }
+
+    /// Narrows the sink of an operation with the crop meet, see
+    /// `VarNode::crop`. Returns whether the sink changed.
+    pub fn crop(&mut self, id: OpId) -> bool {
+        self.solver().crop(id)
+    }
+
+    /// Records, for every variable used as a symbolic bound, the operations
+    /// whose intersection depends on it.
+    pub fn build_symbolic_intersect_map(&mut self) {
+        for (id, op) in self.oprs.iter_enumerated() {
+            if let IntervalType::Symb(symb) = op.get_intersect() {
+                self.symbmap.entry(symb.get_bound()).or_default().insert(id);
+            }
+        }
+    }
+
+    /// Future resolution, see `Solver::fix_intersects`.
+    pub fn fix_intersects(&mut self, component: &HashSet<VarId>) {
+        self.solver().fix_intersects(component);
+    }
+}
+
+impl<T> ConstraintGraph<'_, '_, T>
+where
+    T: PrimInt + fmt::Display,
+{
+    /// Prints every variable with its index and range, ordered by local, and
+    /// then every operation in the order it was added.
+    pub fn dump(&self, out: &mut dyn Write) -> io::Result<()> {
+        let mut vars: Vec<_> = self.vars.iter().collect();
+        vars.sort_by_cached_key(|(_, node)| {
+            let place = node.get_value();
+            (place.local, format!("{:?}", place))
+        });
+        writeln!(out, "vars:")?;
+        for (id, node) in vars {
+            writeln!(out, "  {:?} {}", id, node)?;
+        }
+        writeln!(out, "ops:")?;
+        for op in self.oprs.iter() {
+            writeln!(out, "  {}", op)?;
+        }
+        Ok(())
+    }
+}
+
+/// The strongly connected components of the variables of a constraint
+/// graph, found with Nuutila's variant of Tarjan's algorithm.
+///
+/// A variable has an edge to the sink of every operation that uses it, and a
+/// control-dependence edge to the sink of every operation whose intersection
+/// it bounds. The components come in topological order: every edge between
+/// two components goes from an earlier one to a later one, so solving them
+/// in order sees the final ranges of all their inputs.
+pub struct Nuutila<V> {
+    // Depth-first number of each visited variable.
+    dfs: HashMap<V, usize>,
+    // The variable of least depth-first number known to reach each variable
+    // and be reached from it.
+    root: HashMap<V, V>,
+    // Variables whose component is complete.
+    in_component: HashSet<V>,
+    // Visited variables that are not the root of their component.
+    stack: Vec<V>,
+    components: Vec<HashSet<V>>,
+}
+
+impl Nuutila<VarId> {
+    pub fn new<T: PrimInt>(
+        vars: &VarNodes<'_, T>,
+        oprs: &GenOprs<T>,
+        usemap: &UseMap,
+        symbmap: &SymbMap,
+    ) -> Self {
+        // Sorted, so that the order of components that do not depend on
+        // each other is the same from run to run.
+        let successors = |v: VarId| -> Vec<VarId> {
+            let mut ids: Vec<OpId> = [usemap.get(&v), symbmap.get(&v)]
+                .into_iter()
+                .flatten()
+                .flatten()
+                .copied()
+                .collect();
+            ids.sort_unstable();
+            ids.dedup();
+            ids.into_iter().map(|id| oprs[id].get_sink()).collect()
+        };
+        Self::with_successors(vars.ids().collect(), successors)
+    }
+}
+
+impl<V: Copy + Eq + Hash + Ord> Nuutila<V> {
+    /// The components of the graph on the variables `starts`, whose edges go
+    /// from each variable to the variables `successors` returns for it.
+    pub fn with_successors(mut starts: Vec<V>, successors: impl Fn(V) -> Vec<V>) -> Self {
+        let mut nuutila = Self {
+            dfs: HashMap::new(),
+            root: HashMap::new(),
+            in_component: HashSet::new(),
+            stack: Vec::new(),
+            components: Vec::new(),
+        };
+        starts.sort();
+        for start in starts {
+            if !nuutila.dfs.contains_key(&start) {
+                nuutila.visit(start, &successors);
+            }
+        }
+        // Components are completed sinks first.
+        nuutila.components.reverse();
+        nuutila
+    }
+
+    /// The components, in topological order.
+    pub fn components(&self) -> &[HashSet<V>] {
+        &self.components
+    }
+
+    pub fn into_components(self) -> Vec<HashSet<V>> {
+        self.components
+    }
+
+    fn enter(&mut self, v: V) {
+        self.dfs.insert(v, self.dfs.len());
+        self.root.insert(v, v);
+    }
+
+    /// Merges what `w`, a successor of `v`, reaches into `v`, unless `w`
+    /// belongs to a finished component.
+    fn update(&mut self, v: V, w: V) {
+        if self.in_component.contains(&w) {
+            return;
+        }
+        let (root_v, root_w) = (self.root[&v], self.root[&w]);
+        if self.dfs[&root_w] < self.dfs[&root_v] {
+            self.root.insert(v, root_w);
+        }
+    }
 
-    // 假设的辅助方法定义
-    fn build_symbolic_intersect_map(&self) {
-        // 构建符号交集映射
+    /// Completes the component of `v` once all its successors are visited,
+    /// if `v` is its root.
+    fn finish(&mut self, v: V) {
+        if self.root[&v] != v {
+            self.stack.push(v);
+            return;
+        }
+        let mut component = HashSet::from([v]);
+        self.in_component.insert(v);
+        while let Some(&w) = self.stack.last() {
+            if self.dfs[&w] <= self.dfs[&v] {
+                break;
+            }
+            self.stack.pop();
+            self.in_component.insert(w);
+            component.insert(w);
+        }
+        self.components.push(component);
     }
+
+    /// The depth-first search from `start`, with an explicit stack so that
+    /// long chains of variables cannot overflow the call stack.
+    fn visit(&mut self, start: V, successors: &impl Fn(V) -> Vec<V>) {
+        self.enter(start);
+        let mut calls = vec![(start, successors(start), 0)];
+        while let Some((v, succs, next)) = calls.last_mut() {
+            let v = *v;
+            let Some(&w) = succs.get(*next) else {
+                calls.pop();
+                self.finish(v);
+                if let Some(&(parent, ..)) = calls.last() {
+                    self.update(parent, v);
+                }
+                continue;
+            };
+            *next += 1;
+            if self.dfs.contains_key(&w) {
+                self.update(v, w);
+            } else {
+                self.enter(w);
+                calls.push((w, successors(w), 0));
+            }
+        }
+    }
+}
+
+/// The fixpoint solver of one strongly connected component of a constraint
+/// graph, whose inputs are solved already. It goes through three phases:
+///
+/// 1. widening: a worklist evaluates the operations that define the
+///    variables of the component, widening each range with the constants of
+///    the component, until no range grows;
+/// 2. future resolution: the symbolic intersections bounded by variables of
+///    the component become concrete ranges;
+/// 3. narrowing: a second worklist crops the bounds widening moved, see
+///    `VarNode::crop`.
+///
+/// The known bits and the congruences of the sinks then tighten their
+/// narrowed ranges.
+///
+/// Widening stops at the ends of the type of the sink and of the
+/// intersection of the operation, which hold every value the sink can take,
+/// so that a bound thrown to the end of `T` does not wrap the whole range
+/// around the type. Both worklists terminate. After its first value a range
+/// only grows, and each bound grows by jumping to one of finitely many
+/// values: a constant, an end of the type, of the intersection or of `T`.
+/// Narrowing crops each bound at most once.
+pub struct Solver<'a, 'tcx, T: PartialOrd + Clone + Bounded> {
+    vars: &'a mut VarNodes<'tcx, T>,
+    oprs: &'a mut GenOprs<T>,
+    defmap: &'a DefMap,
+    usemap: &'a UseMap,
+    symbmap: &'a SymbMap,
+    // The constants of the component, sorted, for jump-set widening.
+    thresholds: &'a [T],
+    /// Operations evaluated by the widening phase.
+    pub widening_steps: usize,
+    /// Operations evaluated by the narrowing phase.
+    pub narrowing_steps: usize,
 }
 
-// pub struct Nuutila<'tcx> {
-//     worklist: Vec<&'tcx Rc<VarNode>>,
-//     components: Vec<HashSet<Rc<VarNode>>>,
-// }
-
-// impl<'tcx> Nuutila<'tcx> {
-//     fn new(
-//         vars: &'tcx VarNodes,
-//         use_map: &'tcx HashMap<String, Vec<Rc<VarNode>>>,
-//         symb_map: &'tcx HashMap<String, Vec<Rc<VarNode>>>,
-//     ) -> Self {
-//         Nuutila {
-//             worklist: Vec::new(),
-//             components: Vec::new(),
-//         }
-//     }
-
-//     fn components(&self) -> &Vec<HashSet<Rc<VarNode>>> {
-//         &self.components
-//     }
-// }
+impl<'a, 'tcx, T: PrimInt> Solver<'a, 'tcx, T> {
+    pub fn new(
+        vars: &'a mut VarNodes<'tcx, T>,
+        oprs: &'a mut GenOprs<T>,
+        defmap: &'a DefMap,
+        usemap: &'a UseMap,
+        symbmap: &'a SymbMap,
+        thresholds: &'a [T],
+    ) -> Self {
+        Self {
+            vars,
+            oprs,
+            defmap,
+            usemap,
+            symbmap,
+            thresholds,
+            widening_steps: 0,
+            narrowing_steps: 0,
+        }
+    }
+
+    /// Solves `component`, leaving the ranges of its variables in their
+    /// nodes, which must have been initialized with `VarNode::init`.
+    pub fn solve(&mut self, component: &HashSet<VarId>) {
+        let ops: BTreeSet<OpId> = component
+            .iter()
+            .filter_map(|v| self.defmap.get(v))
+            .copied()
+            .collect();
+        self.widen(component, &ops);
+        self.fix_intersects(component);
+        self.narrow(&ops);
+        self.refine_known_bits(&ops);
+        self.refine_congruences(&ops);
+        // Later components are bounded by the narrowed ranges.
+        self.fix_intersects(component);
+    }
+
+    /// The operations of `ops` that use `v`.
+    fn users(&self, v: VarId, ops: &BTreeSet<OpId>) -> Vec<OpId> {
+        self.usemap
+            .get(&v)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|id| ops.contains(id))
+            .collect()
+    }
+
+    fn widen(&mut self, component: &HashSet<VarId>, ops: &BTreeSet<OpId>) {
+        // The first regular range of each variable, which tells the bounds
+        // that widening moved.
+        let mut first: HashMap<VarId, Range<T>> = HashMap::new();
+        let mut worklist = ops.clone();
+        while let Some(id) = worklist.pop_first() {
+            self.widening_steps += 1;
+            let op = &self.oprs[id];
+            let sink = op.get_sink();
+            let new = op.eval(self.vars);
+            let node = &mut self.vars[sink];
+            let intersect = op.get_intersect().get_range();
+            let (new, limit) = match node.get_type() {
+                Some(ty) => (ty.fit(&new), ty.full_range().meet(intersect)),
+                None => (new, intersect.clone()),
+            };
+            let old = node.get_range().clone();
+            let widened = old.widen_with_thresholds(&new, self.thresholds);
+            node.set_range(widened.meet(&limit));
+            if *node.get_range() == old {
+                continue;
+            }
+            if node.get_range().is_regular() {
+                first
+                    .entry(sink)
+                    .or_insert_with(|| node.get_range().clone());
+            }
+            worklist.extend(self.users(sink, ops));
+        }
+        for &v in component {
+            let node = &mut self.vars[v];
+            // Nothing evaluated the variable, as in a cycle no value enters.
+            if node.get_range().is_unknown() {
+                node.set_range(Range::default());
+            }
+            node.store_abstract_state(first.get(&v).unwrap_or(&Range::unknown()));
+        }
+    }
+
+    fn narrow(&mut self, ops: &BTreeSet<OpId>) {
+        let mut worklist = ops.clone();
+        while let Some(id) = worklist.pop_first() {
+            self.narrowing_steps += 1;
+            if self.crop(id) {
+                worklist.extend(self.users(self.oprs[id].get_sink(), ops));
+            }
+        }
+    }
+
+    /// Tightens the sinks of `ops` with the bits their operations compute.
+    /// A single pass is enough for soundness, since every input is already
+    /// sound when it is read.
+    fn refine_known_bits(&mut self, ops: &BTreeSet<OpId>) {
+        for &id in ops {
+            let op = &self.oprs[id];
+            let known = op.eval_known_bits(self.vars);
+            self.vars[op.get_sink()].set_known_bits(known);
+        }
+    }
+
+    /// Computes the congruences of the sinks of `ops` and tightens their
+    /// ranges with them. The congruences start at `⊥` and a worklist joins
+    /// the results of the operations into them, wrapped into the type of the
+    /// sink, which terminates as strides only shrink to their divisors.
+    fn refine_congruences(&mut self, ops: &BTreeSet<OpId>) {
+        for &id in ops {
+            self.vars[self.oprs[id].get_sink()].set_congruence(Congruence::Bottom);
+        }
+        let mut worklist = ops.clone();
+        while let Some(id) = worklist.pop_first() {
+            let op = &self.oprs[id];
+            let sink = op.get_sink();
+            let product = op.eval_congruence(self.vars);
+            let node = &mut self.vars[sink];
+            let product = match node.get_type() {
+                Some(ty) => product.fit(ty),
+                None => product,
+            };
+            let cong = node.get_congruence().join(&product.cong);
+            if cong == *node.get_congruence() {
+                continue;
+            }
+            node.set_congruence(cong);
+            worklist.extend(self.users(sink, ops));
+        }
+        for &id in ops {
+            self.vars[self.oprs[id].get_sink()].reduce_congruence();
+        }
+    }
+
+    /// Narrows the sink of an operation with the crop meet, see
+    /// `VarNode::crop`. Returns whether the sink changed.
+    pub fn crop(&mut self, id: OpId) -> bool {
+        let op = &self.oprs[id];
+        let new = op.eval(self.vars);
+        self.vars[op.get_sink()].crop(&new)
+    }
+
+    /// Future resolution: replaces the symbolic intersections bounded by the
+    /// variables of `component` with concrete ranges, now that the ranges of
+    /// those variables are known.
+    pub fn fix_intersects(&mut self, component: &HashSet<VarId>) {
+        for &bound in component {
+            let Some(ops) = self.symbmap.get(&bound) else {
+                continue;
+            };
+            let bound_range = self.vars[bound].get_range().clone();
+            for &id in ops {
+                if let IntervalType::Symb(symb) = self.oprs[id].get_intersect_mut() {
+                    let fixed = symb.fix_intersects(&bound_range);
+                    symb.set_range(fixed);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,676 @@
+//! Congruences `a·ℤ + b` and their reduced product with intervals.
+//!
+//! A congruence keeps what intervals lose about strides: that an index is
+//! always a multiple of 4, or that an offset stays aligned to 8. Like the
+//! interval transfer functions, the congruence ones compute on the values of
+//! the carrier `T`; when the interval side says a result may have wrapped,
+//! the product keeps only the part of the congruence that survives wrapping.
+
+use num_traits::{Bounded, PrimInt};
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::lattice::Lattice;
+use super::range::{IntType, Range, RangeType};
+
+/// The values `stride·ℤ + offset`. A stride of zero is the single value
+/// `offset`; otherwise `0 <= offset < stride`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Congruence<T> {
+    Bottom,
+    Mod { stride: T, offset: T },
+}
+
+fn bits<T: PrimInt>() -> u32 {
+    T::zero().count_zeros()
+}
+
+fn gcd<T: PrimInt>(mut a: T, mut b: T) -> T {
+    while b != T::zero() {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn abs<T: PrimInt>(x: T) -> Option<T> {
+    if x < T::zero() {
+        T::zero().checked_sub(&x)
+    } else {
+        Some(x)
+    }
+}
+
+/// `x mod m` in `[0, m)`, for a positive `m`.
+fn mod_floor<T: PrimInt>(x: T, m: T) -> T {
+    let r = x % m;
+    if r < T::zero() {
+        r + m
+    } else {
+        r
+    }
+}
+
+fn pow2<T: PrimInt>(k: u32) -> Option<T> {
+    num_traits::checked_pow(T::one() + T::one(), k as usize)
+}
+
+/// The inverse of `a` modulo `m`, for coprime `a` and `m`.
+fn mod_inverse(a: i128, m: i128) -> i128 {
+    let (mut r0, mut r1) = (a, m);
+    let (mut s0, mut s1) = (1i128, 0i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (s0, s1) = (s1, s0 - q * s1);
+    }
+    s0.rem_euclid(m)
+}
+
+impl<T: PrimInt> Congruence<T> {
+    /// The values `stride·ℤ + offset`, normalized.
+    pub fn new(stride: T, offset: T) -> Self {
+        match abs(stride) {
+            Some(stride) if stride == T::zero() => Congruence::Mod { stride, offset },
+            Some(stride) => Congruence::Mod {
+                stride,
+                offset: mod_floor(offset, stride),
+            },
+            None => Self::top(),
+        }
+    }
+
+    pub fn constant(value: T) -> Self {
+        Congruence::Mod {
+            stride: T::zero(),
+            offset: value,
+        }
+    }
+
+    /// The congruence of the values of `range`: a constant for a single
+    /// value, `ℤ` for several and `⊥` for none.
+    pub fn from_range(range: &Range<T>) -> Self {
+        if range.is_unknown() || range.is_empty() {
+            Congruence::Bottom
+        } else if range.is_regular() && range.get_lower() == range.get_upper() {
+            Self::constant(range.get_lower())
+        } else {
+            Self::top()
+        }
+    }
+
+    fn parts(&self) -> Option<(T, T)> {
+        match self {
+            Congruence::Bottom => None,
+            Congruence::Mod { stride, offset } => Some((*stride, *offset)),
+        }
+    }
+
+    pub fn get_stride(&self) -> Option<T> {
+        self.parts().map(|(stride, _)| stride)
+    }
+
+    pub fn get_offset(&self) -> Option<T> {
+        self.parts().map(|(_, offset)| offset)
+    }
+
+    pub fn as_constant(&self) -> Option<T> {
+        match self.parts() {
+            Some((stride, offset)) if stride == T::zero() => Some(offset),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        match self.parts() {
+            None => false,
+            Some((stride, offset)) if stride == T::zero() => x == offset,
+            Some((stride, offset)) => mod_floor(x, stride) == offset,
+        }
+    }
+
+    /// What remains known once the values wrap modulo `2^bits`: the
+    /// congruence modulo the largest power of two dividing the stride.
+    pub fn wrap(&self, bits: u32) -> Self {
+        match self.parts() {
+            Some((stride, offset)) if stride > T::zero() => {
+                let k = std::cmp::min(stride.trailing_zeros(), bits);
+                Self::new(pow2(k).unwrap_or(T::one()), offset)
+            }
+            _ => *self,
+        }
+    }
+
+    /// `self << s` for some `s >= min`: every value is a multiple of the
+    /// values of `self`, and of `2^min`.
+    pub fn shl_at_least(&self, min: u32) -> Self {
+        let Some((stride, offset)) = self.parts() else {
+            return Congruence::Bottom;
+        };
+        let Some(g) = abs(offset).map(|offset| gcd(stride, offset)) else {
+            return Self::top();
+        };
+        if g == T::zero() {
+            return Self::constant(T::zero());
+        }
+        match pow2(min).and_then(|p: T| g.checked_mul(&p)) {
+            Some(stride) => Self::new(stride, T::zero()),
+            None => Self::new(g, T::zero()),
+        }
+    }
+
+    /// How many low bits every value shares with the offset.
+    fn known_low_bits(stride: T) -> u32 {
+        if stride == T::zero() {
+            bits::<T>()
+        } else {
+            stride.trailing_zeros()
+        }
+    }
+
+    /// `x & mask` where `mask` clears the low `t` bits and keeps the others,
+    /// and `2^t` divides the stride of `x`: the low bits of every value are
+    /// those of the offset, so the stride survives.
+    fn align(&self, mask: T) -> Option<Self> {
+        let (stride, offset) = self.parts()?;
+        let low = !mask;
+        let step = low.checked_add(&T::one())?;
+        if stride == T::zero() || low < T::zero() || low & step != T::zero() {
+            return None;
+        }
+        (stride % step == T::zero()).then(|| Self::new(stride, offset & mask))
+    }
+}
+
+/// Prints `4ℤ+1`, `8ℤ`, `ℤ`, a constant or `⊥`.
+impl<T: PrimInt + fmt::Display> fmt::Display for Congruence<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.parts() {
+            None => write!(f, "⊥"),
+            Some((stride, offset)) if stride == T::zero() => write!(f, "{}", offset),
+            Some((stride, _)) if stride == T::one() => write!(f, "ℤ"),
+            Some((stride, offset)) if offset == T::zero() => write!(f, "{}ℤ", stride),
+            Some((stride, offset)) => write!(f, "{}ℤ+{}", stride, offset),
+        }
+    }
+}
+
+/// Transfer function for `+`.
+impl<'a, T: PrimInt> std::ops::Add<&'a Congruence<T>> for &'a Congruence<T> {
+    type Output = Congruence<T>;
+
+    fn add(self, other: &'a Congruence<T>) -> Congruence<T> {
+        let (Some((a, b)), Some((c, d))) = (self.parts(), other.parts()) else {
+            return Congruence::Bottom;
+        };
+        let g = gcd(a, c);
+        if g == T::zero() {
+            return b
+                .checked_add(&d)
+                .map_or(Congruence::top(), Congruence::constant);
+        }
+        match mod_floor(b, g).checked_add(&mod_floor(d, g)) {
+            Some(offset) => Congruence::new(g, offset),
+            None => Congruence::top(),
+        }
+    }
+}
+
+/// Transfer function for unary `-`.
+impl<T: PrimInt> std::ops::Neg for &Congruence<T> {
+    type Output = Congruence<T>;
+
+    fn neg(self) -> Congruence<T> {
+        match self.parts() {
+            None => Congruence::Bottom,
+            Some((a, b)) if a == T::zero() => T::zero()
+                .checked_sub(&b)
+                .map_or(Congruence::top(), Congruence::constant),
+            Some((a, b)) => Congruence::new(a, a - b),
+        }
+    }
+}
+
+/// Transfer function for `-`.
+impl<'a, T: PrimInt> std::ops::Sub<&'a Congruence<T>> for &'a Congruence<T> {
+    type Output = Congruence<T>;
+
+    fn sub(self, other: &'a Congruence<T>) -> Congruence<T> {
+        let (Some((a, b)), Some((c, d))) = (self.parts(), other.parts()) else {
+            return Congruence::Bottom;
+        };
+        let g = gcd(a, c);
+        if g == T::zero() {
+            return b
+                .checked_sub(&d)
+                .map_or(Congruence::top(), Congruence::constant);
+        }
+        let (b, d) = (mod_floor(b, g), mod_floor(d, g));
+        Congruence::new(g, if b >= d { b - d } else { b + (g - d) })
+    }
+}
+
+/// Transfer function for `*`: `(a·ℤ + b)(c·ℤ + d)` lies in
+/// `gcd(ac, ad, bc)·ℤ + bd`.
+impl<'a, T: PrimInt> std::ops::Mul<&'a Congruence<T>> for &'a Congruence<T> {
+    type Output = Congruence<T>;
+
+    fn mul(self, other: &'a Congruence<T>) -> Congruence<T> {
+        let (Some((a, b)), Some((c, d))) = (self.parts(), other.parts()) else {
+            return Congruence::Bottom;
+        };
+        let products = [a.checked_mul(&c), a.checked_mul(&d), b.checked_mul(&c)];
+        let stride = products
+            .into_iter()
+            .try_fold(T::zero(), |g, p| Some(gcd(g, abs(p?)?)));
+        let Some(stride) = stride else {
+            // Every value is a multiple of the values of both sides.
+            let g = (abs(b), abs(d));
+            return match (g.0, g.1) {
+                (Some(b), Some(d)) => gcd(a, b)
+                    .checked_mul(&gcd(c, d))
+                    .map_or(Congruence::top(), |s| Congruence::new(s, T::zero())),
+                _ => Congruence::top(),
+            };
+        };
+        if stride == T::zero() {
+            return b
+                .checked_mul(&d)
+                .map_or(Congruence::top(), Congruence::constant);
+        }
+        match mod_floor(b, stride).checked_mul(&mod_floor(d, stride)) {
+            Some(offset) => Congruence::new(stride, offset),
+            None => Congruence::top(),
+        }
+    }
+}
+
+/// Transfer function for `<<`: a constant shift multiplies by a power of two.
+/// Shift amounts of at least the bit width panic and are excluded.
+impl<'a, T: PrimInt> std::ops::Shl<&'a Congruence<T>> for &'a Congruence<T> {
+    type Output = Congruence<T>;
+
+    fn shl(self, other: &'a Congruence<T>) -> Congruence<T> {
+        if other == &Congruence::Bottom {
+            return Congruence::Bottom;
+        }
+        let Some(k) = other.as_constant() else {
+            return self.shl_at_least(0);
+        };
+        match k.to_u32() {
+            Some(k) if k < bits::<T>() => match pow2(k) {
+                Some(factor) => self * &Congruence::constant(factor),
+                None => Congruence::top(),
+            },
+            _ => Congruence::Bottom,
+        }
+    }
+}
+
+/// Transfer function for `&`. A bit of the result is known where both sides
+/// know it, or where one side knows it is zero; the low run of known bits
+/// gives a power-of-two stride. Masks that only clear low bits keep the
+/// whole stride.
+impl<'a, T: PrimInt> std::ops::BitAnd<&'a Congruence<T>> for &'a Congruence<T> {
+    type Output = Congruence<T>;
+
+    fn bitand(self, other: &'a Congruence<T>) -> Congruence<T> {
+        let (Some((a, b)), Some((c, d))) = (self.parts(), other.parts()) else {
+            return Congruence::Bottom;
+        };
+        if let Some(mask) = other.as_constant() {
+            if let Some(result) = self.align(mask) {
+                return result;
+            }
+        }
+        if let Some(mask) = self.as_constant() {
+            if let Some(result) = other.align(mask) {
+                return result;
+            }
+        }
+        let (k1, k2) = (Congruence::known_low_bits(a), Congruence::known_low_bits(c));
+        let zero_bit = |x: T, i: u32| (x >> i as usize) & T::one() == T::zero();
+        let known = |i: u32| (i < k1 && (i < k2 || zero_bit(b, i))) || (i < k2 && zero_bit(d, i));
+        let mut run = (0..bits::<T>()).take_while(|&i| known(i)).count() as u32;
+        if run == bits::<T>() {
+            return Congruence::constant(b & d);
+        }
+        loop {
+            if let Some(stride) = pow2::<T>(run) {
+                return Congruence::new(stride, (b & d) & (stride - T::one()));
+            }
+            run -= 1;
+        }
+    }
+}
+
+impl<T: PrimInt> Lattice for Congruence<T> {
+    fn bottom() -> Self {
+        Congruence::Bottom
+    }
+
+    fn top() -> Self {
+        Congruence::Mod {
+            stride: T::one(),
+            offset: T::zero(),
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        let (Some((a, b)), Some((c, d))) = (self.parts(), other.parts()) else {
+            return if self == &Congruence::Bottom {
+                *other
+            } else {
+                *self
+            };
+        };
+        let diff = if b >= d {
+            b.checked_sub(&d)
+        } else {
+            d.checked_sub(&b)
+        };
+        match diff {
+            Some(diff) => Self::new(gcd(gcd(a, c), diff), b),
+            None => Self::top(),
+        }
+    }
+
+    /// Solves both congruences with the Chinese remainder theorem. When the
+    /// combined stride does not fit, `self` is kept, which still holds the
+    /// intersection.
+    fn meet(&self, other: &Self) -> Self {
+        let (Some((a, b)), Some((c, d))) = (self.parts(), other.parts()) else {
+            return Congruence::Bottom;
+        };
+        if a == T::zero() {
+            return if other.contains(b) {
+                *self
+            } else {
+                Congruence::Bottom
+            };
+        }
+        if c == T::zero() {
+            return if self.contains(d) {
+                *other
+            } else {
+                Congruence::Bottom
+            };
+        }
+        let (Some(a), Some(b), Some(c), Some(d)) =
+            (a.to_i128(), b.to_i128(), c.to_i128(), d.to_i128())
+        else {
+            return *self;
+        };
+        let g = gcd(a, c);
+        if (d - b) % g != 0 {
+            return Congruence::Bottom;
+        }
+        let modulus = c / g;
+        let k = ((d - b) / g)
+            .rem_euclid(modulus)
+            .checked_mul(mod_inverse((a / g).rem_euclid(modulus), modulus))
+            .map(|k| k.rem_euclid(modulus));
+        // The larger stride, if the exact meet cannot be represented.
+        let fallback = if a >= c { *self } else { *other };
+        let solution = k.and_then(|k| {
+            let stride = (a / g).checked_mul(c)?;
+            let offset = b.checked_add(a.checked_mul(k)?)?;
+            Some((stride, offset))
+        });
+        let Some((stride, offset)) = solution else {
+            return fallback;
+        };
+        if let (Some(stride), Some(offset)) = (T::from(stride), T::from(offset)) {
+            return Self::new(stride, offset);
+        }
+        // A stride beyond `T`: count the values left in `T` instead.
+        let (Some(min), Some(max)) = (T::min_value().to_i128(), T::max_value().to_i128()) else {
+            return fallback;
+        };
+        let first = min + (offset - min).rem_euclid(stride);
+        match (
+            first <= max,
+            first.checked_add(stride).is_some_and(|next| next <= max),
+        ) {
+            (false, _) => Congruence::Bottom,
+            (true, false) => T::from(first).map_or(fallback, Self::constant),
+            (true, true) => fallback,
+        }
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        match (self.parts(), other.parts()) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some((a, b)), Some((c, _))) => {
+                if a == T::zero() {
+                    other.contains(b)
+                } else {
+                    c != T::zero() && a % c == T::zero() && other.contains(b)
+                }
+            }
+        }
+    }
+
+    /// Strides only shrink to their divisors, so there are no infinite
+    /// ascending chains and joining is enough.
+    fn widen(&self, other: &Self) -> Self {
+        self.join(other)
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        self.meet(other)
+    }
+}
+
+/// An interval and a congruence describing the same values, each tightening
+/// the other: `[1, 10] ∧ 4ℤ` is `[4, 8] ∧ 4ℤ`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RangeCongruence<T: PartialOrd + Clone + Bounded> {
+    pub range: Range<T>,
+    pub cong: Congruence<T>,
+}
+
+impl<T: PrimInt> RangeCongruence<T> {
+    /// The reduced product of `range` and `cong`.
+    pub fn new(range: Range<T>, cong: Congruence<T>) -> Self {
+        Self { range, cong }.reduce()
+    }
+
+    pub fn constant(value: T) -> Self {
+        Self::new(
+            Range::new(value, value, RangeType::Regular),
+            Congruence::constant(value),
+        )
+    }
+
+    fn empty() -> Self {
+        Self {
+            range: Range::empty(),
+            cong: Congruence::Bottom,
+        }
+    }
+
+    /// Moves the bounds of the range inwards to the nearest values of the
+    /// congruence, and turns a congruence with a single value in the range
+    /// into a constant.
+    pub fn reduce(&self) -> Self {
+        if self.range.is_unknown() {
+            return Self {
+                range: Range::unknown(),
+                cong: Congruence::Bottom,
+            };
+        }
+        let Some((stride, offset)) = self.cong.parts() else {
+            return Self::empty();
+        };
+        if self.range.is_empty() {
+            return Self::empty();
+        }
+        let (lo, hi) = (self.range.get_lower(), self.range.get_upper());
+        if stride == T::zero() {
+            return if lo <= offset && offset <= hi {
+                Self {
+                    range: Range::new(offset, offset, RangeType::Regular),
+                    cong: self.cong,
+                }
+            } else {
+                Self::empty()
+            };
+        }
+        // Distance from `x` up to the next value of the congruence, and
+        // down to the previous one.
+        let up = |x: T| {
+            let r = mod_floor(x, stride);
+            if offset >= r {
+                offset - r
+            } else {
+                offset + (stride - r)
+            }
+        };
+        let down = |x: T| {
+            let r = mod_floor(x, stride);
+            if r >= offset {
+                r - offset
+            } else {
+                r + (stride - offset)
+            }
+        };
+        let (Some(lo), Some(hi)) = (lo.checked_add(&up(lo)), hi.checked_sub(&down(hi))) else {
+            return Self::empty();
+        };
+        match lo.cmp(&hi) {
+            Ordering::Greater => Self::empty(),
+            Ordering::Equal => Self {
+                range: Range::new(lo, lo, RangeType::Regular),
+                cong: Congruence::constant(lo),
+            },
+            Ordering::Less => Self {
+                range: Range::new(lo, hi, RangeType::Regular),
+                cong: self.cong,
+            },
+        }
+    }
+
+    /// Wraps the values into `ty`, as `IntType::fit` does for the range.
+    pub fn fit(&self, ty: IntType) -> Self {
+        let range = ty.fit(&self.range);
+        if range == self.range {
+            return self.clone();
+        }
+        Self::new(range, self.cong.wrap(ty.bits))
+    }
+
+    /// Combines the results of both domains, dropping what wrapping may have
+    /// broken when the interval side could overflow.
+    fn combine(range: Range<T>, cong: Congruence<T>) -> Self {
+        let cong = if range.is_regular() && range.is_max_range() {
+            cong.wrap(bits::<T>())
+        } else {
+            cong
+        };
+        Self::new(range, cong)
+    }
+}
+
+/// Prints `range ∧ congruence`.
+impl<T: PrimInt + fmt::Display> fmt::Display for RangeCongruence<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ∧ {}", self.range, self.cong)
+    }
+}
+
+/// Transfer function for `+`.
+impl<'a, T: PrimInt> std::ops::Add<&'a RangeCongruence<T>> for &'a RangeCongruence<T> {
+    type Output = RangeCongruence<T>;
+
+    fn add(self, other: &'a RangeCongruence<T>) -> RangeCongruence<T> {
+        RangeCongruence::combine(&self.range + &other.range, &self.cong + &other.cong)
+    }
+}
+
+/// Transfer function for `-`.
+impl<'a, T: PrimInt> std::ops::Sub<&'a RangeCongruence<T>> for &'a RangeCongruence<T> {
+    type Output = RangeCongruence<T>;
+
+    fn sub(self, other: &'a RangeCongruence<T>) -> RangeCongruence<T> {
+        RangeCongruence::combine(&self.range - &other.range, &self.cong - &other.cong)
+    }
+}
+
+/// Transfer function for `*`.
+impl<'a, T: PrimInt> std::ops::Mul<&'a RangeCongruence<T>> for &'a RangeCongruence<T> {
+    type Output = RangeCongruence<T>;
+
+    fn mul(self, other: &'a RangeCongruence<T>) -> RangeCongruence<T> {
+        RangeCongruence::combine(&self.range * &other.range, &self.cong * &other.cong)
+    }
+}
+
+/// Transfer function for `<<`. A shift amount known to be at least `k`
+/// makes every value a multiple of `2^k`.
+impl<'a, T: PrimInt> std::ops::Shl<&'a RangeCongruence<T>> for &'a RangeCongruence<T> {
+    type Output = RangeCongruence<T>;
+
+    fn shl(self, other: &'a RangeCongruence<T>) -> RangeCongruence<T> {
+        let cong = match (other.cong.as_constant(), other.range.is_regular()) {
+            (None, true) if other.range.get_lower() > T::zero() => {
+                let min = other.range.get_lower().to_u32().unwrap_or(u32::MAX);
+                self.cong.shl_at_least(std::cmp::min(min, bits::<T>()))
+            }
+            _ => &self.cong << &other.cong,
+        };
+        RangeCongruence::combine(&self.range << &other.range, cong)
+    }
+}
+
+/// Transfer function for `&`.
+impl<'a, T: PrimInt> std::ops::BitAnd<&'a RangeCongruence<T>> for &'a RangeCongruence<T> {
+    type Output = RangeCongruence<T>;
+
+    fn bitand(self, other: &'a RangeCongruence<T>) -> RangeCongruence<T> {
+        RangeCongruence::new(&self.range & &other.range, &self.cong & &other.cong)
+    }
+}
+
+impl<T: PrimInt> Lattice for RangeCongruence<T> {
+    fn bottom() -> Self {
+        Self {
+            range: Range::unknown(),
+            cong: Congruence::Bottom,
+        }
+    }
+
+    fn top() -> Self {
+        Self {
+            range: Range::default(),
+            cong: Congruence::top(),
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        Self::new(self.range.join(&other.range), self.cong.join(&other.cong))
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        Self::new(self.range.meet(&other.range), self.cong.meet(&other.cong))
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.range.is_subset(&other.range) && self.cong.is_subset(&other.cong)
+    }
+
+    /// Not reduced, so that the reduction cannot undo the widening.
+    fn widen(&self, other: &Self) -> Self {
+        Self {
+            range: self.range.widen(&other.range),
+            cong: self.cong.widen(&other.cong),
+        }
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        Self::new(
+            self.range.narrow(&other.range),
+            self.cong.narrow(&other.cong),
+        )
+    }
+}
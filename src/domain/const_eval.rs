@@ -0,0 +1,42 @@
+use num_traits::PrimInt;
+use rustc_middle::mir::ConstOperand;
+use rustc_middle::ty::{ParamEnv, TyCtxt, TypeVisitableExt};
+
+use super::range::{IntType, Range, RangeType};
+
+/// The value of an integer, `bool` or `char` constant in the carrier `T`.
+///
+/// Constants still unevaluated in the MIR, such as `i128::MIN`, are
+/// evaluated. Returns `None` for constants of other types, for generic
+/// constants, which have no value until the function is instantiated, for
+/// constants whose evaluation fails, and for values that `T` cannot
+/// represent.
+pub fn const_value<'tcx, T: PrimInt>(tcx: TyCtxt<'tcx>, c: &ConstOperand<'tcx>) -> Option<T> {
+    let ty = IntType::from_ty(tcx, c.const_.ty())?;
+    ty.value_from_bits(const_bits(tcx, c)?)
+}
+
+/// The bits of a scalar constant, evaluating it if it is still unevaluated
+/// in the MIR. Returns `None` for generic constants, for constants whose
+/// evaluation fails and for constants that are not scalars.
+pub fn const_bits<'tcx>(tcx: TyCtxt<'tcx>, c: &ConstOperand<'tcx>) -> Option<u128> {
+    if c.const_.has_param() {
+        return None;
+    }
+    let scalar = c.const_.try_eval_scalar_int(tcx, ParamEnv::reveal_all())?;
+    Some(scalar.to_bits_unchecked())
+}
+
+/// The range of a constant: a single value when it can be evaluated, and
+/// the full range of its type otherwise. That is `top` for a value the
+/// carrier cannot hold, such as `u128::MAX` in `i128`, see
+/// `IntType::full_range`.
+pub fn const_range<'tcx, T: PrimInt>(tcx: TyCtxt<'tcx>, c: &ConstOperand<'tcx>) -> Range<T> {
+    if let Some(value) = const_value(tcx, c) {
+        return Range::new(value, value, RangeType::Regular);
+    }
+    match IntType::from_ty(tcx, c.const_.ty()) {
+        Some(ty) => ty.full_range(),
+        None => Range::default(),
+    }
+}
@@ -1,18 +1,70 @@
-use num_traits::Bounded;
-use rustc_middle::mir::{BasicBlock, Local, LocalDecl, Place, Statement};
+use num_traits::{Bounded, PrimInt};
+use rustc_index::IndexVec;
+use rustc_middle::mir::{BasicBlock, BinOp, Place};
 use std::cmp::PartialEq;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::ops::{Index, IndexMut};
 
-use super::range::Range;
+use super::congruence::{Congruence, RangeCongruence};
+use super::knownbits::KnownBits;
+use super::lattice::Lattice;
+use super::range::{IntType, Range, RangeType};
 
-#[derive(Debug)]
-pub enum IntervalType<'tcx, T: PartialOrd + Clone + Bounded> {
+#[derive(Debug, Clone)]
+pub enum IntervalType<T: PartialOrd + Clone + Bounded> {
     Basic(BasicInterval<T>),
-    Symb(SymbInterval<'tcx, T>), // Using 'static for simplicity, adjust lifetime as needed
+    Symb(SymbInterval<T>),
+}
+
+/// Prints a basic interval as its range and a symbolic one as its bound.
+impl<T: PrimInt + fmt::Display> fmt::Display for IntervalType<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntervalType::Basic(basic) => write!(f, "{}", basic.get_range()),
+            IntervalType::Symb(symb) => write!(
+                f,
+                "{:?}({:?}) ∩ {}",
+                symb.get_operation(),
+                symb.get_bound(),
+                symb.get_range()
+            ),
+        }
+    }
+}
+
+impl<T: PrimInt + fmt::Display> IntervalType<T> {
+    /// As its `Display`, but with a symbolic bound named by its place.
+    pub fn label(&self, vars: &VarNodes<'_, T>) -> String {
+        match self {
+            IntervalType::Symb(symb) => format!(
+                "{:?}({:?}) ∩ {}",
+                symb.get_operation(),
+                vars.place(symb.get_bound()),
+                symb.get_range()
+            ),
+            IntervalType::Basic(_) => self.to_string(),
+        }
+    }
+}
+
+impl<T: PartialOrd + Clone + Bounded> IntervalType<T> {
+    pub fn get_range(&self) -> &Range<T> {
+        match self {
+            IntervalType::Basic(basic) => basic.get_range(),
+            IntervalType::Symb(symb) => symb.get_range(),
+        }
+    }
+
+    pub fn set_range(&mut self, new_range: Range<T>) {
+        match self {
+            IntervalType::Basic(basic) => basic.set_range(new_range),
+            IntervalType::Symb(symb) => symb.set_range(new_range),
+        }
+    }
 }
 
-trait BasicIntervalTrait<T: PartialOrd + Clone + Bounded> {
+pub trait BasicIntervalTrait<T: PartialOrd + Clone + Bounded> {
     // fn get_value_id(&self) -> IntervalId;
     fn get_range(&self) -> &Range<T>;
     fn set_range(&mut self, new_range: Range<T>);
@@ -46,15 +98,17 @@ impl<T: PartialOrd + Clone + Bounded> BasicIntervalTrait<T> for BasicInterval<T>
     }
 }
 
-#[derive(Debug)]
-pub struct SymbInterval<'tcx, T: PartialOrd + Clone + Bounded> {
+/// An interval bounded by another variable: the values `x` with
+/// `x predicate symbound`, as in `i < j` for the sigma of `i`.
+#[derive(Debug, Clone)]
+pub struct SymbInterval<T: PartialOrd + Clone + Bounded> {
     range: Range<T>,
-    symbound: &'tcx Place<'tcx>,
-    predicate: bool,
+    symbound: VarId,
+    predicate: BinOp,
 }
 
-impl<'tcx, T: PartialOrd + Clone + Bounded> SymbInterval<'tcx, T> {
-    pub fn new(range: Range<T>, symbound: &'tcx Place<'tcx>, predicate: bool) -> Self {
+impl<T: PartialOrd + Clone + Bounded> SymbInterval<T> {
+    pub fn new(range: Range<T>, symbound: VarId, predicate: BinOp) -> Self {
         Self {
             range: range,
             symbound,
@@ -62,23 +116,78 @@ impl<'tcx, T: PartialOrd + Clone + Bounded> SymbInterval<'tcx, T> {
         }
     }
 
-    pub fn get_operation(&self) -> &bool {
-        &self.predicate
+    pub fn get_operation(&self) -> BinOp {
+        self.predicate
+    }
+
+    pub fn get_bound(&self) -> VarId {
+        self.symbound
     }
+}
 
-    pub fn get_bound(&self) -> &Place<'tcx> {
-        &self.symbound
+impl<T: PrimInt> SymbInterval<T> {
+    /// Future resolution: once the range of the bound is known, the symbolic
+    /// interval becomes the concrete range of the values that satisfy the
+    /// predicate against some value of the bound.
+    pub fn fix_intersects(&self, bound: &Range<T>) -> Range<T> {
+        if bound.is_unknown() {
+            return Range::default();
+        }
+        predicate_range(self.predicate, bound)
     }
+}
+
+/// The values `x` for which `x op y` holds for some `y` in `bound`.
+pub fn predicate_range<T: PrimInt>(op: BinOp, bound: &Range<T>) -> Range<T> {
+    if bound.is_empty() {
+        return Range::empty();
+    }
+    let (l, u) = (bound.get_lower(), bound.get_upper());
+    let (min, max) = (T::min_value(), T::max_value());
+    let regular = |lb: Option<T>, ub: Option<T>| match (lb, ub) {
+        (Some(lb), Some(ub)) => Range::new(lb, ub, RangeType::Regular),
+        // `x < MIN` and `x > MAX` have no solution.
+        _ => Range::empty(),
+    };
+    match op {
+        BinOp::Eq => regular(Some(l), Some(u)),
+        BinOp::Le => regular(Some(min), Some(u)),
+        BinOp::Lt => regular(Some(min), u.checked_sub(&T::one())),
+        BinOp::Ge => regular(Some(l), Some(max)),
+        BinOp::Gt => regular(l.checked_add(&T::one()), Some(max)),
+        // Only a single value at an end of the carrier can be cut out of an
+        // interval.
+        BinOp::Ne if l == u && l == min => regular(l.checked_add(&T::one()), Some(max)),
+        BinOp::Ne if l == u && l == max => regular(Some(min), u.checked_sub(&T::one())),
+        _ => Range::default(),
+    }
+}
+
+/// The predicate that holds on the other side of the branch.
+pub fn negate_predicate(op: BinOp) -> BinOp {
+    match op {
+        BinOp::Lt => BinOp::Ge,
+        BinOp::Le => BinOp::Gt,
+        BinOp::Gt => BinOp::Le,
+        BinOp::Ge => BinOp::Lt,
+        BinOp::Eq => BinOp::Ne,
+        BinOp::Ne => BinOp::Eq,
+        op => op,
+    }
+}
 
-    pub fn fix_intersects(&self, symbound: &Place, sink: &Place) {
-        println!(
-            "Fixing intersects with bound {:?} and sink {:?}",
-            symbound, sink
-        );
+/// The predicate with its operands exchanged, `a < b` becoming `b > a`.
+pub fn swap_predicate(op: BinOp) -> BinOp {
+    match op {
+        BinOp::Lt => BinOp::Gt,
+        BinOp::Le => BinOp::Ge,
+        BinOp::Gt => BinOp::Lt,
+        BinOp::Ge => BinOp::Le,
+        op => op,
     }
 }
 
-impl<'tcx, T: PartialOrd + Clone + Bounded> BasicIntervalTrait<T> for SymbInterval<'tcx, T> {
+impl<T: PartialOrd + Clone + Bounded> BasicIntervalTrait<T> for SymbInterval<T> {
     // fn get_value_id(&self) -> IntervalId {
     //     IntervalId::SymbIntervalId
     // }
@@ -92,108 +201,651 @@ impl<'tcx, T: PartialOrd + Clone + Bounded> BasicIntervalTrait<T> for SymbInterv
     }
 }
 
-// Define the basic operation trait
-pub trait Operation<T: PartialOrd + Clone + Bounded> {
-    fn get_value_id(&self) -> u32; // Placeholder for an operation identifier
-    fn eval(&self) -> Range<T>; // Method to evaluate the result of the operation
-    fn print(&self, os: &mut dyn fmt::Write);
+rustc_index::newtype_index! {
+    /// Index of a variable in `VarNodes`.
+    #[orderable]
+    #[debug_format = "v{}"]
+    pub struct VarId {}
+}
+
+rustc_index::newtype_index! {
+    /// Index of an operation in `ConstraintGraph::oprs`.
+    #[orderable]
+    #[debug_format = "op{}"]
+    pub struct OpId {}
+}
+
+/// A value an operation reads: a variable, or a constant given as the range
+/// of its values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpSource<T: PartialOrd + Clone> {
+    Var(VarId),
+    Const(Range<T>),
+}
+
+impl<T: PartialOrd + Clone> From<VarId> for OpSource<T> {
+    fn from(var: VarId) -> Self {
+        OpSource::Var(var)
+    }
+}
+
+impl<T: PrimInt> OpSource<T> {
+    /// The variable read, if the source is not a constant.
+    pub fn get_var(&self) -> Option<VarId> {
+        match self {
+            OpSource::Var(var) => Some(*var),
+            OpSource::Const(_) => None,
+        }
+    }
+
+    /// The current range of the source.
+    pub fn get_range(&self, vars: &VarNodes<'_, T>) -> Range<T> {
+        match self {
+            OpSource::Var(var) => range_of(vars, *var),
+            OpSource::Const(range) => range.clone(),
+        }
+    }
+
+    /// The current known bits of the source.
+    pub fn get_known_bits(&self, vars: &VarNodes<'_, T>) -> KnownBits {
+        match self {
+            OpSource::Var(var) => known_bits_of(vars, *var),
+            OpSource::Const(range) => KnownBits::from_range(range),
+        }
+    }
+
+    /// The current range and congruence of the source.
+    pub fn get_congruence(&self, vars: &VarNodes<'_, T>) -> RangeCongruence<T> {
+        match self {
+            OpSource::Var(var) => congruence_of(vars, *var),
+            OpSource::Const(range) => {
+                RangeCongruence::new(range.clone(), Congruence::from_range(range))
+            }
+        }
+    }
+}
+
+/// The current range of `var`. A variable without a node is unconstrained.
+fn range_of<T: PrimInt>(vars: &VarNodes<'_, T>, var: VarId) -> Range<T> {
+    vars.get_by_id(var)
+        .map_or(Range::default(), |node| node.get_range().clone())
+}
+
+fn known_bits_of<T: PrimInt>(vars: &VarNodes<'_, T>, var: VarId) -> KnownBits {
+    vars.get_by_id(var)
+        .map_or(KnownBits::top(), |node| *node.get_known_bits())
+}
+
+fn congruence_of<T: PrimInt>(vars: &VarNodes<'_, T>, var: VarId) -> RangeCongruence<T> {
+    vars.get_by_id(var).map_or(RangeCongruence::top(), |node| {
+        RangeCongruence::new(node.get_range().clone(), *node.get_congruence())
+    })
+}
+
+/// Prints a variable as its index and a constant as its range.
+impl<T: PrimInt + fmt::Display> fmt::Display for OpSource<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpSource::Var(var) => write!(f, "{:?}", var),
+            OpSource::Const(range) => write!(f, "{}", range),
+        }
+    }
+}
+
+/// The operator of a `UnaryOp`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOpKind {
+    /// A copy or move of the source.
+    Copy,
+    /// Wrapping negation.
+    Neg,
+    /// Bitwise not, which is also the logical not of a `bool`.
+    Not,
+    /// An integer-to-integer `as` cast, which also covers casts from `bool`
+    /// and `char` and from `u8` to `char`.
+    Cast(IntType),
+}
+
+/// `sink = op(source) ∩ intersect`.
+#[derive(Debug, Clone)]
+pub struct UnaryOp<T: PartialOrd + Clone + Bounded> {
+    pub intersect: IntervalType<T>,
+    pub sink: VarId,
+    pub source: OpSource<T>,
+    pub kind: UnaryOpKind,
+}
+
+impl<T: PrimInt> UnaryOp<T> {
+    pub fn new(sink: VarId, source: impl Into<OpSource<T>>, kind: UnaryOpKind) -> Self {
+        Self {
+            intersect: IntervalType::Basic(BasicInterval::new(Range::default())),
+            sink,
+            source: source.into(),
+            kind,
+        }
+    }
+
+    pub fn eval(&self, source: &Range<T>) -> Range<T> {
+        // Results leaving the type of the sink are wrapped back into it by
+        // `VarNode::set_range`.
+        let result = match self.kind {
+            UnaryOpKind::Copy => source.clone(),
+            UnaryOpKind::Neg => -source,
+            UnaryOpKind::Not => !source,
+            // The source range is exact in the carrier, so truncation and
+            // sign or zero extension are all a wrap into the target type.
+            UnaryOpKind::Cast(target) => target.fit(source),
+        };
+        result.meet(self.intersect.get_range())
+    }
+
+    /// Evaluates the operation on the known bits of its source.
+    pub fn eval_known_bits(&self, source: &KnownBits) -> KnownBits {
+        let result = match self.kind {
+            UnaryOpKind::Copy => *source,
+            UnaryOpKind::Neg => -*source,
+            UnaryOpKind::Not => !*source,
+            UnaryOpKind::Cast(target) => source.fit(target),
+        };
+        result.meet(&KnownBits::from_range(self.intersect.get_range()))
+    }
+
+    /// Evaluates the operation on the range and congruence of its source.
+    /// Like `eval`, the result is exact in the carrier, and the intersection
+    /// is left to the range of the sink.
+    pub fn eval_congruence(&self, source: &RangeCongruence<T>) -> RangeCongruence<T> {
+        let zero = RangeCongruence::constant(T::zero());
+        match self.kind {
+            UnaryOpKind::Copy => source.clone(),
+            UnaryOpKind::Neg => &zero - source,
+            // `!x` is `-x - 1`.
+            UnaryOpKind::Not => &(&zero - source) - &RangeCongruence::constant(T::one()),
+            UnaryOpKind::Cast(target) => source.fit(target),
+        }
+    }
 }
 
-// Define the BasicOp struct
-pub struct BasicOp<'tcx, T: PartialOrd + Clone + Bounded> {
-    pub intersect: &'tcx mut BasicInterval<T>, // The range associated with the operation
-    pub sink: &'tcx mut VarNode<'tcx, T>,      // The target node storing the result
-    pub inst: &'tcx Statement<'tcx>,           // The instruction that originated this operation
+/// `sink = lhs op rhs ∩ intersect`, where `ty` is the type of `lhs`. The
+/// `*WithOverflow` operators assign a pair whose first field is the sink.
+#[derive(Debug, Clone)]
+pub struct BinaryOp<T: PartialOrd + Clone + Bounded> {
+    pub intersect: IntervalType<T>,
+    pub sink: VarId,
+    pub lhs: OpSource<T>,
+    pub rhs: OpSource<T>,
+    pub op: BinOp,
+    pub ty: IntType,
 }
 
-impl<'tcx, T: PartialOrd + Clone + Bounded> BasicOp<'tcx, T> {
-    // Constructor for creating a new BasicOp
+impl<T: PrimInt> BinaryOp<T> {
     pub fn new(
-        intersect: &'tcx mut BasicInterval<T>,
-        sink: &'tcx mut VarNode<'tcx, T>,
-        inst: &'tcx Statement<'tcx>,
+        sink: VarId,
+        lhs: impl Into<OpSource<T>>,
+        rhs: impl Into<OpSource<T>>,
+        op: BinOp,
+        ty: IntType,
     ) -> Self {
-        BasicOp {
+        Self {
+            intersect: IntervalType::Basic(BasicInterval::new(Range::default())),
+            sink,
+            lhs: lhs.into(),
+            rhs: rhs.into(),
+            op,
+            ty,
+        }
+    }
+
+    /// The amounts a shift by `rhs` can shift by. MIR masks the amount to
+    /// the width of `lhs`, so an amount that may be out of range may be any.
+    fn shift_amounts(&self, rhs: &Range<T>) -> Range<T> {
+        let max = T::from(self.ty.bits - 1).unwrap();
+        if !rhs.is_regular() || (rhs.get_lower() >= T::zero() && rhs.get_upper() <= max) {
+            return rhs.clone();
+        }
+        Range::new(T::zero(), max, RangeType::Regular)
+    }
+
+    pub fn eval(&self, lhs: &Range<T>, rhs: &Range<T>) -> Range<T> {
+        // Results leaving the type of the sink are wrapped back into it by
+        // `VarNode::set_range`.
+        let result = match self.op {
+            BinOp::Add | BinOp::AddUnchecked | BinOp::AddWithOverflow => lhs + rhs,
+            BinOp::Sub | BinOp::SubUnchecked | BinOp::SubWithOverflow => lhs - rhs,
+            BinOp::Mul | BinOp::MulUnchecked | BinOp::MulWithOverflow => lhs * rhs,
+            BinOp::Div => lhs / rhs,
+            BinOp::Rem => lhs % rhs,
+            BinOp::BitAnd => lhs & rhs,
+            BinOp::BitOr => lhs | rhs,
+            BinOp::BitXor => lhs ^ rhs,
+            BinOp::Shl | BinOp::ShlUnchecked => lhs << &self.shift_amounts(rhs),
+            BinOp::Shr | BinOp::ShrUnchecked => lhs >> &self.shift_amounts(rhs),
+            BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                compare(self.op, lhs, rhs)
+            }
+            BinOp::Cmp => three_way_compare(lhs, rhs),
+            // Pointer offsets.
+            _ => Range::default(),
+        };
+        // The result of a checked operation is only read once the assertion
+        // that it did not overflow has passed, so it never wraps.
+        let result = match self.op {
+            BinOp::AddWithOverflow | BinOp::SubWithOverflow | BinOp::MulWithOverflow => {
+                result.meet(&self.ty.full_range())
+            }
+            _ => result,
+        };
+        result.meet(self.intersect.get_range())
+    }
+
+    /// Evaluates the operation on the known bits of its operands. `rhs` is
+    /// the range of the right operand, which gives the amounts of shifts.
+    pub fn eval_known_bits(
+        &self,
+        lhs: &KnownBits,
+        rhs: &KnownBits,
+        rhs_range: &Range<T>,
+    ) -> KnownBits {
+        let (lhs, rhs) = (*lhs, *rhs);
+        let amounts = || {
+            let amounts = self.shift_amounts(rhs_range);
+            match (
+                amounts.is_regular(),
+                amounts.get_lower().to_i128(),
+                amounts.get_upper().to_i128(),
+            ) {
+                (true, Some(lb), Some(ub)) => Range::new(lb, ub, RangeType::Regular),
+                _ => Range::empty(),
+            }
+        };
+        let result = match self.op {
+            BinOp::Add | BinOp::AddUnchecked | BinOp::AddWithOverflow => lhs + rhs,
+            BinOp::Sub | BinOp::SubUnchecked | BinOp::SubWithOverflow => lhs - rhs,
+            BinOp::Mul | BinOp::MulUnchecked | BinOp::MulWithOverflow => lhs * rhs,
+            BinOp::BitAnd => lhs & rhs,
+            BinOp::BitOr => lhs | rhs,
+            BinOp::BitXor => lhs ^ rhs,
+            BinOp::Shl | BinOp::ShlUnchecked => lhs.shl(&amounts()),
+            // The bits of an unsigned value are zero-extended, so shifting
+            // them arithmetically shifts in zeros.
+            BinOp::Shr | BinOp::ShrUnchecked => lhs.shr(&amounts(), true),
+            _ => KnownBits::top(),
+        };
+        result.meet(&KnownBits::from_range(self.intersect.get_range()))
+    }
+
+    /// Evaluates the operation on the ranges and congruences of its
+    /// operands, see `UnaryOp::eval_congruence`.
+    pub fn eval_congruence(
+        &self,
+        lhs: &RangeCongruence<T>,
+        rhs: &RangeCongruence<T>,
+    ) -> RangeCongruence<T> {
+        let result = match self.op {
+            BinOp::Add | BinOp::AddUnchecked | BinOp::AddWithOverflow => lhs + rhs,
+            BinOp::Sub | BinOp::SubUnchecked | BinOp::SubWithOverflow => lhs - rhs,
+            BinOp::Mul | BinOp::MulUnchecked | BinOp::MulWithOverflow => lhs * rhs,
+            BinOp::BitAnd => lhs & rhs,
+            BinOp::Shl | BinOp::ShlUnchecked => {
+                let amounts = self.shift_amounts(&rhs.range);
+                if amounts == rhs.range {
+                    lhs << rhs
+                } else {
+                    lhs << &RangeCongruence::new(amounts, Congruence::top())
+                }
+            }
+            _ => RangeCongruence::top(),
+        };
+        match self.op {
+            BinOp::AddWithOverflow | BinOp::SubWithOverflow | BinOp::MulWithOverflow => {
+                RangeCongruence::new(result.range.meet(&self.ty.full_range()), result.cong)
+            }
+            _ => result,
+        }
+    }
+}
+
+/// The `bool` result of a comparison: 1 if it holds for every pair of
+/// values of `lhs` and `rhs`, 0 if it holds for none, and either otherwise.
+fn compare<T: PrimInt>(op: BinOp, lhs: &Range<T>, rhs: &Range<T>) -> Range<T> {
+    if lhs.is_unknown() || rhs.is_unknown() {
+        return Range::unknown();
+    }
+    if lhs.is_empty() || rhs.is_empty() {
+        return Range::empty();
+    }
+    let (a, b) = (lhs.get_lower(), lhs.get_upper());
+    let (c, d) = (rhs.get_lower(), rhs.get_upper());
+    let same_constant = a == b && b == c && c == d;
+    let disjoint = b < c || d < a;
+    let (always, never) = match op {
+        BinOp::Lt => (b < c, a >= d),
+        BinOp::Le => (b <= c, a > d),
+        BinOp::Gt => (a > d, b <= c),
+        BinOp::Ge => (a >= d, b < c),
+        BinOp::Eq => (same_constant, disjoint),
+        BinOp::Ne => (disjoint, same_constant),
+        _ => (false, false),
+    };
+    let lb = if always { T::one() } else { T::zero() };
+    let ub = if never { T::zero() } else { T::one() };
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// The `Ordering` of `lhs` and `rhs` as an `i8`: -1, 0 or 1.
+fn three_way_compare<T: PrimInt>(lhs: &Range<T>, rhs: &Range<T>) -> Range<T> {
+    if lhs.is_unknown() || rhs.is_unknown() {
+        return Range::unknown();
+    }
+    if lhs.is_empty() || rhs.is_empty() {
+        return Range::empty();
+    }
+    let (a, b) = (lhs.get_lower(), lhs.get_upper());
+    let (c, d) = (rhs.get_lower(), rhs.get_upper());
+    let (less, equal, greater) = (a < d, !(b < c || d < a), b > c);
+    let lb = if less {
+        -1
+    } else if equal {
+        0
+    } else {
+        1
+    };
+    let ub = if greater {
+        1
+    } else if equal {
+        0
+    } else {
+        -1
+    };
+    match (T::from(lb), T::from(ub)) {
+        (Some(lb), Some(ub)) => Range::new(lb, ub, RangeType::Regular),
+        // An unsigned carrier cannot hold -1.
+        _ => Range::default(),
+    }
+}
+
+/// `sink = φ(sources) ∩ intersect`: the value of whichever source reaches
+/// the join point.
+#[derive(Debug, Clone)]
+pub struct PhiOp<T: PartialOrd + Clone + Bounded> {
+    pub intersect: IntervalType<T>,
+    pub sink: VarId,
+    pub sources: Vec<VarId>,
+}
+
+impl<T: PrimInt> PhiOp<T> {
+    pub fn new(sink: VarId, sources: Vec<VarId>) -> Self {
+        Self {
+            intersect: IntervalType::Basic(BasicInterval::new(Range::default())),
+            sink,
+            sources,
+        }
+    }
+
+    /// The join of the sources. Sources not evaluated yet, as around a loop,
+    /// add nothing.
+    pub fn eval(&self, sources: &[Range<T>]) -> Range<T> {
+        let result = sources
+            .iter()
+            .fold(Range::unknown(), |acc, source| acc.join(source));
+        result.meet(self.intersect.get_range())
+    }
+
+    pub fn eval_known_bits(&self, sources: &[KnownBits]) -> KnownBits {
+        let result = sources
+            .iter()
+            .fold(KnownBits::bottom(), |acc, source| acc.join(source));
+        result.meet(&KnownBits::from_range(self.intersect.get_range()))
+    }
+
+    pub fn eval_congruence(&self, sources: &[RangeCongruence<T>]) -> RangeCongruence<T> {
+        sources
+            .iter()
+            .fold(RangeCongruence::bottom(), |acc, source| acc.join(source))
+    }
+}
+
+/// `sink = source ∩ intersect`, where `intersect` is what the branch into
+/// the block of the sigma tells about `source`.
+#[derive(Debug, Clone)]
+pub struct SigmaOp<T: PartialOrd + Clone + Bounded> {
+    pub intersect: IntervalType<T>,
+    pub sink: VarId,
+    pub source: VarId,
+}
+
+impl<T: PrimInt> SigmaOp<T> {
+    pub fn new(sink: VarId, source: VarId, intersect: IntervalType<T>) -> Self {
+        Self {
             intersect,
             sink,
-            inst,
+            source,
+        }
+    }
+
+    pub fn eval(&self, source: &Range<T>) -> Range<T> {
+        source.meet(self.intersect.get_range())
+    }
+
+    pub fn eval_known_bits(&self, source: &KnownBits) -> KnownBits {
+        source.meet(&KnownBits::from_range(self.intersect.get_range()))
+    }
+}
+
+/// An operation of the constraint graph.
+#[derive(Debug, Clone)]
+pub enum BasicOpKind<T: PartialOrd + Clone + Bounded> {
+    Unary(UnaryOp<T>),
+    Binary(BinaryOp<T>),
+    Phi(PhiOp<T>),
+    Sigma(SigmaOp<T>),
+}
+
+impl<T: PrimInt> BasicOpKind<T> {
+    /// The variable the operation defines.
+    pub fn get_sink(&self) -> VarId {
+        match self {
+            BasicOpKind::Unary(op) => op.sink,
+            BasicOpKind::Binary(op) => op.sink,
+            BasicOpKind::Phi(op) => op.sink,
+            BasicOpKind::Sigma(op) => op.sink,
         }
     }
 
-    pub fn get_instruction(&self) -> Option<&Statement<'tcx>> {
-        Some(self.inst)
+    /// The variables the operation reads.
+    pub fn get_sources(&self) -> Vec<VarId> {
+        match self {
+            BasicOpKind::Unary(op) => op.source.get_var().into_iter().collect(),
+            BasicOpKind::Binary(op) => [&op.lhs, &op.rhs]
+                .into_iter()
+                .filter_map(|source| source.get_var())
+                .collect(),
+            BasicOpKind::Phi(op) => op.sources.clone(),
+            BasicOpKind::Sigma(op) => vec![op.source],
+        }
     }
 
-    pub fn fix_intersects(&mut self, _v: &VarNode<T>) {}
+    pub fn get_intersect(&self) -> &IntervalType<T> {
+        match self {
+            BasicOpKind::Unary(op) => &op.intersect,
+            BasicOpKind::Binary(op) => &op.intersect,
+            BasicOpKind::Phi(op) => &op.intersect,
+            BasicOpKind::Sigma(op) => &op.intersect,
+        }
+    }
 
-    pub fn set_intersect(&mut self, new_intersect: Range<T>) {
-        self.intersect.set_range(new_intersect);
+    pub fn get_intersect_mut(&mut self) -> &mut IntervalType<T> {
+        match self {
+            BasicOpKind::Unary(op) => &mut op.intersect,
+            BasicOpKind::Binary(op) => &mut op.intersect,
+            BasicOpKind::Phi(op) => &mut op.intersect,
+            BasicOpKind::Sigma(op) => &mut op.intersect,
+        }
     }
 
-    pub fn get_sink(&self) -> &VarNode<'tcx, T> {
-        self.sink
+    /// Evaluates the operation on the current ranges of its sources.
+    pub fn eval(&self, vars: &VarNodes<'_, T>) -> Range<T> {
+        match self {
+            BasicOpKind::Unary(op) => op.eval(&op.source.get_range(vars)),
+            BasicOpKind::Binary(op) => op.eval(&op.lhs.get_range(vars), &op.rhs.get_range(vars)),
+            BasicOpKind::Phi(op) => {
+                let sources: Vec<_> = op.sources.iter().map(|&s| range_of(vars, s)).collect();
+                op.eval(&sources)
+            }
+            BasicOpKind::Sigma(op) => op.eval(&range_of(vars, op.source)),
+        }
     }
-    // Returns the instruction that originated this operation
 
-    // Returns the target of the operation (sink), mutable version
-    pub fn get_sink_mut(&mut self) -> &mut VarNode<'tcx, T> {
-        &mut self.sink
+    /// Evaluates the operation on the current known bits of its sources.
+    pub fn eval_known_bits(&self, vars: &VarNodes<'_, T>) -> KnownBits {
+        match self {
+            BasicOpKind::Unary(op) => op.eval_known_bits(&op.source.get_known_bits(vars)),
+            BasicOpKind::Binary(op) => op.eval_known_bits(
+                &op.lhs.get_known_bits(vars),
+                &op.rhs.get_known_bits(vars),
+                &op.rhs.get_range(vars),
+            ),
+            BasicOpKind::Phi(op) => {
+                let sources: Vec<_> = op.sources.iter().map(|&s| known_bits_of(vars, s)).collect();
+                op.eval_known_bits(&sources)
+            }
+            BasicOpKind::Sigma(op) => op.eval_known_bits(&known_bits_of(vars, op.source)),
+        }
+    }
+
+    /// Evaluates the operation on the current ranges and congruences of its
+    /// sources. A sigma passes its source on: its intersection is already
+    /// in the range of its sink.
+    pub fn eval_congruence(&self, vars: &VarNodes<'_, T>) -> RangeCongruence<T> {
+        match self {
+            BasicOpKind::Unary(op) => op.eval_congruence(&op.source.get_congruence(vars)),
+            BasicOpKind::Binary(op) => {
+                op.eval_congruence(&op.lhs.get_congruence(vars), &op.rhs.get_congruence(vars))
+            }
+            BasicOpKind::Phi(op) => {
+                let sources: Vec<_> = op.sources.iter().map(|&s| congruence_of(vars, s)).collect();
+                op.eval_congruence(&sources)
+            }
+            BasicOpKind::Sigma(op) => congruence_of(vars, op.source),
+        }
     }
 }
 
-// Implement the Operation trait for BasicOp
-impl<'tcx, T: PartialOrd + Clone + Bounded> Operation<T> for BasicOp<'tcx, T> {
-    fn get_value_id(&self) -> u32 {
-        0 // Placeholder implementation
+impl fmt::Display for UnaryOpKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnaryOpKind::Copy => write!(f, "copy"),
+            UnaryOpKind::Neg => write!(f, "neg"),
+            UnaryOpKind::Not => write!(f, "not"),
+            UnaryOpKind::Cast(ty) => write!(f, "cast<{}>", ty),
+        }
     }
+}
 
-    fn eval(&self) -> Range<T> {
-        // Placeholder for evaluating the range
-        Range::default() // Assuming Range<T> implements Default
+impl<T: PrimInt + fmt::Display> fmt::Display for UnaryOp<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} = {}({}) ∩ {}",
+            self.sink,
+            self.kind,
+            self.source,
+            self.intersect.get_range()
+        )
     }
+}
 
-    fn print(&self, os: &mut dyn fmt::Write) {}
+impl<T: PrimInt + fmt::Display> fmt::Display for BinaryOp<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} = {:?}({}, {}) ∩ {}",
+            self.sink,
+            self.op,
+            self.lhs,
+            self.rhs,
+            self.intersect.get_range()
+        )
+    }
+}
+
+/// Prints `v5 = phi(v3, v4) ∩ ⊤`.
+impl<T: PrimInt + fmt::Display> fmt::Display for PhiOp<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sources: Vec<_> = self.sources.iter().map(|s| format!("{:?}", s)).collect();
+        write!(
+            f,
+            "{:?} = phi({}) ∩ {}",
+            self.sink,
+            sources.join(", "),
+            self.intersect.get_range()
+        )
+    }
+}
+
+/// Prints the intersection of a sigma with its bound while it is symbolic,
+/// as in `v6 = sigma(v1) ∩ Lt(v2) ∩ ⊤`.
+impl<T: PrimInt + fmt::Display> fmt::Display for SigmaOp<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} = sigma({:?}) ∩ {}",
+            self.sink, self.source, self.intersect
+        )
+    }
+}
+
+impl<T: PrimInt + fmt::Display> fmt::Display for BasicOpKind<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BasicOpKind::Unary(op) => write!(f, "{}", op),
+            BasicOpKind::Binary(op) => write!(f, "{}", op),
+            BasicOpKind::Phi(op) => write!(f, "{}", op),
+            BasicOpKind::Sigma(op) => write!(f, "{}", op),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct VarNode<'tcx, T: PartialOrd + Clone + Bounded> {
     // The program variable which is represented.
-    v: &'tcx Place<'tcx>,
+    v: Place<'tcx>,
     // A Range associated to the variable.
     interval: Range<T>,
+    // The integer type of the variable, if it has one.
+    ty: Option<IntType>,
     // Used by the crop meet operator.
     abstract_state: char,
+    // The bits of the variable known to be 0 or 1, kept no less precise
+    // than the interval.
+    known: KnownBits,
+    // The congruence of the variable, also kept no less precise than the
+    // interval.
+    cong: Congruence<T>,
 }
-impl<'tcx, T: PartialOrd + Clone + Bounded> VarNode<'tcx, T> {
-    pub fn new(v: &'tcx Place<'tcx>) -> Self {
+impl<'tcx, T: PrimInt> VarNode<'tcx, T> {
+    pub fn new(v: Place<'tcx>, ty: Option<IntType>) -> Self {
+        let interval = ty.map_or(Range::default(), |ty| ty.full_range());
         Self {
             v,
-            interval: Range::default(),
+            known: KnownBits::from_range(&interval),
+            cong: Congruence::from_range(&interval),
+            interval,
+            ty,
             abstract_state: '?',
         }
     }
 
-    /// Initializes the value of the node.
+    /// Initializes the node before solving: a variable defined by an
+    /// operation of the graph starts unknown and only grows as it is
+    /// evaluated, while one defined `outside` of it, such as an argument, may
+    /// hold any value of its type.
     pub fn init(&mut self, outside: bool) {
-        let value = self.get_value();
-
-        // if let Some(ci) = value.as_constant_int() {
-        //     let tmp = ci.get_value();
-        //     if tmp.bits() < MAX_BIT_INT {
-        //         self.set_range(Range::new(
-        //             tmp.extend_bits(MAX_BIT_INT),
-        //             tmp.extend_bits(MAX_BIT_INT),
-        //         ));
-        //     } else {
-        //         self.set_range(Range::new(tmp, tmp));
-        //     }
-        // } else {
-        //     if !outside {
-        //         self.set_range(Range::new(MIN, MAX));
-        //     } else {
-        //         self.set_range(Range::new(MIN, MAX));
-        //     }
-        // }
+        if outside {
+            self.set_range(Range::default());
+        } else {
+            self.set_range(Range::unknown());
+        }
+        self.abstract_state = '?';
     }
 
     /// Returns the range of the variable represented by this node.
@@ -206,46 +858,158 @@ impl<'tcx, T: PartialOrd + Clone + Bounded> VarNode<'tcx, T> {
         &self.v
     }
 
-    /// Changes the status of the variable represented by this node.
+    /// Returns the integer type of the variable, if it has one.
+    pub fn get_type(&self) -> Option<IntType> {
+        self.ty
+    }
+
+    /// Changes the status of the variable represented by this node. The new
+    /// range is wrapped into the type of the variable.
     pub fn set_range(&mut self, new_interval: Range<T>) {
-        self.interval = new_interval;
+        self.interval = match self.ty {
+            Some(ty) => ty.fit(&new_interval),
+            None => new_interval,
+        };
 
         // Check if lower bound is greater than upper bound. If it is,
         // set range to empty.
-        // if self.interval.get_lower().sgt(self.interval.get_upper()) {
-        //     self.interval.set_empty();
-        // }
+        if self.interval.is_regular() && self.interval.get_lower() > self.interval.get_upper() {
+            self.interval.set_empty();
+        }
+        self.known = KnownBits::from_range(&self.interval);
+        self.cong = Congruence::from_range(&self.interval);
+    }
+
+    /// Returns the bits of the variable known to be 0 or 1.
+    pub fn get_known_bits(&self) -> &KnownBits {
+        &self.known
+    }
+
+    /// Refines the node with bits computed by a bitwise transfer function:
+    /// the bits are wrapped into the type of the variable and met with those
+    /// of the interval, and the interval is then tightened to the bits.
+    pub fn set_known_bits(&mut self, known: KnownBits) {
+        let known = match self.ty {
+            Some(ty) => known.fit(ty),
+            None => known,
+        };
+        self.known = known.meet(&KnownBits::from_range(&self.interval));
+        if self.interval.is_regular() {
+            self.interval = self.interval.meet(&self.known.to_range());
+        }
+    }
+
+    /// Returns the congruence of the variable.
+    pub fn get_congruence(&self) -> &Congruence<T> {
+        &self.cong
+    }
+
+    /// Replaces the congruence of the variable, leaving the interval alone
+    /// until `reduce_congruence`.
+    pub fn set_congruence(&mut self, cong: Congruence<T>) {
+        self.cong = cong;
+    }
+
+    /// Tightens the interval and the congruence to each other, as in
+    /// `RangeCongruence::reduce`.
+    pub fn reduce_congruence(&mut self) {
+        let product = RangeCongruence::new(self.interval.clone(), self.cong);
+        if self.interval.is_regular() {
+            self.interval = product.range;
+            self.known = self.known.meet(&KnownBits::from_range(&self.interval));
+        }
+        self.cong = product.cong;
     }
 
     /// Pretty print.
-    pub fn print(&self, os: &mut dyn std::io::Write) {
-        // Implementation of pretty printing using the `os` writer.
+    pub fn print(&self, os: &mut dyn std::io::Write) -> std::io::Result<()>
+    where
+        T: fmt::Display,
+    {
+        writeln!(os, "{}", self)
     }
 
     pub fn get_abstract_state(&self) -> char {
         self.abstract_state
     }
 
-    /// The possible states are '0', '+', '-', and '?'.
-    pub fn store_abstract_state(&mut self) {
-        // Implementation of storing the abstract state.
+    /// Records which bounds the widening phase moved, comparing the current
+    /// range with the range `before` it: '-' if only the lower bound went
+    /// down, '+' if only the upper bound went up, '?' if both did and '0' if
+    /// neither did. Every bound counts as widened when `before` was not a
+    /// regular range.
+    pub fn store_abstract_state(&mut self, before: &Range<T>) {
+        if !before.is_regular() {
+            self.abstract_state = '?';
+            return;
+        }
+        if !self.interval.is_regular() {
+            self.abstract_state = '0';
+            return;
+        }
+        let lower = self.interval.get_lower() < before.get_lower();
+        let upper = self.interval.get_upper() > before.get_upper();
+        self.abstract_state = match (lower, upper) {
+            (true, true) => '?',
+            (true, false) => '-',
+            (false, true) => '+',
+            (false, false) => '0',
+        };
+    }
+
+    /// Crop meet: moves the bounds that widening moved inwards to those of
+    /// `new`, wrapped into the type of the variable, leaving the other bounds
+    /// alone. Each bound is cropped at most once, so narrowing terminates.
+    /// Returns whether the range changed.
+    pub fn crop(&mut self, new: &Range<T>) -> bool {
+        let new = match self.ty {
+            Some(ty) => ty.fit(new),
+            None => new.clone(),
+        };
+        if !self.interval.is_regular() || !new.is_regular() {
+            return false;
+        }
+        let (mut lower, mut upper) = (self.interval.get_lower(), self.interval.get_upper());
+        let mut state = self.abstract_state;
+        let mut changed = false;
+        if matches!(state, '-' | '?') && new.get_lower() > lower {
+            lower = new.get_lower();
+            state = if state == '?' { '+' } else { '0' };
+            changed = true;
+        }
+        if matches!(state, '+' | '?') && new.get_upper() < upper {
+            upper = new.get_upper();
+            state = if state == '?' { '-' } else { '0' };
+            changed = true;
+        }
+        if changed {
+            self.set_range(Range::new(lower, upper, RangeType::Regular));
+            self.abstract_state = state;
+        }
+        changed
     }
 }
-#[derive(Debug)]
-pub struct ValueBranchMap<'tcx, T: PartialOrd + Clone + Bounded> {
-    v: &'tcx Place<'tcx>,         // The value associated with the branch
-    bb_true: &'tcx BasicBlock,    // True side of the branch
-    bb_false: &'tcx BasicBlock,   // False side of the branch
-    itv_t: IntervalType<'tcx, T>, // Interval for the true side
-    itv_f: IntervalType<'tcx, T>,
+/// Prints `name: range`.
+impl<T: PrimInt + fmt::Display> fmt::Display for VarNode<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.v, self.interval)
+    }
+}
+#[derive(Debug, Clone)]
+pub struct ValueBranchMap<T: PartialOrd + Clone + Bounded> {
+    v: VarId,               // The value associated with the branch
+    bb_true: BasicBlock,    // True side of the branch
+    bb_false: BasicBlock,   // False side of the branch
+    itv_t: IntervalType<T>, // Interval for the true side
+    itv_f: IntervalType<T>,
 }
-impl<'tcx, T: PartialOrd + Clone + Bounded> ValueBranchMap<'tcx, T> {
+impl<T: PartialOrd + Clone + Bounded> ValueBranchMap<T> {
     pub fn new(
-        v: &'tcx Place<'tcx>,
-        bb_true: &'tcx BasicBlock,
-        bb_false: &'tcx BasicBlock,
-        itv_t: IntervalType<'tcx, T>,
-        itv_f: IntervalType<'tcx, T>,
+        v: VarId,
+        bb_true: BasicBlock,
+        bb_false: BasicBlock,
+        itv_t: IntervalType<T>,
+        itv_f: IntervalType<T>,
     ) -> Self {
         Self {
             v,
@@ -258,26 +1022,38 @@ impl<'tcx, T: PartialOrd + Clone + Bounded> ValueBranchMap<'tcx, T> {
 
     /// Get the "false side" of the branch
     pub fn get_bb_false(&self) -> &BasicBlock {
-        self.bb_false
+        &self.bb_false
     }
 
     /// Get the "true side" of the branch
     pub fn get_bb_true(&self) -> &BasicBlock {
-        self.bb_true
+        &self.bb_true
     }
 
     /// Get the interval associated with the true side of the branch
-    pub fn get_itv_t(&self) -> &IntervalType<'tcx, T> {
+    pub fn get_itv_t(&self) -> &IntervalType<T> {
         &self.itv_t
     }
 
     /// Get the interval associated with the false side of the branch
-    pub fn get_itv_f(&self) -> &IntervalType<'tcx, T> {
+    pub fn get_itv_f(&self) -> &IntervalType<T> {
         &self.itv_f
     }
 
+    /// The interval for the side of the branch that leads to `bb`, if it
+    /// leads there.
+    pub fn get_itv_to(&self, bb: BasicBlock) -> Option<&IntervalType<T>> {
+        if bb == self.bb_true {
+            Some(&self.itv_t)
+        } else if bb == self.bb_false {
+            Some(&self.itv_f)
+        } else {
+            None
+        }
+    }
+
     /// Get the value associated with the branch
-    pub fn get_v(&self) -> &Place<'tcx> {
+    pub fn get_v(&self) -> VarId {
         self.v
     }
 
@@ -301,14 +1077,131 @@ impl<'tcx, T: PartialOrd + Clone + Bounded> ValueBranchMap<'tcx, T> {
 //     Place(Place<'tcx>),
 // }
 
-pub type VarNodes<'tcx, T> = HashMap<&'tcx Place<'tcx>, VarNode<'tcx, T>>;
-// pub type VarNodes<'tcx, T> = HashMap<&'tcx  Place<'tcx>, VarNode<'tcx,  T>>;
+/// The variables of a constraint graph. The nodes are stored in a vector
+/// indexed by `VarId`, and each place maps to the index of its node.
+#[derive(Debug, Clone)]
+pub struct VarNodes<'tcx, T: PartialOrd + Clone + Bounded> {
+    nodes: IndexVec<VarId, VarNode<'tcx, T>>,
+    ids: HashMap<Place<'tcx>, VarId>,
+}
+
+impl<'tcx, T: PrimInt> VarNodes<'tcx, T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: IndexVec::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The index of the node of `place`, if it has one.
+    pub fn id_of(&self, place: &Place<'tcx>) -> Option<VarId> {
+        self.ids.get(place).copied()
+    }
+
+    pub fn contains_key(&self, place: &Place<'tcx>) -> bool {
+        self.ids.contains_key(place)
+    }
+
+    /// Stores `node` as the node of its place, replacing the one it had.
+    pub fn insert(&mut self, node: VarNode<'tcx, T>) -> VarId {
+        let place = *node.get_value();
+        if let Some(id) = self.id_of(&place) {
+            self.nodes[id] = node;
+            return id;
+        }
+        let id = self.nodes.push(node);
+        self.ids.insert(place, id);
+        id
+    }
+
+    /// The index of the node of `place`, which gets `default()` if it has
+    /// none.
+    pub fn id_or_insert_with(
+        &mut self,
+        place: Place<'tcx>,
+        default: impl FnOnce() -> VarNode<'tcx, T>,
+    ) -> VarId {
+        match self.id_of(&place) {
+            Some(id) => id,
+            None => self.insert(default()),
+        }
+    }
+
+    pub fn get(&self, place: &Place<'tcx>) -> Option<&VarNode<'tcx, T>> {
+        self.id_of(place).map(|id| &self.nodes[id])
+    }
+
+    pub fn get_mut(&mut self, place: &Place<'tcx>) -> Option<&mut VarNode<'tcx, T>> {
+        self.id_of(place).map(|id| &mut self.nodes[id])
+    }
+
+    pub fn get_by_id(&self, id: VarId) -> Option<&VarNode<'tcx, T>> {
+        self.nodes.get(id)
+    }
+
+    /// The place of the node `id`.
+    pub fn place(&self, id: VarId) -> Place<'tcx> {
+        *self.nodes[id].get_value()
+    }
+
+    /// The indices of the nodes, in the order they were added.
+    pub fn ids(&self) -> impl Iterator<Item = VarId> {
+        (0..self.nodes.len()).map(VarId::from_usize)
+    }
+
+    /// The nodes with their indices, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (VarId, &VarNode<'tcx, T>)> {
+        self.nodes.iter_enumerated()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (VarId, &mut VarNode<'tcx, T>)> {
+        self.nodes.iter_enumerated_mut()
+    }
+}
+
+impl<T: PrimInt> Default for VarNodes<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'tcx, T: PartialOrd + Clone + Bounded> Index<VarId> for VarNodes<'tcx, T> {
+    type Output = VarNode<'tcx, T>;
+
+    fn index(&self, id: VarId) -> &Self::Output {
+        &self.nodes[id]
+    }
+}
+
+impl<T: PartialOrd + Clone + Bounded> IndexMut<VarId> for VarNodes<'_, T> {
+    fn index_mut(&mut self, id: VarId) -> &mut Self::Output {
+        &mut self.nodes[id]
+    }
+}
+
+/// The node of a place, which must have one.
+impl<'tcx, T: PartialOrd + Clone + Bounded> Index<&Place<'tcx>> for VarNodes<'tcx, T> {
+    type Output = VarNode<'tcx, T>;
+
+    fn index(&self, place: &Place<'tcx>) -> &Self::Output {
+        let id = self.ids.get(place).expect("no node for the place");
+        &self.nodes[*id]
+    }
+}
 
-pub type GenOprs<'tcx, T> = HashSet<&'tcx BasicOp<'tcx, T>>;
-pub type UseMap<'tcx, T> = HashMap<&'tcx Place<'tcx>, HashSet<&'tcx BasicOp<'tcx, T>>>;
-pub type SymbMap<'tcx, T> = HashMap<&'tcx Place<'tcx>, HashSet<&'tcx BasicOp<'tcx, T>>>;
-pub type DefMap<'tcx, T> = HashMap<&'tcx Place<'tcx>, &'tcx BasicOp<'tcx, T>>;
-pub type ValuesBranchMap<'tcx, T> = HashMap<&'tcx Place<'tcx>, ValueBranchMap<'tcx, T>>;
+pub type GenOprs<T> = IndexVec<OpId, BasicOpKind<T>>;
+pub type UseMap = HashMap<VarId, HashSet<OpId>>;
+pub type SymbMap = HashMap<VarId, HashSet<OpId>>;
+pub type DefMap = HashMap<VarId, OpId>;
+pub type ValuesBranchMap<T> = HashMap<VarId, ValueBranchMap<T>>;
 // pub type ValuesSwitchMap<'tcx, T> = HashMap<&'tcx Place<'tcx>, ValueSwitchMap<'tcx, T>>;
 // impl<'tcx, T: fmt::Debug + PartialOrd + Clone + Bounded> fmt::Debug for ValueBranchMap<'tcx, T> {
 //     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -0,0 +1,122 @@
+use num_traits::PrimInt;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, Write};
+
+use super::domain::{BasicOpKind, GenOprs, OpSource, SymbMap, VarId, VarNodes};
+
+/// Writes the constraint graph as a Graphviz digraph named `name`.
+///
+/// Variables are boxes labelled with their name and range, and operations
+/// are ellipses labelled with their kind and intersection. Solid edges carry
+/// values from the sources of an operation to its sink; dashed edges go from
+/// a symbolic bound to the operations whose intersection depends on it.
+/// Every component of more than one variable is drawn as a cluster holding
+/// its variables and the operations that define them.
+pub fn write_dot<T: PrimInt + fmt::Display>(
+    os: &mut dyn Write,
+    name: &str,
+    vars: &VarNodes<'_, T>,
+    oprs: &GenOprs<T>,
+    symbmap: &SymbMap,
+    components: &[HashSet<VarId>],
+) -> io::Result<()> {
+    writeln!(os, "digraph \"{}\" {{", escape(name))?;
+    writeln!(os, "    node [fontname = \"monospace\"];")?;
+
+    // Each variable and operation is drawn in the cluster of its component,
+    // or at the top level when the component is a single variable.
+    let mut cluster_of: HashMap<VarId, usize> = HashMap::new();
+    for (index, component) in components.iter().enumerate() {
+        if component.len() > 1 {
+            for &id in component {
+                cluster_of.insert(id, index);
+            }
+        }
+    }
+    let mut lines: Vec<Vec<String>> = vec![Vec::new(); components.len() + 1];
+    let top = components.len();
+    for (id, node) in vars.iter() {
+        let label = format!("{:?}\n{}", node.get_value(), node.get_range());
+        lines[*cluster_of.get(&id).unwrap_or(&top)].push(format!(
+            "{:?} [shape = box, label = \"{}\"];",
+            id,
+            escape(&label)
+        ));
+    }
+    for (id, op) in oprs.iter_enumerated() {
+        let label = format!(
+            "{}\n∩ {}",
+            kind_label(vars, op),
+            op.get_intersect().label(vars)
+        );
+        let cluster = cluster_of.get(&op.get_sink()).unwrap_or(&top);
+        lines[*cluster].push(format!(
+            "{:?} [shape = ellipse, label = \"{}\"];",
+            id,
+            escape(&label)
+        ));
+    }
+    for (index, cluster) in lines[..top].iter().enumerate() {
+        if cluster.is_empty() {
+            continue;
+        }
+        writeln!(os, "    subgraph cluster_{} {{", index)?;
+        writeln!(os, "        style = dashed;")?;
+        for line in cluster {
+            writeln!(os, "        {}", line)?;
+        }
+        writeln!(os, "    }}")?;
+    }
+    for line in &lines[top] {
+        writeln!(os, "    {}", line)?;
+    }
+
+    for (id, op) in oprs.iter_enumerated() {
+        for source in op.get_sources() {
+            writeln!(os, "    {:?} -> {:?};", source, id)?;
+        }
+        writeln!(os, "    {:?} -> {:?};", id, op.get_sink())?;
+    }
+    // Sorted so that the output does not depend on the order of the map.
+    let mut control: Vec<_> = symbmap
+        .iter()
+        .flat_map(|(&bound, ops)| ops.iter().map(move |&op| (bound, op)))
+        .collect();
+    control.sort();
+    for (bound, op) in control {
+        writeln!(os, "    {:?} -> {:?} [style = dashed];", bound, op)?;
+    }
+    writeln!(os, "}}")
+}
+
+/// The operator of an operation and its operands, as in `Add(_3, [1, 1])`.
+fn kind_label<T: PrimInt + fmt::Display>(vars: &VarNodes<'_, T>, op: &BasicOpKind<T>) -> String {
+    match op {
+        BasicOpKind::Unary(op) => format!("{}({})", op.kind, source_label(vars, &op.source)),
+        BasicOpKind::Binary(op) => format!(
+            "{:?}({}, {})",
+            op.op,
+            source_label(vars, &op.lhs),
+            source_label(vars, &op.rhs)
+        ),
+        BasicOpKind::Phi(_) => String::from("phi"),
+        BasicOpKind::Sigma(_) => String::from("sigma"),
+    }
+}
+
+/// A variable operand by the name of its place, or a constant by its range.
+fn source_label<T: PrimInt + fmt::Display>(vars: &VarNodes<'_, T>, source: &OpSource<T>) -> String {
+    match source {
+        OpSource::Var(id) => format!("{:?}", vars.place(*id)),
+        OpSource::Const(range) => range.to_string(),
+    }
+}
+
+/// Escapes a label for a double-quoted DOT string.
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
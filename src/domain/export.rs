@@ -0,0 +1,219 @@
+use num_traits::PrimInt;
+use rustc_hir::def_id::{DefId, LocalDefId, LOCAL_CRATE};
+use rustc_hir::ConstContext;
+use rustc_middle::mir::{Body, Local, Operand, Rvalue, StatementKind, VarDebugInfoContents};
+use rustc_middle::ty::TyCtxt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+#[cfg(feature = "float")]
+use super::float::FloatRange;
+use super::range::{Range, RangeType};
+use super::ConstraintGraph::ConstraintGraph;
+use crate::SSA::SsaBody::{classify_statement, SsaStatementKind};
+
+/// Version of the results format. Bump it whenever a field is renamed,
+/// removed or changes meaning; adding fields is backwards compatible.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `{"kind": "regular", "lo": "0", "hi": "100"}`, with the bounds as decimal
+/// strings because JSON numbers cannot hold every 128-bit value.
+pub fn range_to_json<T: PrimInt + fmt::Display>(range: &Range<T>) -> Value {
+    match range.rtype {
+        RangeType::Unknown => json!({ "kind": "unknown" }),
+        RangeType::Empty => json!({ "kind": "empty" }),
+        RangeType::Regular => json!({
+            "kind": "regular",
+            "lo": range.get_lower().to_string(),
+            "hi": range.get_upper().to_string(),
+        }),
+    }
+}
+
+/// The results file of a crate, wrapping the output of `function_to_json`.
+pub fn crate_to_json(crate_name: &str, functions: Vec<Value>) -> Value {
+    json!({
+        "schema_version": SCHEMA_VERSION,
+        "crate": crate_name,
+        "functions": functions,
+    })
+}
+
+/// Runs `analyze` on every function of the crate that has MIR and wraps its
+/// results with `crate_to_json`. The bodies of constants and statics are
+/// skipped: they are only evaluated at compile time.
+pub fn crate_results(tcx: TyCtxt<'_>, analyze: impl FnMut(LocalDefId) -> Value) -> Value {
+    let functions = tcx
+        .hir()
+        .body_owners()
+        .filter(|&def_id| {
+            tcx.is_mir_available(def_id)
+                && matches!(
+                    tcx.hir().body_const_context(def_id),
+                    None | Some(ConstContext::ConstFn)
+                )
+        })
+        .map(analyze)
+        .collect();
+    crate_to_json(tcx.crate_name(LOCAL_CRATE).as_str(), functions)
+}
+
+pub fn write_results(path: &Path, results: &Value) -> io::Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(writer, results)?;
+    Ok(())
+}
+
+/// The ranges of every SSA variable of `body` and the phi and sigma
+/// statements the analysis relied on. `ssa_origin` maps every renamed local
+/// to the local it was split from, as returned by `PassRunner::run_pass`.
+pub fn function_to_json<'tcx, T: PrimInt + fmt::Display>(
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    body: &Body<'tcx>,
+    ssa_origin: &HashMap<Local, Local>,
+    graph: &ConstraintGraph<'_, 'tcx, T>,
+) -> Value {
+    let source_map = tcx.sess.source_map();
+    let mut vars: Vec<_> = graph.vars.iter().map(|(_, node)| node).collect();
+    vars.sort_by_cached_key(|node| {
+        let place = node.get_value();
+        (place.local, format!("{:?}", place))
+    });
+    let variables: Vec<Value> = vars
+        .into_iter()
+        .map(|node| {
+            let place = node.get_value();
+            let origin = ssa_origin.get(&place.local).copied().unwrap_or(place.local);
+            let span = body
+                .local_decls
+                .get(place.local)
+                .map(|decl| source_map.span_to_diagnostic_string(decl.source_info.span));
+            json!({
+                "name": format!("{:?}", place),
+                "origin": format!("{:?}", origin),
+                "source_name": source_name(body, origin),
+                "span": span,
+                "type": node.get_type().map(|ty| ty.to_string()),
+                "range": range_to_json(node.get_range()),
+            })
+        })
+        .collect();
+    #[cfg(feature = "float")]
+    let variables = [variables, float_variables(body, ssa_origin, graph)].concat();
+    json!({
+        "name": tcx.def_path_str(def_id),
+        "variables": variables,
+        "facts": ssa_facts(body, graph),
+    })
+}
+
+/// `{"kind": "float", "lo": "-inf", "hi": "1.5", "nan": true}`, without the
+/// bounds when the range holds no number.
+#[cfg(feature = "float")]
+pub fn float_range_to_json(range: &FloatRange) -> Value {
+    match range.bounds() {
+        Some((lo, hi)) => json!({
+            "kind": "float",
+            "lo": lo.to_string(),
+            "hi": hi.to_string(),
+            "nan": range.may_be_nan(),
+        }),
+        None => json!({ "kind": "float", "nan": range.may_be_nan() }),
+    }
+}
+
+#[cfg(feature = "float")]
+fn float_variables<T: PrimInt>(
+    body: &Body<'_>,
+    ssa_origin: &HashMap<Local, Local>,
+    graph: &ConstraintGraph<'_, '_, T>,
+) -> Vec<Value> {
+    let mut vars: Vec<_> = graph.float_vars.iter().collect();
+    vars.sort_by_cached_key(|node| {
+        let place = node.get_value();
+        (place.local, format!("{:?}", place))
+    });
+    vars.into_iter()
+        .map(|node| {
+            let place = node.get_value();
+            let origin = ssa_origin.get(&place.local).copied().unwrap_or(place.local);
+            json!({
+                "name": format!("{:?}", place),
+                "origin": format!("{:?}", origin),
+                "source_name": source_name(body, origin),
+                "type": node.get_type().to_string(),
+                "range": float_range_to_json(node.get_range()),
+            })
+        })
+        .collect()
+}
+
+/// The user-visible name of a local, if it has one.
+fn source_name(body: &Body<'_>, local: Local) -> Option<String> {
+    body.var_debug_info
+        .iter()
+        .find_map(|info| match &info.value {
+            VarDebugInfoContents::Place(place)
+                if place.local == local && place.projection.is_empty() =>
+            {
+                Some(info.name.to_string())
+            }
+            _ => None,
+        })
+}
+
+fn operand_name(op: &Operand<'_>) -> String {
+    match op {
+        Operand::Copy(place) | Operand::Move(place) => format!("{:?}", place),
+        Operand::Constant(c) => format!("{}", c),
+    }
+}
+
+/// Every phi and sigma of `body`, with the branch condition that constrains
+/// each sigma.
+fn ssa_facts<'tcx, T: PrimInt + fmt::Display>(
+    body: &Body<'tcx>,
+    graph: &ConstraintGraph<'_, 'tcx, T>,
+) -> Vec<Value> {
+    let mut facts = Vec::new();
+    for (bb, block_data) in body.basic_blocks.iter_enumerated() {
+        for stmt in block_data.statements.iter() {
+            let StatementKind::Assign(box (dest, Rvalue::Aggregate(_, operands))) = &stmt.kind
+            else {
+                continue;
+            };
+            match classify_statement(body, stmt) {
+                SsaStatementKind::Phi => facts.push(json!({
+                    "kind": "phi",
+                    "block": format!("{:?}", bb),
+                    "dest": format!("{:?}", dest),
+                    "sources": operands.iter().map(operand_name).collect::<Vec<_>>(),
+                })),
+                SsaStatementKind::Sigma => {
+                    let source = operands.iter().next().and_then(|op| op.place());
+                    let condition = source.and_then(|source| {
+                        graph
+                            .values_branchmap
+                            .get(&graph.vars.id_of(&source)?)
+                            .and_then(|vbm| vbm.get_itv_to(bb))
+                            .map(|itv| itv.label(&graph.vars))
+                    });
+                    facts.push(json!({
+                        "kind": "sigma",
+                        "block": format!("{:?}", bb),
+                        "dest": format!("{:?}", dest),
+                        "source": source.map(|source| format!("{:?}", source)),
+                        "condition": condition,
+                    }));
+                }
+                SsaStatementKind::Plain => {}
+            }
+        }
+    }
+    facts
+}
@@ -0,0 +1,724 @@
+//! Intervals of `f32` and `f64` values.
+//!
+//! Bounds are kept as `f64` for both types and ordered with `f64::total_cmp`,
+//! so `-0.0` sorts below `+0.0` and an interval says which zeros it holds.
+//! Every computed bound is rounded outwards to the type of the values, and
+//! NaN is tracked by a separate flag since it has no place in the order.
+
+use rustc_index::IndexVec;
+use rustc_middle::mir::{BasicBlock, BinOp, ConstOperand, Place};
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use super::const_eval::const_bits;
+use super::domain::negate_predicate;
+use super::lattice::Lattice;
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum FloatType {
+    F32,
+    F64,
+}
+
+impl fmt::Display for FloatType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FloatType::F32 => write!(f, "f32"),
+            FloatType::F64 => write!(f, "f64"),
+        }
+    }
+}
+
+impl FloatType {
+    /// The type of an `f32` or `f64` value.
+    pub fn from_ty(ty: Ty<'_>) -> Option<Self> {
+        match ty.kind() {
+            ty::Float(ty::FloatTy::F32) => Some(FloatType::F32),
+            ty::Float(ty::FloatTy::F64) => Some(FloatType::F64),
+            _ => None,
+        }
+    }
+
+    /// The value of a constant of this type, from its raw bits.
+    pub fn value_from_bits(&self, raw: u128) -> f64 {
+        match self {
+            FloatType::F32 => f32::from_bits(raw as u32) as f64,
+            FloatType::F64 => f64::from_bits(raw as u64),
+        }
+    }
+
+    /// The next value of the type above `x`.
+    pub fn next_up(&self, x: f64) -> f64 {
+        match self {
+            FloatType::F32 => next_up_f32(x as f32) as f64,
+            FloatType::F64 => next_up_f64(x),
+        }
+    }
+
+    /// The next value of the type below `x`.
+    pub fn next_down(&self, x: f64) -> f64 {
+        -self.next_up(-x)
+    }
+
+    /// Rounds a lower bound down and an upper bound up to values of the type.
+    fn round_out(&self, down: f64, up: f64) -> (f64, f64) {
+        match self {
+            FloatType::F32 => {
+                let (mut lo, mut hi) = (down as f32, up as f32);
+                if lo as f64 > down {
+                    lo = next_down_f32(lo);
+                }
+                if (hi as f64) < up {
+                    hi = next_up_f32(hi);
+                }
+                (lo as f64, hi as f64)
+            }
+            FloatType::F64 => (down, up),
+        }
+    }
+}
+
+/// The value of a float constant, evaluated like integer constants by
+/// `const_bits`. Returns `None` for other constants and for constants that
+/// cannot be evaluated.
+pub fn const_float<'tcx>(tcx: TyCtxt<'tcx>, c: &ConstOperand<'tcx>) -> Option<f64> {
+    let ty = FloatType::from_ty(c.const_.ty())?;
+    Some(ty.value_from_bits(const_bits(tcx, c)?))
+}
+
+fn next_up_f64(x: f64) -> f64 {
+    if x.is_nan() || x == f64::INFINITY {
+        return x;
+    }
+    if x == 0.0 {
+        return f64::from_bits(1);
+    }
+    let bits = x.to_bits();
+    f64::from_bits(if x > 0.0 { bits + 1 } else { bits - 1 })
+}
+
+fn next_up_f32(x: f32) -> f32 {
+    if x.is_nan() || x == f32::INFINITY {
+        return x;
+    }
+    if x == 0.0 {
+        return f32::from_bits(1);
+    }
+    let bits = x.to_bits();
+    f32::from_bits(if x > 0.0 { bits + 1 } else { bits - 1 })
+}
+
+fn next_down_f32(x: f32) -> f32 {
+    -next_up_f32(-x)
+}
+
+fn total_min(a: f64, b: f64) -> f64 {
+    if a.total_cmp(&b) == Ordering::Greater {
+        b
+    } else {
+        a
+    }
+}
+
+fn total_max(a: f64, b: f64) -> f64 {
+    if a.total_cmp(&b) == Ordering::Less {
+        b
+    } else {
+        a
+    }
+}
+
+// Directed rounding of `f64` operations.
+//
+// Each function returns the correctly rounded result widened to a lower and
+// an upper bound of the exact result, using the rounding error that an
+// error-free transformation recovers. NaN results are returned as is.
+
+/// The bounds of a result `r` whose exact value is `r + err`.
+fn directed(r: f64, err: f64) -> (f64, f64) {
+    if err > 0.0 {
+        (r, next_up_f64(r))
+    } else if err < 0.0 {
+        (next_down_f64(r), r)
+    } else {
+        (r, r)
+    }
+}
+
+fn next_down_f64(x: f64) -> f64 {
+    -next_up_f64(-x)
+}
+
+/// The bounds of a finite result that rounded to an infinity.
+fn overflow(r: f64) -> (f64, f64) {
+    if r > 0.0 {
+        (f64::MAX, r)
+    } else {
+        (r, f64::MIN)
+    }
+}
+
+/// Subnormal results lose the low bits of the error, so they only get the
+/// neighbours on both sides.
+fn underflow(r: f64) -> (f64, f64) {
+    (next_down_f64(r), next_up_f64(r))
+}
+
+fn add_round(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    if !a.is_finite() || !b.is_finite() {
+        return (s, s);
+    }
+    if s.is_infinite() {
+        return overflow(s);
+    }
+    // TwoSum.
+    let bb = s - a;
+    let err = (a - (s - bb)) + (b - bb);
+    directed(s, err)
+}
+
+fn mul_round(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    if !a.is_finite() || !b.is_finite() || a == 0.0 || b == 0.0 {
+        return (p, p);
+    }
+    if p.is_infinite() {
+        return overflow(p);
+    }
+    if p.abs() < f64::MIN_POSITIVE {
+        return underflow(p);
+    }
+    directed(p, a.mul_add(b, -p))
+}
+
+fn div_round(a: f64, b: f64) -> (f64, f64) {
+    let q = a / b;
+    if !a.is_finite() || !b.is_finite() || a == 0.0 || b == 0.0 {
+        return (q, q);
+    }
+    if q.is_infinite() {
+        return overflow(q);
+    }
+    if q.abs() < f64::MIN_POSITIVE {
+        return underflow(q);
+    }
+    // `a - q * b` is exact, and `a / b - q` has its sign times the sign of b.
+    let r = (-q).mul_add(b, a);
+    directed(q, if b > 0.0 { r } else { -r })
+}
+
+/// A set of `f32` or `f64` values: the values between two bounds, and NaN if
+/// `nan` is set.
+#[derive(Debug, Clone, Copy)]
+pub struct FloatRange {
+    // The non-NaN values, if there are any.
+    bounds: Option<(f64, f64)>,
+    // Whether the set holds NaN.
+    nan: bool,
+}
+
+impl PartialEq for FloatRange {
+    fn eq(&self, other: &Self) -> bool {
+        let same = |a: f64, b: f64| a.total_cmp(&b) == Ordering::Equal;
+        self.nan == other.nan
+            && match (self.bounds, other.bounds) {
+                (Some((a, b)), Some((c, d))) => same(a, c) && same(b, d),
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+
+impl FloatRange {
+    /// The values from `lo` to `hi`, without NaN. An empty set of values if
+    /// `lo` is above `hi`.
+    pub fn new(lo: f64, hi: f64) -> Self {
+        assert!(!lo.is_nan() && !hi.is_nan(), "NaN is not a bound");
+        let bounds = (lo.total_cmp(&hi) != Ordering::Greater).then_some((lo, hi));
+        Self { bounds, nan: false }
+    }
+
+    /// The range of a single value, which may be NaN.
+    pub fn singleton(x: f64) -> Self {
+        if x.is_nan() {
+            Self::nan()
+        } else {
+            Self::new(x, x)
+        }
+    }
+
+    /// Only NaN.
+    pub fn nan() -> Self {
+        Self {
+            bounds: None,
+            nan: true,
+        }
+    }
+
+    /// The same values, with NaN added.
+    pub fn with_nan(&self) -> Self {
+        Self {
+            bounds: self.bounds,
+            nan: true,
+        }
+    }
+
+    pub fn bounds(&self) -> Option<(f64, f64)> {
+        self.bounds
+    }
+
+    pub fn may_be_nan(&self) -> bool {
+        self.nan
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_none() && !self.nan
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        if x.is_nan() {
+            return self.nan;
+        }
+        self.bounds.is_some_and(|(lo, hi)| {
+            lo.total_cmp(&x) != Ordering::Greater && x.total_cmp(&hi) != Ordering::Greater
+        })
+    }
+
+    fn contains_zero(&self) -> bool {
+        self.bounds.is_some_and(|(lo, hi)| lo <= 0.0 && hi >= 0.0)
+    }
+
+    fn has_infinity(&self) -> bool {
+        self.bounds
+            .is_some_and(|(lo, hi)| lo.is_infinite() || hi.is_infinite())
+    }
+
+    /// The values of `self` at or below `hi` and at or above `lo`.
+    fn clamp(&self, lo: f64, hi: f64) -> Option<(f64, f64)> {
+        let (a, b) = self.bounds?;
+        let (lo, hi) = (total_max(a, lo), total_min(b, hi));
+        (lo.total_cmp(&hi) != Ordering::Greater).then_some((lo, hi))
+    }
+
+    /// Applies `op` to every pair of bounds and takes the hull of the
+    /// results, which is exact for operations that are monotone in each
+    /// argument. Pairs without a value, such as `0 * inf`, give NaN; with
+    /// `signed_limits` the values near them, anything from zero to infinity
+    /// with the sign of the product of their signs, are added too.
+    fn corners(
+        &self,
+        other: &Self,
+        ty: FloatType,
+        op: fn(f64, f64) -> (f64, f64),
+        signed_limits: bool,
+    ) -> Self {
+        let mut nan = self.nan || other.nan;
+        let (Some((a, b)), Some((c, d))) = (self.bounds, other.bounds) else {
+            return Self { bounds: None, nan };
+        };
+        let mut bounds: Option<(f64, f64)> = None;
+        for (x, y) in [(a, c), (a, d), (b, c), (b, d)] {
+            let (down, up) = op(x, y);
+            let (down, up) = if !down.is_nan() {
+                ty.round_out(down, up)
+            } else if signed_limits {
+                nan = true;
+                let sign = x.signum() * y.signum();
+                let (zero, inf) = (sign * 0.0, sign * f64::INFINITY);
+                (total_min(zero, inf), total_max(zero, inf))
+            } else {
+                nan = true;
+                continue;
+            };
+            bounds = Some(match bounds {
+                Some((lo, hi)) => (total_min(lo, down), total_max(hi, up)),
+                None => (down, up),
+            });
+        }
+        Self { bounds, nan }
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            bounds: self.bounds.map(|(lo, hi)| (-hi, -lo)),
+            nan: self.nan,
+        }
+    }
+
+    pub fn add(&self, other: &Self, ty: FloatType) -> Self {
+        self.corners(other, ty, add_round, false)
+    }
+
+    pub fn sub(&self, other: &Self, ty: FloatType) -> Self {
+        self.add(&other.neg(), ty)
+    }
+
+    pub fn mul(&self, other: &Self, ty: FloatType) -> Self {
+        let result = self.corners(other, ty, mul_round, true);
+        // `0 * inf` inside the ranges, not only at a corner.
+        if (self.contains_zero() && other.has_infinity())
+            || (self.has_infinity() && other.contains_zero())
+        {
+            return result.with_nan();
+        }
+        result
+    }
+
+    /// Division is monotone on each side of zero, so the divisor is split
+    /// into its negative values (down to `-0.0`) and its positive values
+    /// (from `+0.0`), and the quotients joined.
+    pub fn div(&self, other: &Self, ty: FloatType) -> Self {
+        let negative = Self {
+            bounds: other.clamp(f64::NEG_INFINITY, -0.0),
+            nan: other.nan,
+        };
+        let positive = Self {
+            bounds: other.clamp(0.0, f64::INFINITY),
+            nan: other.nan,
+        };
+        let result = self
+            .corners(&negative, ty, div_round, true)
+            .join(&self.corners(&positive, ty, div_round, true));
+        // `0 / 0` and `inf / inf` inside the ranges.
+        if (self.contains_zero() && other.contains_zero())
+            || (self.has_infinity() && other.has_infinity())
+        {
+            return result.with_nan();
+        }
+        result
+    }
+
+    /// The values of `self` that make `self op bound` hold, or fail if
+    /// `holds` is false, for some value of `bound`. Every ordered comparison
+    /// with NaN is false, so NaN only survives where the comparison must be
+    /// false, or with `!=`.
+    pub fn refine(&self, op: BinOp, bound: &Self, holds: bool, ty: FloatType) -> Self {
+        let nan_satisfies = holds == (op == BinOp::Ne);
+        if nan_satisfies && bound.nan {
+            // Any value of `self` compares as required against a NaN bound.
+            return *self;
+        }
+        let nan = self.nan && nan_satisfies;
+        let Some((l, u)) = bound.bounds else {
+            return Self { bounds: None, nan };
+        };
+        // `x <= -0.0` holds for `+0.0`, and `x >= +0.0` for `-0.0`.
+        let upper_zero = |u: f64| if u == 0.0 { 0.0 } else { u };
+        let lower_zero = |l: f64| if l == 0.0 { -0.0 } else { l };
+        let (lo, hi) = match if holds { op } else { negate_predicate(op) } {
+            BinOp::Lt => (f64::NEG_INFINITY, ty.next_down(u)),
+            BinOp::Le => (f64::NEG_INFINITY, upper_zero(u)),
+            BinOp::Gt => (ty.next_up(l), f64::INFINITY),
+            BinOp::Ge => (lower_zero(l), f64::INFINITY),
+            BinOp::Eq => (lower_zero(l), upper_zero(u)),
+            _ => (f64::NEG_INFINITY, f64::INFINITY),
+        };
+        Self {
+            bounds: self.clamp(lo, hi),
+            nan,
+        }
+    }
+}
+
+impl Lattice for FloatRange {
+    fn bottom() -> Self {
+        Self {
+            bounds: None,
+            nan: false,
+        }
+    }
+
+    fn top() -> Self {
+        Self {
+            bounds: Some((f64::NEG_INFINITY, f64::INFINITY)),
+            nan: true,
+        }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        let bounds = match (self.bounds, other.bounds) {
+            (Some((a, b)), Some((c, d))) => Some((total_min(a, c), total_max(b, d))),
+            (bounds, None) | (None, bounds) => bounds,
+        };
+        Self {
+            bounds,
+            nan: self.nan || other.nan,
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        Self {
+            bounds: other.bounds.and_then(|(lo, hi)| self.clamp(lo, hi)),
+            nan: self.nan && other.nan,
+        }
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        (!self.nan || other.nan)
+            && match self.bounds {
+                Some((lo, hi)) => other.contains(lo) && other.contains(hi),
+                None => true,
+            }
+    }
+
+    /// Moves every bound that grew to the matching infinity.
+    fn widen(&self, other: &Self) -> Self {
+        let (Some((a, b)), Some((c, d))) = (self.bounds, other.bounds) else {
+            return self.join(other);
+        };
+        let lo = if c.total_cmp(&a) == Ordering::Less {
+            f64::NEG_INFINITY
+        } else {
+            a
+        };
+        let hi = if d.total_cmp(&b) == Ordering::Greater {
+            f64::INFINITY
+        } else {
+            b
+        };
+        Self {
+            bounds: Some((lo, hi)),
+            nan: self.nan || other.nan,
+        }
+    }
+
+    /// Replaces the infinite bounds with those of `other`.
+    fn narrow(&self, other: &Self) -> Self {
+        let (Some((a, b)), Some((c, d))) = (self.bounds, other.bounds) else {
+            return self.meet(other);
+        };
+        let lo = if a == f64::NEG_INFINITY { c } else { a };
+        let hi = if b == f64::INFINITY { d } else { b };
+        Self {
+            bounds: (lo.total_cmp(&hi) != Ordering::Greater).then_some((lo, hi)),
+            nan: self.nan && other.nan,
+        }
+    }
+}
+
+/// Prints `[lo, hi]`, with ` ∪ NaN` when the range may be NaN.
+impl fmt::Display for FloatRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.bounds, self.nan) {
+            (Some((lo, hi)), nan) => {
+                write!(f, "[{}, {}]", lo, hi)?;
+                if nan {
+                    write!(f, " ∪ NaN")?;
+                }
+                Ok(())
+            }
+            (None, true) => write!(f, "NaN"),
+            (None, false) => write!(f, "∅"),
+        }
+    }
+}
+
+rustc_index::newtype_index! {
+    /// Index of a variable in `FloatVarNodes`.
+    #[orderable]
+    #[debug_format = "f{}"]
+    pub struct FloatVarId {}
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FloatVarNode<'tcx> {
+    // The program variable which is represented.
+    v: Place<'tcx>,
+    // The values the variable may hold.
+    interval: FloatRange,
+    // The float type of the variable.
+    ty: FloatType,
+}
+
+impl<'tcx> FloatVarNode<'tcx> {
+    pub fn new(v: Place<'tcx>, ty: FloatType) -> Self {
+        Self {
+            v,
+            interval: FloatRange::top(),
+            ty,
+        }
+    }
+
+    pub fn get_range(&self) -> &FloatRange {
+        &self.interval
+    }
+
+    pub fn set_range(&mut self, new_interval: FloatRange) {
+        self.interval = new_interval;
+    }
+
+    pub fn get_value(&self) -> &Place<'tcx> {
+        &self.v
+    }
+
+    pub fn get_type(&self) -> FloatType {
+        self.ty
+    }
+}
+
+/// Prints `name: range`.
+impl fmt::Display for FloatVarNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.v, self.interval)
+    }
+}
+
+pub type FloatVarNodes<'tcx> = IndexVec<FloatVarId, FloatVarNode<'tcx>>;
+
+/// A float operand: a constant or a variable.
+#[derive(Debug, Clone, Copy)]
+pub enum FloatOperand {
+    Const(f64),
+    Var(FloatVarId),
+}
+
+impl FloatOperand {
+    /// The values of the operand.
+    pub fn range(&self, vars: &FloatVarNodes<'_>) -> FloatRange {
+        match self {
+            FloatOperand::Const(value) => FloatRange::singleton(*value),
+            FloatOperand::Var(var) => *vars[*var].get_range(),
+        }
+    }
+}
+
+/// What a float operation computes from its operands.
+#[derive(Debug, Clone)]
+pub enum FloatOpKind {
+    /// `lhs op rhs`, for `+`, `-`, `*` and `/`.
+    Binary {
+        op: BinOp,
+        lhs: FloatOperand,
+        rhs: FloatOperand,
+    },
+    /// A copy of the operand.
+    Copy(FloatOperand),
+    /// `φ(sources)`, the versions reaching a join.
+    Phi(Vec<FloatVarId>),
+    /// The values of `source` on the side `holds` of the branch on
+    /// `source op bound`.
+    Sigma {
+        source: FloatVarId,
+        op: BinOp,
+        bound: FloatOperand,
+        holds: bool,
+    },
+}
+
+/// `sink = kind` on floats of type `ty`.
+#[derive(Debug, Clone)]
+pub struct FloatOp {
+    pub sink: FloatVarId,
+    pub kind: FloatOpKind,
+    pub ty: FloatType,
+}
+
+impl FloatOp {
+    pub fn eval(&self, vars: &FloatVarNodes<'_>) -> FloatRange {
+        let var = |var: FloatVarId| *vars[var].get_range();
+        match &self.kind {
+            FloatOpKind::Binary { op, lhs, rhs } => {
+                let (lhs, rhs) = (lhs.range(vars), rhs.range(vars));
+                match op {
+                    BinOp::Add => lhs.add(&rhs, self.ty),
+                    BinOp::Sub => lhs.sub(&rhs, self.ty),
+                    BinOp::Mul => lhs.mul(&rhs, self.ty),
+                    BinOp::Div => lhs.div(&rhs, self.ty),
+                    _ => FloatRange::top(),
+                }
+            }
+            FloatOpKind::Copy(operand) => operand.range(vars),
+            FloatOpKind::Phi(sources) => sources
+                .iter()
+                .fold(FloatRange::bottom(), |acc, &source| acc.join(&var(source))),
+            FloatOpKind::Sigma {
+                source,
+                op,
+                bound,
+                holds,
+            } => var(*source).refine(*op, &bound.range(vars), *holds, self.ty),
+        }
+    }
+
+    /// The variables the operation reads, including the bound of a sigma.
+    pub fn sources(&self) -> Vec<FloatVarId> {
+        let operands = match &self.kind {
+            FloatOpKind::Binary { lhs, rhs, .. } => vec![*lhs, *rhs],
+            FloatOpKind::Copy(operand) => vec![*operand],
+            FloatOpKind::Phi(sources) => return sources.clone(),
+            FloatOpKind::Sigma { source, bound, .. } => vec![FloatOperand::Var(*source), *bound],
+        };
+        operands
+            .into_iter()
+            .filter_map(|operand| match operand {
+                FloatOperand::Var(var) => Some(var),
+                FloatOperand::Const(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// The comparison `v op bound` that decides a branch, as seen from `v`.
+#[derive(Debug, Clone)]
+pub struct FloatBranchMap {
+    pub v: FloatVarId,
+    pub bb_true: BasicBlock,
+    pub bb_false: BasicBlock,
+    pub op: BinOp,
+    pub bound: FloatOperand,
+    pub ty: FloatType,
+}
+
+pub type FloatOprs = Vec<FloatOp>;
+pub type FloatBranchMaps = HashMap<FloatVarId, Vec<FloatBranchMap>>;
+
+/// Solves one strongly connected component of the float variables, whose
+/// inputs are solved already, with the phases of the integer `Solver`: a
+/// worklist widens the ranges until none grows, and a second one narrows
+/// the bounds widening threw to an infinity. `ops` are the operations
+/// defining the variables of the component, whose nodes start empty, and
+/// `users` maps every variable to the operations reading it.
+///
+/// Every operation is evaluated at least once, so a variable left empty is
+/// one no value reaches. Widening terminates since a bound only moves to an
+/// infinity and the NaN flag is only set; narrowing since a bound only
+/// leaves an infinity and the flag is only cleared.
+pub fn solve_float_component(
+    vars: &mut FloatVarNodes<'_>,
+    oprs: &FloatOprs,
+    users: &HashMap<FloatVarId, Vec<usize>>,
+    ops: &BTreeSet<usize>,
+) {
+    let users_in = |v: FloatVarId| -> Vec<usize> {
+        users
+            .get(&v)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|id| ops.contains(id))
+            .collect()
+    };
+    let mut worklist = ops.clone();
+    while let Some(id) = worklist.pop_first() {
+        let new = oprs[id].eval(vars);
+        let node = &mut vars[oprs[id].sink];
+        let old = *node.get_range();
+        node.set_range(old.widen(&new));
+        if *node.get_range() != old {
+            worklist.extend(users_in(oprs[id].sink));
+        }
+    }
+    let mut worklist = ops.clone();
+    while let Some(id) = worklist.pop_first() {
+        let new = oprs[id].eval(vars);
+        let node = &mut vars[oprs[id].sink];
+        let old = *node.get_range();
+        node.set_range(old.narrow(&new));
+        if *node.get_range() != old {
+            worklist.extend(users_in(oprs[id].sink));
+        }
+    }
+}
@@ -0,0 +1,383 @@
+//! Known-zero and known-one bits.
+//!
+//! A `KnownBits` describes the 128-bit two's complement pattern of the
+//! carrier value, so it needs no width: a value of a narrower type is sign or
+//! zero extended, and `fit` wraps a pattern into a type the same way
+//! `IntType::fit` wraps a range.
+
+use num_traits::PrimInt;
+use std::fmt;
+
+use super::lattice::Lattice;
+use super::range::{IntType, Range, RangeType};
+
+const SIGN: u128 = 1 << 127;
+
+/// `zeros` has the bits known to be 0, `ones` the bits known to be 1. A bit
+/// in both means there is no value.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct KnownBits {
+    pub zeros: u128,
+    pub ones: u128,
+}
+
+fn is_signed<T: PrimInt>() -> bool {
+    T::min_value() < T::zero()
+}
+
+/// The bit pattern of a carrier value.
+fn pattern<T: PrimInt>(x: T) -> u128 {
+    if is_signed::<T>() {
+        x.to_i128().unwrap() as u128
+    } else {
+        x.to_u128().unwrap()
+    }
+}
+
+/// The low `n` bits set.
+fn low_mask(n: u32) -> u128 {
+    if n >= 128 {
+        u128::MAX
+    } else {
+        (1 << n) - 1
+    }
+}
+
+impl KnownBits {
+    pub fn constant(value: u128) -> Self {
+        Self {
+            zeros: !value,
+            ones: value,
+        }
+    }
+
+    pub fn is_bottom(&self) -> bool {
+        self.zeros & self.ones != 0
+    }
+
+    fn unknown(&self) -> u128 {
+        !(self.zeros | self.ones)
+    }
+
+    /// The value, if every bit is known.
+    pub fn as_constant(&self) -> Option<u128> {
+        (!self.is_bottom() && self.unknown() == 0).then_some(self.ones)
+    }
+
+    /// The common high bits of the bounds: within one sign the order of
+    /// values is the order of their patterns, and bounds of different signs
+    /// share no prefix.
+    pub fn from_range<T: PrimInt>(range: &Range<T>) -> Self {
+        if !range.is_regular() {
+            return Self::bottom();
+        }
+        let (lo, hi) = (pattern(range.get_lower()), pattern(range.get_upper()));
+        let known = !low_mask(128 - (lo ^ hi).leading_zeros());
+        Self {
+            zeros: !lo & known,
+            ones: lo & known,
+        }
+    }
+
+    /// The smallest range of the carrier holding every value.
+    pub fn to_range<T: PrimInt>(&self) -> Range<T> {
+        if self.is_bottom() {
+            return Range::empty();
+        }
+        let unknown = self.unknown();
+        let (lo, hi) = if is_signed::<T>() {
+            let lo = (self.ones | (unknown & SIGN)) as i128;
+            let hi = (self.ones | (unknown & !SIGN)) as i128;
+            let clamp = |x: i128| {
+                if x < T::min_value().to_i128().unwrap() {
+                    T::min_value()
+                } else {
+                    T::from(x).unwrap_or(T::max_value())
+                }
+            };
+            (clamp(lo), clamp(hi))
+        } else {
+            let clamp = |x: u128| T::from(x).unwrap_or(T::max_value());
+            (clamp(self.ones), clamp(self.ones | unknown))
+        };
+        if lo > hi {
+            return Range::empty();
+        }
+        Range::new(lo, hi, RangeType::Regular)
+    }
+
+    /// Wraps the values into `ty`: the low bits stay, and the high bits are
+    /// zeros for unsigned types and copies of the sign bit for signed ones.
+    pub fn fit(&self, ty: IntType) -> Self {
+        if self.is_bottom() || ty.bits >= 128 {
+            return *self;
+        }
+        let low = low_mask(ty.bits);
+        let (zeros, ones) = (self.zeros & low, self.ones & low);
+        if !ty.signed {
+            return Self {
+                zeros: zeros | !low,
+                ones,
+            };
+        }
+        let sign = 1 << (ty.bits - 1);
+        Self {
+            zeros: if zeros & sign != 0 { zeros | !low } else { zeros },
+            ones: if ones & sign != 0 { ones | !low } else { ones },
+        }
+    }
+
+    /// The number of low bits that are known.
+    fn known_low(&self) -> u32 {
+        (self.zeros | self.ones).trailing_ones()
+    }
+
+    /// `self + other + carry`, following LLVM's `KnownBits::computeForAddSub`:
+    /// a bit of the sum is known when the bits of both sides and the carry
+    /// into it are.
+    fn add_carry(&self, other: &Self, carry: bool) -> Self {
+        if self.is_bottom() || other.is_bottom() {
+            return Self::bottom();
+        }
+        let carry = carry as u128;
+        let sum_zero = (!self.zeros).wrapping_add(!other.zeros).wrapping_add(carry);
+        let sum_one = self.ones.wrapping_add(other.ones).wrapping_add(carry);
+        let carry_zero = !(sum_zero ^ self.zeros ^ other.zeros);
+        let carry_one = sum_one ^ self.ones ^ other.ones;
+        let known =
+            (self.zeros | self.ones) & (other.zeros | other.ones) & (carry_zero | carry_one);
+        Self {
+            zeros: !sum_zero & known,
+            ones: sum_one & known,
+        }
+    }
+
+    /// `self << k`, for `k < 128`.
+    pub fn shl_const(&self, k: u32) -> Self {
+        Self {
+            zeros: (self.zeros << k) | low_mask(k),
+            ones: self.ones << k,
+        }
+    }
+
+    /// `self >> k`, for `k < 128`. Arithmetic shifts copy the sign bit,
+    /// logical ones shift in zeros.
+    pub fn shr_const(&self, k: u32, arithmetic: bool) -> Self {
+        if arithmetic {
+            Self {
+                zeros: ((self.zeros as i128) >> k) as u128,
+                ones: ((self.ones as i128) >> k) as u128,
+            }
+        } else {
+            Self {
+                zeros: (self.zeros >> k) | !(u128::MAX >> k),
+                ones: self.ones >> k,
+            }
+        }
+    }
+
+    /// The join of `shift(k)` over the shift amounts in `amounts`.
+    fn shift_by(&self, amounts: &Range<i128>, shift: impl Fn(&Self, u32) -> Self) -> Self {
+        if self.is_bottom() || !amounts.is_regular() {
+            return Self::bottom();
+        }
+        let lo = amounts.get_lower().clamp(0, 127) as u32;
+        let hi = amounts.get_upper().clamp(0, 127) as u32;
+        (lo..=hi).fold(Self::bottom(), |acc, k| acc.join(&shift(self, k)))
+    }
+
+    /// `self << k` for every `k` in `amounts`.
+    pub fn shl(&self, amounts: &Range<i128>) -> Self {
+        self.shift_by(amounts, |x, k| x.shl_const(k))
+    }
+
+    /// `self >> k` for every `k` in `amounts`.
+    pub fn shr(&self, amounts: &Range<i128>, arithmetic: bool) -> Self {
+        self.shift_by(amounts, |x, k| x.shr_const(k, arithmetic))
+    }
+}
+
+/// Prints the pattern from the highest unknown or one bit down, with `?` for
+/// unknown bits: `0b??01` is a value whose low bits are `01`.
+impl fmt::Display for KnownBits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_bottom() {
+            return write!(f, "⊥");
+        }
+        // Leading copies of the top bit are left out.
+        let top = if self.ones & SIGN != 0 {
+            !self.ones
+        } else if self.zeros & SIGN != 0 {
+            !self.zeros
+        } else {
+            u128::MAX
+        };
+        let width = std::cmp::max(1, 128 - top.leading_zeros()) + 1;
+        let width = std::cmp::min(width, 128);
+        write!(f, "0b")?;
+        for i in (0..width).rev() {
+            let bit = 1 << i;
+            let c = if self.zeros & bit != 0 {
+                '0'
+            } else if self.ones & bit != 0 {
+                '1'
+            } else {
+                '?'
+            };
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Transfer function for `&`.
+impl std::ops::BitAnd for KnownBits {
+    type Output = KnownBits;
+
+    fn bitand(self, other: KnownBits) -> KnownBits {
+        KnownBits {
+            zeros: self.zeros | other.zeros,
+            ones: self.ones & other.ones,
+        }
+    }
+}
+
+/// Transfer function for `|`.
+impl std::ops::BitOr for KnownBits {
+    type Output = KnownBits;
+
+    fn bitor(self, other: KnownBits) -> KnownBits {
+        KnownBits {
+            zeros: self.zeros & other.zeros,
+            ones: self.ones | other.ones,
+        }
+    }
+}
+
+/// Transfer function for `^`.
+impl std::ops::BitXor for KnownBits {
+    type Output = KnownBits;
+
+    fn bitxor(self, other: KnownBits) -> KnownBits {
+        if self.is_bottom() || other.is_bottom() {
+            return KnownBits::bottom();
+        }
+        KnownBits {
+            zeros: (self.zeros & other.zeros) | (self.ones & other.ones),
+            ones: (self.zeros & other.ones) | (self.ones & other.zeros),
+        }
+    }
+}
+
+/// Transfer function for `!`.
+impl std::ops::Not for KnownBits {
+    type Output = KnownBits;
+
+    fn not(self) -> KnownBits {
+        KnownBits {
+            zeros: self.ones,
+            ones: self.zeros,
+        }
+    }
+}
+
+/// Transfer function for `+`.
+impl std::ops::Add for KnownBits {
+    type Output = KnownBits;
+
+    fn add(self, other: KnownBits) -> KnownBits {
+        self.add_carry(&other, false)
+    }
+}
+
+/// Transfer function for `-`: `a - b` is `a + !b + 1`.
+impl std::ops::Sub for KnownBits {
+    type Output = KnownBits;
+
+    fn sub(self, other: KnownBits) -> KnownBits {
+        self.add_carry(&!other, true)
+    }
+}
+
+/// Transfer function for unary `-`.
+impl std::ops::Neg for KnownBits {
+    type Output = KnownBits;
+
+    fn neg(self) -> KnownBits {
+        KnownBits::constant(0) - self
+    }
+}
+
+/// Transfer function for `*`. The low bits of a product only depend on the
+/// low bits of its factors, and its trailing zeros add up.
+impl std::ops::Mul for KnownBits {
+    type Output = KnownBits;
+
+    fn mul(self, other: KnownBits) -> KnownBits {
+        if self.is_bottom() || other.is_bottom() {
+            return KnownBits::bottom();
+        }
+        let k = std::cmp::min(self.known_low(), other.known_low());
+        let low = low_mask(k);
+        let product = (self.ones & low).wrapping_mul(other.ones & low) & low;
+        let trailing = self.zeros.trailing_ones() + other.zeros.trailing_ones();
+        KnownBits {
+            zeros: (!product & low) | low_mask(trailing),
+            ones: product,
+        }
+    }
+}
+
+impl Lattice for KnownBits {
+    fn bottom() -> Self {
+        Self {
+            zeros: u128::MAX,
+            ones: u128::MAX,
+        }
+    }
+
+    fn top() -> Self {
+        Self { zeros: 0, ones: 0 }
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        if self.is_bottom() {
+            return *other;
+        }
+        if other.is_bottom() {
+            return *self;
+        }
+        Self {
+            zeros: self.zeros & other.zeros,
+            ones: self.ones & other.ones,
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let meet = Self {
+            zeros: self.zeros | other.zeros,
+            ones: self.ones | other.ones,
+        };
+        if meet.is_bottom() {
+            Self::bottom()
+        } else {
+            meet
+        }
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        self.is_bottom()
+            || (!other.is_bottom()
+                && other.zeros & !self.zeros == 0
+                && other.ones & !self.ones == 0)
+    }
+
+    /// There are only 256 bits to forget, so joining terminates.
+    fn widen(&self, other: &Self) -> Self {
+        self.join(other)
+    }
+
+    fn narrow(&self, other: &Self) -> Self {
+        self.meet(other)
+    }
+}
@@ -0,0 +1,36 @@
+/// An abstract domain ordered by `is_subset`.
+///
+/// Implementations must satisfy the usual lattice laws, where `==` is
+/// structural equality on the values the operations return:
+///
+/// - `bottom() ⊑ a ⊑ top()` for every `a`;
+/// - `join` and `meet` are commutative, associative and idempotent, and
+///   `a.join(&a.meet(&b)) == a` and `a.meet(&a.join(&b)) == a`;
+/// - `a ⊑ b` iff `a.join(&b) == b` iff `a.meet(&b) == a`;
+/// - `a ⊑ a.widen(&b)` and `b ⊑ a.widen(&b)`, and every ascending chain
+///   `w_{i+1} = w_i.widen(&x_i)` stabilizes after finitely many steps;
+/// - if `b ⊑ a` then `b ⊑ a.narrow(&b) ⊑ a`.
+pub trait Lattice: Sized {
+    /// The least element.
+    fn bottom() -> Self;
+
+    /// The greatest element.
+    fn top() -> Self;
+
+    /// Least upper bound.
+    fn join(&self, other: &Self) -> Self;
+
+    /// Greatest lower bound.
+    fn meet(&self, other: &Self) -> Self;
+
+    /// Partial order of the lattice.
+    fn is_subset(&self, other: &Self) -> bool;
+
+    /// Upper bound of `self` and `other` that guarantees termination of
+    /// ascending iteration, `self` being the previous value.
+    fn widen(&self, other: &Self) -> Self;
+
+    /// Refines the widened value `self` with the new value `other` without
+    /// losing soundness, as long as `other ⊑ self`.
+    fn narrow(&self, other: &Self) -> Self;
+}
@@ -1,12 +1,10 @@
-use bounds::Bound;
+use crate::domain::lattice::Lattice;
 use intervals::*;
-use num_traits::{Bounded, Num, Zero};
-use z3::ast::Int;
+use num_traits::{Bounded, PrimInt};
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use std::fmt;
 // use std::ops::Range;
 
-const MIN: i64 = i64::MIN;
-const MAX: i64 = i64::MAX;
-
 // #[derive(PartialEq, Debug)]
 // pub struct MyStruct<T>
 // where T: PartialOrd+Clone
@@ -41,13 +39,137 @@ where
     pub range: Closed<T>,
 }
 
+/// The integer type whose values a range describes. Ranges are computed in
+/// the carrier `T` of `Range<T>`, which must be at least as wide as every
+/// type in the analysis, and are then fitted into their `IntType` with
+/// Rust's wrapping semantics.
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-enum UserType {
-    Unknown,
-    I32(i32),
-    I64(i64),
-    Usize(usize),
-    Empty,
+pub struct IntType {
+    pub bits: u32,
+    pub signed: bool,
+}
+
+/// Prints the type as `i32`, `u8` and so on.
+impl fmt::Display for IntType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", if self.signed { "i" } else { "u" }, self.bits)
+    }
+}
+
+impl IntType {
+    pub const fn new(bits: u32, signed: bool) -> Self {
+        assert!(bits != 0 && bits <= 128, "unsupported bit width");
+        Self { bits, signed }
+    }
+
+    /// The type of an integer, `bool` or `char` value, with `isize` and
+    /// `usize` as wide as a pointer of the target.
+    pub fn from_ty<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<Self> {
+        let pointer_bits = tcx.data_layout.pointer_size.bits() as u32;
+        match ty.kind() {
+            ty::Int(int) => Some(Self::new(
+                int.bit_width().map_or(pointer_bits, |bits| bits as u32),
+                true,
+            )),
+            ty::Uint(uint) => Some(Self::new(
+                uint.bit_width().map_or(pointer_bits, |bits| bits as u32),
+                false,
+            )),
+            ty::Bool => Some(Self::new(1, false)),
+            ty::Char => Some(Self::new(32, false)),
+            _ => None,
+        }
+    }
+
+    /// Smallest value of the type, or `T::min_value()` if `T` is narrower.
+    /// Saturated bounds do not describe the type, see `full_range`.
+    pub fn min_value<T: PrimInt>(&self) -> T {
+        if !self.signed {
+            return T::zero();
+        }
+        T::from(i128::MIN >> (128 - self.bits)).unwrap_or(T::min_value())
+    }
+
+    /// Largest value of the type, or `T::max_value()` if `T` is narrower.
+    pub fn max_value<T: PrimInt>(&self) -> T {
+        let max = if self.signed {
+            T::from(i128::MAX >> (128 - self.bits))
+        } else {
+            T::from(u128::MAX >> (128 - self.bits))
+        };
+        max.unwrap_or(T::max_value())
+    }
+
+    /// Interprets the low `bits` of `raw`, the bit pattern of a value of this
+    /// type, as a value of the carrier, sign-extending it if the type is
+    /// signed. Returns `None` if `T` cannot represent the value.
+    pub fn value_from_bits<T: PrimInt>(&self, raw: u128) -> Option<T> {
+        let shift = 128 - self.bits;
+        if self.signed {
+            T::from(((raw << shift) as i128) >> shift)
+        } else {
+            T::from((raw << shift) >> shift)
+        }
+    }
+
+    /// Whether the carrier `T` holds every value of the type.
+    pub fn fits_in<T: PrimInt>(&self) -> bool {
+        let shift = 128 - self.bits;
+        if self.signed {
+            T::from(i128::MIN >> shift).is_some() && T::from(i128::MAX >> shift).is_some()
+        } else {
+            T::from(u128::MAX >> shift).is_some()
+        }
+    }
+
+    /// Every value of the type, or the full range of the carrier, which is
+    /// `top`, if the carrier cannot hold them all: `[0, i128::MAX]` would
+    /// leave out half of the values of a `u128`.
+    pub fn full_range<T: PrimInt>(&self) -> Range<T> {
+        if !self.fits_in::<T>() {
+            return Range::default();
+        }
+        Range::new(self.min_value(), self.max_value(), RangeType::Regular)
+    }
+
+    /// Maps a range computed in the carrier back into the type, the way
+    /// `as` would: values that leave the type wrap around, and a range that
+    /// straddles a wrapping point becomes the full range of the type.
+    pub fn fit<T: PrimInt>(&self, range: &Range<T>) -> Range<T> {
+        if !range.is_regular() {
+            return range.clone();
+        }
+        let (lb, ub) = (range.get_lower(), range.get_upper());
+        if self.min_value::<T>() <= lb && ub <= self.max_value::<T>() {
+            return range.clone();
+        }
+        // The wrapped value is computed in i128, which holds every value of a
+        // type narrower than 128 bits.
+        let (Some(lb), Some(ub)) = (lb.to_i128(), ub.to_i128()) else {
+            return self.full_range();
+        };
+        if self.bits == 128 {
+            return self.full_range();
+        }
+        let modulus = 1i128 << self.bits;
+        let width = ub.checked_sub(lb);
+        if width.is_none_or(|width| width >= modulus - 1) {
+            return self.full_range();
+        }
+        let wrap = |v: i128| {
+            let r = v.rem_euclid(modulus);
+            if self.signed && r >= modulus / 2 {
+                r - modulus
+            } else {
+                r
+            }
+        };
+        let (lb, ub) = (wrap(lb), wrap(ub));
+        match (T::from(lb), T::from(ub)) {
+            (Some(lb), Some(ub)) if lb <= ub => Range::new(lb, ub, RangeType::Regular),
+            _ => self.full_range(),
+        }
+    }
 }
 
 impl<T> Range<T>
@@ -122,21 +244,705 @@ where
     }
 
     // Check if the range is the maximum range
-    // pub fn is_max_range(&self) -> bool {
-    //     self.range.lower() == T::min_value() && self.range.upper() == T::max_value()
-    // }
+    pub fn is_max_range(&self) -> bool {
+        self.is_regular()
+            && self.get_lower() == T::min_value()
+            && self.get_upper() == T::max_value()
+    }
+
+    /// The range of a variable that has not been evaluated yet.
+    pub fn unknown() -> Self {
+        Self::new(T::min_value(), T::max_value(), RangeType::Unknown)
+    }
+
+    /// The range of an expression that has no possible value.
+    pub fn empty() -> Self {
+        Self::new(T::max_value(), T::min_value(), RangeType::Empty)
+    }
+}
+
+/// Prints `[lo, hi]`, `∅` for an empty range, `⊤` for the maximum range and
+/// `⊥` for a range that has not been evaluated yet.
+impl<T> fmt::Display for Range<T>
+where
+    T: PartialOrd + Clone + Bounded + fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rtype {
+            RangeType::Unknown => write!(f, "⊥"),
+            RangeType::Empty => write!(f, "∅"),
+            RangeType::Regular if self.is_max_range() => write!(f, "⊤"),
+            RangeType::Regular => write!(f, "[{}, {}]", self.get_lower(), self.get_upper()),
+        }
+    }
+}
+
+// Arithmetic and bitwise transfer functions.
+//
+// Every bound is a concrete value of `T`, so `[T::min_value(), T::max_value()]`
+// is the full range. Operations follow Rust's wrapping semantics: whenever a
+// result may leave `T` the full range is returned, because the wrapped value
+// can be anything. Operations that always panic (division by zero, shifting
+// by at least the bit width) exclude those operands.
+impl<T> Range<T>
+where
+    T: PrimInt,
+{
+    fn regular(lb: T, ub: T) -> Self {
+        Self::new(lb, ub, RangeType::Regular)
+    }
+
+    /// `Unknown` wins over `Empty`, which wins over any regular range.
+    fn propagate(&self, other: &Self) -> Option<Self> {
+        if self.is_unknown() || other.is_unknown() {
+            Some(Self::unknown())
+        } else if self.is_empty() || other.is_empty() {
+            Some(Self::empty())
+        } else {
+            None
+        }
+    }
+
+    /// Smallest range containing all the values, or the full range if one of
+    /// them overflowed.
+    fn hull(values: &[Option<T>]) -> Self {
+        if values.iter().any(|v| v.is_none()) {
+            return Self::default();
+        }
+        let lb = values.iter().flatten().copied().min().unwrap();
+        let ub = values.iter().flatten().copied().max().unwrap();
+        Self::regular(lb, ub)
+    }
+
+    fn union(ranges: &[Self]) -> Self {
+        let regular: Vec<&Self> = ranges.iter().filter(|r| r.is_regular()).collect();
+        if regular.is_empty() {
+            return Self::empty();
+        }
+        let lb = regular.iter().map(|r| r.get_lower()).min().unwrap();
+        let ub = regular.iter().map(|r| r.get_upper()).max().unwrap();
+        Self::regular(lb, ub)
+    }
+
+    /// Splits the range at zero into its negative and non-negative parts.
+    fn sign_parts(&self) -> Vec<Self> {
+        let (lb, ub) = (self.get_lower(), self.get_upper());
+        let zero = T::zero();
+        if lb < zero && ub >= zero {
+            vec![Self::regular(lb, zero - T::one()), Self::regular(zero, ub)]
+        } else {
+            vec![self.clone()]
+        }
+    }
+
+    /// The divisor range without zero, split into a negative and a positive part.
+    fn nonzero_parts(&self) -> Vec<Self> {
+        let (lb, ub) = (self.get_lower(), self.get_upper());
+        let (zero, one) = (T::zero(), T::one());
+        let mut parts = Vec::new();
+        if lb < zero {
+            parts.push(Self::regular(lb, if ub < zero { ub } else { zero - one }));
+        }
+        if ub > zero {
+            parts.push(Self::regular(if lb > zero { lb } else { one }, ub));
+        }
+        parts
+    }
+
+    fn bits() -> usize {
+        T::zero().count_zeros() as usize
+    }
+
+    /// The valid shift amounts of `self`, or `None` if it can only be out of range.
+    fn shift_amounts(&self) -> Option<(usize, usize)> {
+        let bits = T::from(Self::bits())?;
+        let lb = std::cmp::max(self.get_lower(), T::zero());
+        let ub = std::cmp::min(self.get_upper(), bits - T::one());
+        if lb > ub {
+            return None;
+        }
+        Some((lb.to_usize()?, ub.to_usize()?))
+    }
+
+    fn checked_neg(value: T) -> Option<T> {
+        T::zero().checked_sub(&value)
+    }
+
+    /// Bitwise operations are evaluated on sign-constant parts. Within one
+    /// part signed order and bit-pattern order agree, so the unsigned bounds
+    /// of Hacker's Delight (section 4-3) apply directly.
+    fn bitwise(&self, other: &Self, min: fn(T, T, T, T) -> T, max: fn(T, T, T, T) -> T) -> Self {
+        if let Some(r) = self.propagate(other) {
+            return r;
+        }
+        let mut parts = Vec::new();
+        for x in self.sign_parts() {
+            for y in other.sign_parts() {
+                let (a, b) = (x.get_lower(), x.get_upper());
+                let (c, d) = (y.get_lower(), y.get_upper());
+                parts.push(Self::regular(min(a, b, c, d), max(a, b, c, d)));
+            }
+        }
+        Self::union(&parts)
+    }
+}
+
+/// Transfer function for `+`.
+impl<'a, T: PrimInt> std::ops::Add<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn add(self, other: &'a Range<T>) -> Range<T> {
+        if let Some(r) = self.propagate(other) {
+            return r;
+        }
+        Range::hull(&[
+            self.get_lower().checked_add(&other.get_lower()),
+            self.get_upper().checked_add(&other.get_upper()),
+        ])
+    }
+}
+
+impl<T: PrimInt> std::ops::Add for Range<T> {
+    type Output = Range<T>;
+
+    fn add(self, other: Range<T>) -> Range<T> {
+        &self + &other
+    }
+}
+
+/// Transfer function for `-`.
+impl<'a, T: PrimInt> std::ops::Sub<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn sub(self, other: &'a Range<T>) -> Range<T> {
+        if let Some(r) = self.propagate(other) {
+            return r;
+        }
+        Range::hull(&[
+            self.get_lower().checked_sub(&other.get_upper()),
+            self.get_upper().checked_sub(&other.get_lower()),
+        ])
+    }
+}
+
+impl<T: PrimInt> std::ops::Sub for Range<T> {
+    type Output = Range<T>;
+
+    fn sub(self, other: Range<T>) -> Range<T> {
+        &self - &other
+    }
+}
+
+/// Transfer function for `*`.
+impl<'a, T: PrimInt> std::ops::Mul<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn mul(self, other: &'a Range<T>) -> Range<T> {
+        if let Some(r) = self.propagate(other) {
+            return r;
+        }
+        let (a, b) = (self.get_lower(), self.get_upper());
+        let (c, d) = (other.get_lower(), other.get_upper());
+        Range::hull(&[
+            a.checked_mul(&c),
+            a.checked_mul(&d),
+            b.checked_mul(&c),
+            b.checked_mul(&d),
+        ])
+    }
+}
+
+impl<T: PrimInt> std::ops::Mul for Range<T> {
+    type Output = Range<T>;
+
+    fn mul(self, other: Range<T>) -> Range<T> {
+        &self * &other
+    }
+}
+
+/// Transfer function for `/`. Division truncates towards zero, so for a
+/// divisor of constant sign the extremes are reached at the corners.
+impl<'a, T: PrimInt> std::ops::Div<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn div(self, other: &'a Range<T>) -> Range<T> {
+        if let Some(r) = self.propagate(other) {
+            return r;
+        }
+        let parts: Vec<Range<T>> = other
+            .nonzero_parts()
+            .iter()
+            .map(|divisor| {
+                let (a, b) = (self.get_lower(), self.get_upper());
+                let (c, d) = (divisor.get_lower(), divisor.get_upper());
+                Range::hull(&[
+                    a.checked_div(&c),
+                    a.checked_div(&d),
+                    b.checked_div(&c),
+                    b.checked_div(&d),
+                ])
+            })
+            .collect();
+        Range::union(&parts)
+    }
+}
+
+impl<T: PrimInt> std::ops::Div for Range<T> {
+    type Output = Range<T>;
+
+    fn div(self, other: Range<T>) -> Range<T> {
+        &self / &other
+    }
+}
+
+/// Transfer function for `%`. The result has the sign of the dividend and
+/// is smaller in magnitude than the divisor.
+impl<'a, T: PrimInt> std::ops::Rem<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn rem(self, other: &'a Range<T>) -> Range<T> {
+        if let Some(r) = self.propagate(other) {
+            return r;
+        }
+        let parts = other.nonzero_parts();
+        if parts.is_empty() {
+            return Range::empty();
+        }
+        let (a, b) = (self.get_lower(), self.get_upper());
+        let (c, d) = (other.get_lower(), other.get_upper());
+        let zero = T::zero();
+        // A dividend smaller than every divisor is left unchanged.
+        if c > zero && a >= zero && b < c {
+            return self.clone();
+        }
+        // Largest possible magnitude of the result. `T::min_value()` has no
+        // positive counterpart, but `|x % MIN| <= MAX` holds anyway.
+        let magnitude = |v: T| {
+            if v >= zero {
+                Some(v)
+            } else {
+                Range::checked_neg(v)
+            }
+        };
+        let bound = match (magnitude(c), magnitude(d)) {
+            (Some(mc), Some(md)) => std::cmp::max(mc, md) - T::one(),
+            _ => T::max_value(),
+        };
+        let lb = if a >= zero {
+            zero
+        } else {
+            std::cmp::max(a, Range::checked_neg(bound).unwrap_or(T::min_value()))
+        };
+        let ub = if b <= zero {
+            zero
+        } else {
+            std::cmp::min(b, bound)
+        };
+        Range::regular(lb, ub)
+    }
+}
+
+impl<T: PrimInt> std::ops::Rem for Range<T> {
+    type Output = Range<T>;
+
+    fn rem(self, other: Range<T>) -> Range<T> {
+        &self % &other
+    }
+}
+
+/// Transfer function for `<<`. Shifting left by `k` multiplies by `2^k`,
+/// so any bit shifted out makes the result wrap.
+impl<'a, T: PrimInt> std::ops::Shl<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn shl(self, other: &'a Range<T>) -> Range<T> {
+        if let Some(r) = self.propagate(other) {
+            return r;
+        }
+        let Some((lo, hi)) = other.shift_amounts() else {
+            return Range::empty();
+        };
+        let factor = Range::regular(
+            num_traits::checked_pow(T::one() + T::one(), lo).unwrap_or(T::max_value()),
+            num_traits::checked_pow(T::one() + T::one(), hi).unwrap_or(T::max_value()),
+        );
+        if T::min_value() < T::zero() && hi + 1 >= Range::<T>::bits() {
+            // `2^(bits - 1)` is not representable in a signed type.
+            return Range::default();
+        }
+        self * &factor
+    }
+}
+
+impl<T: PrimInt> std::ops::Shl for Range<T> {
+    type Output = Range<T>;
 
-    // // Print the range
-    // pub fn print(&self) {
-    //     println!("Range: [{} - {}]", self.get_lower(), self.get_upper());
-    // }
+    fn shl(self, other: Range<T>) -> Range<T> {
+        &self << &other
+    }
+}
 
-    // // Arithmetic and bitwise operations (example for addition)
-    // pub fn add(&self, other: &Range<T>) -> Range<T> {
-    //     let lower = self.get_lower() + other.get_lower();
-    //     let upper = self.get_upper() + other.get_upper();
-    //     Range::with_bounds(lower, upper, RangeType::Regular)
-    // }
+/// Transfer function for `>>`: arithmetic for signed types, logical for
+/// unsigned ones. Either way it is monotonic in the shifted value.
+impl<'a, T: PrimInt> std::ops::Shr<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn shr(self, other: &'a Range<T>) -> Range<T> {
+        if let Some(r) = self.propagate(other) {
+            return r;
+        }
+        let Some((lo, hi)) = other.shift_amounts() else {
+            return Range::empty();
+        };
+        let (a, b) = (self.get_lower(), self.get_upper());
+        Range::hull(&[Some(a >> lo), Some(a >> hi), Some(b >> lo), Some(b >> hi)])
+    }
 }
 
-// Implement the comparison operators
+impl<T: PrimInt> std::ops::Shr for Range<T> {
+    type Output = Range<T>;
+
+    fn shr(self, other: Range<T>) -> Range<T> {
+        &self >> &other
+    }
+}
+
+/// Transfer function for `&`.
+impl<'a, T: PrimInt> std::ops::BitAnd<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn bitand(self, other: &'a Range<T>) -> Range<T> {
+        self.bitwise(other, bits::min_and, bits::max_and)
+    }
+}
+
+impl<T: PrimInt> std::ops::BitAnd for Range<T> {
+    type Output = Range<T>;
+
+    fn bitand(self, other: Range<T>) -> Range<T> {
+        &self & &other
+    }
+}
+
+/// Transfer function for `|`.
+impl<'a, T: PrimInt> std::ops::BitOr<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn bitor(self, other: &'a Range<T>) -> Range<T> {
+        self.bitwise(other, bits::min_or, bits::max_or)
+    }
+}
+
+impl<T: PrimInt> std::ops::BitOr for Range<T> {
+    type Output = Range<T>;
+
+    fn bitor(self, other: Range<T>) -> Range<T> {
+        &self | &other
+    }
+}
+
+/// Transfer function for `^`.
+impl<'a, T: PrimInt> std::ops::BitXor<&'a Range<T>> for &'a Range<T> {
+    type Output = Range<T>;
+
+    fn bitxor(self, other: &'a Range<T>) -> Range<T> {
+        self.bitwise(other, bits::min_xor, bits::max_xor)
+    }
+}
+
+impl<T: PrimInt> std::ops::BitXor for Range<T> {
+    type Output = Range<T>;
+
+    fn bitxor(self, other: Range<T>) -> Range<T> {
+        &self ^ &other
+    }
+}
+
+/// Transfer function for unary `-`.
+impl<T: PrimInt> std::ops::Neg for &Range<T> {
+    type Output = Range<T>;
+
+    fn neg(self) -> Range<T> {
+        if self.is_unknown() || self.is_empty() {
+            return self.clone();
+        }
+        Range::hull(&[
+            Range::checked_neg(self.get_upper()),
+            Range::checked_neg(self.get_lower()),
+        ])
+    }
+}
+
+impl<T: PrimInt> std::ops::Neg for Range<T> {
+    type Output = Range<T>;
+
+    fn neg(self) -> Range<T> {
+        -&self
+    }
+}
+
+/// Transfer function for `!`, which reverses the order of its operand.
+impl<T: PrimInt> std::ops::Not for &Range<T> {
+    type Output = Range<T>;
+
+    fn not(self) -> Range<T> {
+        if self.is_unknown() || self.is_empty() {
+            return self.clone();
+        }
+        Range::regular(!self.get_upper(), !self.get_lower())
+    }
+}
+
+impl<T: PrimInt> std::ops::Not for Range<T> {
+    type Output = Range<T>;
+
+    fn not(self) -> Range<T> {
+        !&self
+    }
+}
+
+/// Regular ranges are ordered by inclusion. Below all of them sit `Empty`
+/// (no possible value) and, below that, `Unknown` (not evaluated yet), so
+/// that a variable starts at `Unknown` and only grows once it is evaluated.
+/// Operations return the canonical `Range::unknown()` and `Range::empty()`.
+impl<T: PrimInt> Lattice for Range<T> {
+    fn bottom() -> Self {
+        Self::unknown()
+    }
+
+    fn top() -> Self {
+        Self::default()
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (&self.rtype, &other.rtype) {
+            (RangeType::Regular, RangeType::Regular) => Self::regular(
+                std::cmp::min(self.get_lower(), other.get_lower()),
+                std::cmp::max(self.get_upper(), other.get_upper()),
+            ),
+            (RangeType::Regular, _) => self.clone(),
+            (_, RangeType::Regular) => other.clone(),
+            (RangeType::Empty, _) | (_, RangeType::Empty) => Self::empty(),
+            _ => Self::unknown(),
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        if let Some(r) = self.propagate(other) {
+            return r;
+        }
+        let lb = std::cmp::max(self.get_lower(), other.get_lower());
+        let ub = std::cmp::min(self.get_upper(), other.get_upper());
+        if lb > ub {
+            Self::empty()
+        } else {
+            Self::regular(lb, ub)
+        }
+    }
+
+    fn is_subset(&self, other: &Self) -> bool {
+        match (&self.rtype, &other.rtype) {
+            (RangeType::Unknown, _) => true,
+            (RangeType::Empty, RangeType::Unknown) => false,
+            (RangeType::Empty, _) => true,
+            (RangeType::Regular, RangeType::Regular) => {
+                other.get_lower() <= self.get_lower() && self.get_upper() <= other.get_upper()
+            }
+            (RangeType::Regular, _) => false,
+        }
+    }
+
+    /// Classic widening: a bound that grows jumps straight to the end of `T`.
+    fn widen(&self, other: &Self) -> Self {
+        if !self.is_regular() || !other.is_regular() {
+            return self.join(other);
+        }
+        let lb = if other.get_lower() < self.get_lower() {
+            T::min_value()
+        } else {
+            self.get_lower()
+        };
+        let ub = if other.get_upper() > self.get_upper() {
+            T::max_value()
+        } else {
+            self.get_upper()
+        };
+        Self::regular(lb, ub)
+    }
+
+    /// Only bounds that were widened to the end of `T` are refined.
+    fn narrow(&self, other: &Self) -> Self {
+        if !self.is_regular() || !other.is_regular() {
+            return self.meet(other);
+        }
+        let lb = if self.get_lower() == T::min_value() {
+            other.get_lower()
+        } else {
+            self.get_lower()
+        };
+        let ub = if self.get_upper() == T::max_value() {
+            other.get_upper()
+        } else {
+            self.get_upper()
+        };
+        Self::regular(lb, ub)
+    }
+}
+
+impl<T: PrimInt> Range<T> {
+    /// Jump-set widening: a bound that grows jumps to the nearest constant of
+    /// `thresholds` (sorted in ascending order) that still covers `other`, and
+    /// to the end of `T` only when there is none. Termination is preserved
+    /// because each bound can only jump `thresholds.len() + 1` times.
+    pub fn widen_with_thresholds(&self, other: &Self, thresholds: &[T]) -> Self {
+        if !self.is_regular() || !other.is_regular() {
+            return self.join(other);
+        }
+        let lb = if other.get_lower() < self.get_lower() {
+            let target = other.get_lower();
+            thresholds
+                .iter()
+                .rev()
+                .find(|&&c| c <= target)
+                .copied()
+                .unwrap_or(T::min_value())
+        } else {
+            self.get_lower()
+        };
+        let ub = if other.get_upper() > self.get_upper() {
+            let target = other.get_upper();
+            thresholds
+                .iter()
+                .find(|&&c| c >= target)
+                .copied()
+                .unwrap_or(T::max_value())
+        } else {
+            self.get_upper()
+        };
+        Self::regular(lb, ub)
+    }
+}
+
+/// Bounds of bitwise operations over `[a, b]` and `[c, d]`, where both ranges
+/// lie on one side of zero (Warren, Hacker's Delight, 4-3).
+mod bits {
+    use num_traits::PrimInt;
+
+    fn top_bit<T: PrimInt>() -> T {
+        T::one() << (T::zero().count_zeros() as usize - 1)
+    }
+
+    /// The bits strictly below the single bit `m`.
+    fn below<T: PrimInt>(m: T) -> T {
+        if m == top_bit() {
+            !m
+        } else {
+            m - T::one()
+        }
+    }
+
+    /// Unsigned `<=` on the bit patterns.
+    fn ule<T: PrimInt>(x: T, y: T) -> bool {
+        let (xn, yn) = (x < T::zero(), y < T::zero());
+        if xn == yn {
+            x <= y
+        } else {
+            yn
+        }
+    }
+
+    pub fn min_or<T: PrimInt>(mut a: T, b: T, mut c: T, d: T) -> T {
+        let mut m = top_bit::<T>();
+        while m != T::zero() {
+            if (!a & c & m) != T::zero() {
+                let temp = (a | m) & !below(m);
+                if ule(temp, b) {
+                    a = temp;
+                    break;
+                }
+            } else if (a & !c & m) != T::zero() {
+                let temp = (c | m) & !below(m);
+                if ule(temp, d) {
+                    c = temp;
+                    break;
+                }
+            }
+            m = m.unsigned_shr(1);
+        }
+        a | c
+    }
+
+    pub fn max_or<T: PrimInt>(a: T, mut b: T, c: T, mut d: T) -> T {
+        let mut m = top_bit::<T>();
+        while m != T::zero() {
+            if (b & d & m) != T::zero() {
+                let temp = (b & !m) | below(m);
+                if ule(a, temp) {
+                    b = temp;
+                    break;
+                }
+                let temp = (d & !m) | below(m);
+                if ule(c, temp) {
+                    d = temp;
+                    break;
+                }
+            }
+            m = m.unsigned_shr(1);
+        }
+        b | d
+    }
+
+    pub fn min_and<T: PrimInt>(mut a: T, b: T, mut c: T, d: T) -> T {
+        let mut m = top_bit::<T>();
+        while m != T::zero() {
+            if (!a & !c & m) != T::zero() {
+                let temp = (a | m) & !below(m);
+                if ule(temp, b) {
+                    a = temp;
+                    break;
+                }
+                let temp = (c | m) & !below(m);
+                if ule(temp, d) {
+                    c = temp;
+                    break;
+                }
+            }
+            m = m.unsigned_shr(1);
+        }
+        a & c
+    }
+
+    pub fn max_and<T: PrimInt>(a: T, mut b: T, c: T, mut d: T) -> T {
+        let mut m = top_bit::<T>();
+        while m != T::zero() {
+            if (b & !d & m) != T::zero() {
+                let temp = (b & !m) | below(m);
+                if ule(a, temp) {
+                    b = temp;
+                    break;
+                }
+            } else if (!b & d & m) != T::zero() {
+                let temp = (d & !m) | below(m);
+                if ule(c, temp) {
+                    d = temp;
+                    break;
+                }
+            }
+            m = m.unsigned_shr(1);
+        }
+        b & d
+    }
+
+    pub fn min_xor<T: PrimInt>(a: T, b: T, c: T, d: T) -> T {
+        min_and(a, b, !d, !c) | min_and(!b, !a, c, d)
+    }
+
+    pub fn max_xor<T: PrimInt>(a: T, b: T, c: T, d: T) -> T {
+        max_or(
+            T::zero(),
+            max_and(a, b, !d, !c),
+            T::zero(),
+            max_and(!b, !a, c, d),
+        )
+    }
+}
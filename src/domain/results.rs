@@ -0,0 +1,98 @@
+use num_traits::PrimInt;
+use rustc_middle::mir::{
+    BasicBlock, Body, Local, Location, Operand, Place, StatementKind, TerminatorKind,
+};
+use rustc_middle::ty::TyCtxt;
+use std::collections::HashMap;
+
+use super::const_eval::const_range;
+use super::range::{IntType, Range};
+use crate::SSA::SsaBody::reaching_def;
+
+/// The ranges found by `ConstraintGraph::find_intervals`, queried by the
+/// variables and locations of the body in e-SSA form.
+pub struct RangeResults<'a, 'tcx, T: PrimInt> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    /// Maps every renamed local to the local it was split from.
+    ssa_origin: HashMap<Local, Local>,
+    ranges: HashMap<Place<'tcx>, Range<T>>,
+}
+
+impl<'a, 'tcx, T: PrimInt> RangeResults<'a, 'tcx, T> {
+    pub fn new(
+        tcx: TyCtxt<'tcx>,
+        body: &'a Body<'tcx>,
+        ssa_origin: HashMap<Local, Local>,
+        ranges: HashMap<Place<'tcx>, Range<T>>,
+    ) -> Self {
+        Self {
+            tcx,
+            body,
+            ssa_origin,
+            ranges,
+        }
+    }
+
+    pub fn body(&self) -> &'a Body<'tcx> {
+        self.body
+    }
+
+    /// The range of `place`, if it is an integer the analysis tracked.
+    pub fn range_of(&self, place: &Place<'tcx>) -> Option<&Range<T>> {
+        self.ranges.get(place)
+    }
+
+    /// The range of `local` just before the statement at `location` runs.
+    /// `local` may be the original variable or any of its versions: the
+    /// version whose definition reaches `location` is the one looked up.
+    pub fn range_at(&self, local: Local, location: Location) -> Option<&Range<T>> {
+        let version = reaching_def(self.body, &self.ssa_origin, local, location);
+        self.range_of(&Place::from(version))
+    }
+
+    /// The range of an operand read at `location`: a constant is its own
+    /// value, and a place is looked up in the version reaching `location`.
+    pub fn range_of_operand(
+        &self,
+        operand: &Operand<'tcx>,
+        location: Location,
+    ) -> Option<Range<T>> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => {
+                let version = reaching_def(self.body, &self.ssa_origin, place.local, location);
+                let place = Place {
+                    local: version,
+                    projection: place.projection,
+                };
+                self.range_of(&place).cloned()
+            }
+            Operand::Constant(c) => {
+                IntType::from_ty(self.tcx, c.const_.ty())?;
+                Some(const_range(self.tcx, c))
+            }
+        }
+    }
+
+    /// The places assigned in `block` that have a range, with their ranges,
+    /// in the order they are assigned.
+    pub fn ranges_in_block(&self, block: BasicBlock) -> Vec<(&'a Place<'tcx>, &Range<T>)> {
+        let body = self.body;
+        let data = &body.basic_blocks[block];
+        let statements = data.statements.iter().filter_map(|stmt| match &stmt.kind {
+            StatementKind::Assign(box (place, _)) => Some(place),
+            _ => None,
+        });
+        let call = data
+            .terminator
+            .iter()
+            .filter_map(|terminator| match &terminator.kind {
+                TerminatorKind::Call { destination, .. } => Some(destination),
+                _ => None,
+            });
+        statements
+            .chain(call)
+            .filter_map(|place| Some((place, self.ranges.get(place)?)))
+            .collect()
+    }
+}
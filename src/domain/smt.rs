@@ -0,0 +1,594 @@
+//! Checks the ranges of a constraint graph with Z3.
+//!
+//! The operations of the graph are encoded over bit-vectors as wide as the
+//! types of their variables, so wrapping is exact. Every solution of the
+//! encoding is a solution of the constraint system, and the range analysis
+//! promises that those lie within the computed ranges: a bound the solver
+//! can break is a soundness bug, and a bound no solution reaches can move
+//! inwards. Variables no operation defines are the inputs of the system and
+//! are assumed to lie in their ranges.
+//!
+//! One assignment of the bit-vectors stands for one execution, so phis and
+//! sigmas are left out: a loop needs a phi to hold several values, and the
+//! sigmas on both sides of a branch cannot all hold at once. Their sinks are
+//! free, which keeps every bound the solver confirms sound. For the same
+//! reason a query on a variable only asserts the operations it is computed
+//! from: the intersection of an operation reading it may hold on one path
+//! only.
+
+use num_traits::PrimInt;
+use rustc_middle::mir::BinOp;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use z3::ast::{Ast, Bool, BV};
+use z3::{Config, Context, Model, Params, SatResult, Solver};
+
+use super::domain::{
+    BasicIntervalTrait, BasicOpKind, GenOprs, IntervalType, OpSource, UnaryOpKind, VarId, VarNodes,
+};
+use super::lattice::Lattice;
+use super::range::{IntType, Range, RangeType};
+use super::ConstraintGraph::ConstraintGraph;
+
+/// What the solver says about one bound of a range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoundCheck<T> {
+    /// No solution leaves the bound, and a solution reaches it.
+    Exact,
+    /// No solution leaves the bound, but a query timed out before showing
+    /// whether one reaches it.
+    Confirmed,
+    /// No solution leaves the bound, and none goes past the value either.
+    Tightened(T),
+    /// A solution takes the value, which is outside the bound.
+    Disproved(T),
+    /// No variable of the system has a value.
+    Unreachable,
+    /// The solver gave up, or the operations of the variable could not all
+    /// be encoded.
+    Unknown,
+}
+
+/// The verdict on the range of one variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeCheck<T: PartialOrd + Clone> {
+    pub var: VarId,
+    pub range: Range<T>,
+    pub lower: BoundCheck<T>,
+    pub upper: BoundCheck<T>,
+}
+
+impl<T: PrimInt> RangeCheck<T> {
+    /// Whether the solver found no value outside the range.
+    pub fn is_sound(&self) -> bool {
+        !matches!(self.lower, BoundCheck::Disproved(_))
+            && !matches!(self.upper, BoundCheck::Disproved(_))
+    }
+
+    /// The range with the bounds the solver tightened. A disproved range is
+    /// left alone: it needs fixing, not tightening.
+    pub fn refined_range(&self) -> Range<T> {
+        if !self.is_sound() {
+            return self.range.clone();
+        }
+        if self.lower == BoundCheck::Unreachable {
+            return Range::empty();
+        }
+        let mut range = self.range.clone();
+        if let BoundCheck::Tightened(lower) = self.lower {
+            range = range.meet(&Range::new(lower, T::max_value(), RangeType::Regular));
+        }
+        if let BoundCheck::Tightened(upper) = self.upper {
+            range = range.meet(&Range::new(T::min_value(), upper, RangeType::Regular));
+        }
+        range
+    }
+}
+
+/// The answer to one query, with the value of the checked variable in the
+/// solution if there is one.
+enum Answer<T> {
+    Sat(T),
+    Unsat,
+    Unknown,
+}
+
+/// The bit pattern of `value` in a type of `ty.bits` bits.
+fn bits_of<T: PrimInt>(value: T, ty: IntType) -> u128 {
+    let raw = match value.to_i128() {
+        Some(v) => v as u128,
+        None => value.to_u128().unwrap(),
+    };
+    if ty.bits >= 128 {
+        raw
+    } else {
+        raw & ((1 << ty.bits) - 1)
+    }
+}
+
+/// `floor((a + b) / 2)` and `ceil((a + b) / 2)`, without overflow.
+fn floor_mid<T: PrimInt>(a: T, b: T) -> T {
+    (a >> 1) + (b >> 1) + (a & b & T::one())
+}
+
+fn ceil_mid<T: PrimInt>(a: T, b: T) -> T {
+    (a >> 1) + (b >> 1) + ((a | b) & T::one())
+}
+
+/// The encoding of a constraint graph, and the solver holding it.
+pub struct SmtChecker<'ctx> {
+    ctx: &'ctx Context,
+    solver: Solver<'ctx>,
+    consts: HashMap<VarId, (BV<'ctx>, IntType)>,
+    // The encoded operations defining each variable, and the variables each
+    // variable is computed from, including symbolic bounds.
+    definitions: HashMap<VarId, Vec<Bool<'ctx>>>,
+    sources: HashMap<VarId, Vec<VarId>>,
+    // The variables that depend on an operation that could not be encoded.
+    // Their encoding has solutions the system does not, so their bounds can
+    // be confirmed but not disproved.
+    inexact: HashSet<VarId>,
+}
+
+impl<'ctx> SmtChecker<'ctx> {
+    /// Encodes the graph. Every query gives up after `timeout`.
+    pub fn new<T: PrimInt>(
+        ctx: &'ctx Context,
+        vars: &VarNodes<'_, T>,
+        oprs: &GenOprs<T>,
+        timeout: Duration,
+    ) -> Self {
+        let solver = Solver::new(ctx);
+        let mut params = Params::new(ctx);
+        params.set_u32(
+            "timeout",
+            timeout.as_millis().try_into().unwrap_or(u32::MAX),
+        );
+        solver.set_params(&params);
+        let mut checker = Self {
+            ctx,
+            solver,
+            consts: HashMap::new(),
+            definitions: HashMap::new(),
+            sources: HashMap::new(),
+            inexact: HashSet::new(),
+        };
+        for (id, node) in vars.iter() {
+            if let Some(ty) = node.get_type() {
+                let name = format!("{:?}", node.get_value());
+                checker
+                    .consts
+                    .insert(id, (BV::new_const(ctx, name, ty.bits), ty));
+            }
+        }
+        let defined: Vec<_> = oprs.iter().map(|op| op.get_sink()).collect();
+        for (id, node) in vars.iter() {
+            if !defined.contains(&id) {
+                if let Some(constraint) = checker.in_range(id, node.get_range()) {
+                    checker.solver.assert(&constraint);
+                }
+            }
+        }
+        for op in oprs {
+            let sink = op.get_sink();
+            match checker.encode(op) {
+                Some(constraint) => checker
+                    .definitions
+                    .entry(sink)
+                    .or_default()
+                    .push(constraint),
+                None => {
+                    checker.inexact.insert(sink);
+                }
+            }
+            let sources = checker.sources.entry(sink).or_default();
+            sources.extend(op.get_sources());
+            if let IntervalType::Symb(symb) = op.get_intersect() {
+                sources.push(symb.get_bound());
+            }
+        }
+        // Inexactness flows to every variable computed from an inexact one,
+        // including through symbolic bounds.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (sink, sources) in &checker.sources {
+                if sources
+                    .iter()
+                    .any(|source| checker.inexact.contains(source))
+                {
+                    changed |= checker.inexact.insert(*sink);
+                }
+            }
+        }
+        checker
+    }
+
+    /// The definitions of `var` and of every variable it is computed from.
+    fn definitions_of(&self, var: VarId) -> Vec<&Bool<'ctx>> {
+        let mut seen = HashSet::new();
+        let mut stack = vec![var];
+        let mut definitions = Vec::new();
+        while let Some(var) = stack.pop() {
+            if !seen.insert(var) {
+                continue;
+            }
+            definitions.extend(self.definitions.get(&var).into_iter().flatten());
+            stack.extend(self.sources.get(&var).into_iter().flatten());
+        }
+        definitions
+    }
+
+    fn constant<T: PrimInt>(&self, value: T, ty: IntType) -> BV<'ctx> {
+        BV::from_str(self.ctx, ty.bits, &bits_of(value, ty).to_string()).unwrap()
+    }
+
+    /// `var ∈ range`, or `None` if `var` has no bit-vector.
+    fn in_range<T: PrimInt>(&self, var: VarId, range: &Range<T>) -> Option<Bool<'ctx>> {
+        let (v, ty) = self.consts.get(&var)?;
+        Some(self.within(v, range, *ty))
+    }
+
+    /// `v ∈ range` for `v` of type `ty`.
+    fn within<T: PrimInt>(&self, v: &BV<'ctx>, range: &Range<T>, ty: IntType) -> Bool<'ctx> {
+        match range.rtype {
+            RangeType::Regular => Bool::and(
+                self.ctx,
+                &[
+                    &self.compare(BinOp::Ge, v, &self.constant(range.get_lower(), ty), ty),
+                    &self.compare(BinOp::Le, v, &self.constant(range.get_upper(), ty), ty),
+                ],
+            ),
+            RangeType::Empty => Bool::from_bool(self.ctx, false),
+            // Nothing is known yet.
+            RangeType::Unknown => Bool::from_bool(self.ctx, true),
+        }
+    }
+
+    fn compare(&self, op: BinOp, a: &BV<'ctx>, b: &BV<'ctx>, ty: IntType) -> Bool<'ctx> {
+        match (op, ty.signed) {
+            (BinOp::Lt, true) => a.bvslt(b),
+            (BinOp::Le, true) => a.bvsle(b),
+            (BinOp::Gt, true) => a.bvsgt(b),
+            (BinOp::Ge, true) => a.bvsge(b),
+            (BinOp::Lt, false) => a.bvult(b),
+            (BinOp::Le, false) => a.bvule(b),
+            (BinOp::Gt, false) => a.bvugt(b),
+            (BinOp::Ge, false) => a.bvuge(b),
+            (BinOp::Eq, _) => a._eq(b),
+            (BinOp::Ne, _) => a._eq(b).not(),
+            _ => unreachable!("not a comparison: {:?}", op),
+        }
+    }
+
+    /// The value of `source` of type `from` as a value of type `to`.
+    fn resize(source: &BV<'ctx>, from: IntType, to: IntType) -> BV<'ctx> {
+        if to.bits < from.bits {
+            source.extract(to.bits - 1, 0)
+        } else if to.bits == from.bits {
+            source.clone()
+        } else if from.signed {
+            source.sign_ext(to.bits - from.bits)
+        } else {
+            source.zero_ext(to.bits - from.bits)
+        }
+    }
+
+    /// The bit-vector of `source` as a value of type `ty`, and what a
+    /// constant that is not a single value tells about it. `None` if a
+    /// variable has no bit-vector or another type.
+    fn source<T: PrimInt>(
+        &self,
+        source: &OpSource<T>,
+        ty: IntType,
+    ) -> Option<(BV<'ctx>, Option<Bool<'ctx>>)> {
+        match source {
+            OpSource::Var(var) => {
+                let (v, v_ty) = self.consts.get(var)?;
+                (*v_ty == ty).then(|| (v.clone(), None))
+            }
+            OpSource::Const(range)
+                if range.is_regular() && range.get_lower() == range.get_upper() =>
+            {
+                Some((self.constant(range.get_lower(), ty), None))
+            }
+            OpSource::Const(range) => {
+                let v = BV::fresh_const(self.ctx, "const", ty.bits);
+                let within = self.within(&v, range, ty);
+                Some((v, Some(within)))
+            }
+        }
+    }
+
+    /// The amount a shift of a value of type `ty` by `amount` shifts by:
+    /// MIR masks it to the width of the shifted value.
+    fn shift_amount<T: PrimInt>(&self, amount: &OpSource<T>, ty: IntType) -> Option<BV<'ctx>> {
+        let amount = match amount {
+            OpSource::Var(var) => {
+                let (v, v_ty) = self.consts.get(var)?;
+                Self::resize(v, *v_ty, ty)
+            }
+            // The type of the constant is not known, but wrapping it into
+            // `ty` keeps the bits the mask keeps.
+            OpSource::Const(range)
+                if range.is_regular() && range.get_lower() == range.get_upper() =>
+            {
+                self.constant(range.get_lower(), ty)
+            }
+            OpSource::Const(_) => return None,
+        };
+        Some(amount.bvand(&BV::from_u64(self.ctx, (ty.bits - 1) as u64, ty.bits)))
+    }
+
+    /// `sink = op(sources) ∧ sink ∈ intersect`, or `None` if a variable of
+    /// the operation has no bit-vector or the operation is not supported.
+    fn encode<T: PrimInt>(&self, op: &BasicOpKind<T>) -> Option<Bool<'ctx>> {
+        let (sink, sink_ty) = self.consts.get(&op.get_sink())?;
+        let mut facts = Vec::new();
+        let value = match op {
+            BasicOpKind::Unary(unary) => match (unary.kind, &unary.source) {
+                (UnaryOpKind::Cast(target), OpSource::Var(var)) if target.bits == sink_ty.bits => {
+                    let (source, source_ty) = self.consts.get(var)?;
+                    Self::resize(source, *source_ty, target)
+                }
+                (UnaryOpKind::Cast(_), _) => return None,
+                (kind, source) => {
+                    let (source, fact) = self.source(source, *sink_ty)?;
+                    facts.extend(fact);
+                    match kind {
+                        UnaryOpKind::Neg => source.bvneg(),
+                        UnaryOpKind::Not => source.bvnot(),
+                        _ => source,
+                    }
+                }
+            },
+            BasicOpKind::Binary(binary) => {
+                let ty = binary.ty;
+                let (a, fact) = self.source(&binary.lhs, ty)?;
+                facts.extend(fact);
+                let b = match binary.op {
+                    BinOp::Shl | BinOp::ShlUnchecked | BinOp::Shr | BinOp::ShrUnchecked => {
+                        self.shift_amount(&binary.rhs, ty)?
+                    }
+                    _ => {
+                        let (b, fact) = self.source(&binary.rhs, ty)?;
+                        facts.extend(fact);
+                        b
+                    }
+                };
+                let (zero, one) = (
+                    BV::from_i64(self.ctx, 0, sink_ty.bits),
+                    BV::from_i64(self.ctx, 1, sink_ty.bits),
+                );
+                match binary.op {
+                    BinOp::Add | BinOp::AddUnchecked | BinOp::AddWithOverflow => a.bvadd(&b),
+                    BinOp::Sub | BinOp::SubUnchecked | BinOp::SubWithOverflow => a.bvsub(&b),
+                    BinOp::Mul | BinOp::MulUnchecked | BinOp::MulWithOverflow => a.bvmul(&b),
+                    BinOp::Div | BinOp::Rem => {
+                        // Dividing by zero panics before the assignment.
+                        facts.push(b._eq(&BV::from_i64(self.ctx, 0, ty.bits)).not());
+                        match (binary.op, ty.signed) {
+                            (BinOp::Div, true) => a.bvsdiv(&b),
+                            (BinOp::Div, false) => a.bvudiv(&b),
+                            (_, true) => a.bvsrem(&b),
+                            (_, false) => a.bvurem(&b),
+                        }
+                    }
+                    BinOp::BitAnd => a.bvand(&b),
+                    BinOp::BitOr => a.bvor(&b),
+                    BinOp::BitXor => a.bvxor(&b),
+                    BinOp::Shl | BinOp::ShlUnchecked => a.bvshl(&b),
+                    BinOp::Shr | BinOp::ShrUnchecked if ty.signed => a.bvashr(&b),
+                    BinOp::Shr | BinOp::ShrUnchecked => a.bvlshr(&b),
+                    BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                        self.compare(binary.op, &a, &b, ty).ite(&one, &zero)
+                    }
+                    // An `Ordering` as an `i8`.
+                    BinOp::Cmp => {
+                        let less = BV::from_i64(self.ctx, -1, sink_ty.bits);
+                        self.compare(BinOp::Lt, &a, &b, ty)
+                            .ite(&less, &a._eq(&b).ite(&zero, &one))
+                    }
+                    _ => return None,
+                }
+            }
+            // One assignment cannot stand for the values of several paths.
+            BasicOpKind::Phi(_) | BasicOpKind::Sigma(_) => return None,
+        };
+        if value.get_size() != sink_ty.bits {
+            return None;
+        }
+        let intersect = match op.get_intersect() {
+            IntervalType::Basic(basic) => self.in_range(op.get_sink(), basic.get_range())?,
+            IntervalType::Symb(symb) => {
+                let (bound, bound_ty) = self.consts.get(&symb.get_bound())?;
+                let predicate = symb.get_operation();
+                let comparison = matches!(
+                    predicate,
+                    BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge | BinOp::Eq | BinOp::Ne
+                );
+                if bound_ty != sink_ty || !comparison {
+                    return None;
+                }
+                self.compare(predicate, sink, bound, *sink_ty)
+            }
+        };
+        let equation = sink._eq(&value);
+        let mut conjuncts = vec![&equation, &intersect];
+        conjuncts.extend(facts.iter());
+        Some(Bool::and(self.ctx, &conjuncts))
+    }
+
+    /// Reads the value of `v` from a model, 64 bits at a time.
+    fn value_of<T: PrimInt>(model: &Model<'ctx>, v: &BV<'ctx>, ty: IntType) -> Option<T> {
+        let mut raw = 0u128;
+        for low in (0..ty.bits).step_by(64) {
+            let high = std::cmp::min(low + 64, ty.bits) - 1;
+            let chunk = model.eval(&v.extract(high, low), true)?.as_u64()?;
+            raw |= (chunk as u128) << low;
+        }
+        ty.value_from_bits(raw)
+    }
+
+    fn query<T: PrimInt>(&self, constraint: &Bool<'ctx>, v: &BV<'ctx>, ty: IntType) -> Answer<T> {
+        self.solver.push();
+        self.solver.assert(constraint);
+        let answer = match self.solver.check() {
+            SatResult::Sat => match self
+                .solver
+                .get_model()
+                .and_then(|model| Self::value_of(&model, v, ty))
+            {
+                Some(value) => Answer::Sat(value),
+                None => Answer::Unknown,
+            },
+            SatResult::Unsat => Answer::Unsat,
+            SatResult::Unknown => Answer::Unknown,
+        };
+        self.solver.pop(1);
+        answer
+    }
+
+    /// Checks the range of `var`.
+    pub fn check<T: PrimInt>(&self, var: VarId, range: &Range<T>) -> RangeCheck<T> {
+        let Some((v, ty)) = self.consts.get(&var) else {
+            return RangeCheck {
+                var,
+                range: range.clone(),
+                lower: BoundCheck::Unknown,
+                upper: BoundCheck::Unknown,
+            };
+        };
+        self.solver.push();
+        for definition in self.definitions_of(var) {
+            self.solver.assert(definition);
+        }
+        let check = self.check_bounds(var, range, v, *ty);
+        self.solver.pop(1);
+        check
+    }
+
+    /// Checks the range of `var`, whose bit-vector is `v`, once the
+    /// operations it is computed from are asserted.
+    fn check_bounds<T: PrimInt>(
+        &self,
+        var: VarId,
+        range: &Range<T>,
+        v: &BV<'ctx>,
+        ty: IntType,
+    ) -> RangeCheck<T> {
+        let report = |lower, upper| RangeCheck {
+            var,
+            range: range.clone(),
+            lower,
+            upper,
+        };
+        let disproved = |value| {
+            if self.inexact.contains(&var) {
+                BoundCheck::Unknown
+            } else {
+                BoundCheck::Disproved(value)
+            }
+        };
+        // Some solution, to start the searches from.
+        let witness = match self.query::<T>(&Bool::from_bool(self.ctx, true), v, ty) {
+            Answer::Sat(value) => value,
+            Answer::Unsat => return report(BoundCheck::Unreachable, BoundCheck::Unreachable),
+            Answer::Unknown => return report(BoundCheck::Unknown, BoundCheck::Unknown),
+        };
+        if !range.is_regular() {
+            // The analysis found no value at all.
+            return report(disproved(witness), disproved(witness));
+        }
+        let (lower, upper) = (range.get_lower(), range.get_upper());
+        let below = self.compare(BinOp::Lt, v, &self.constant(lower, ty), ty);
+        let lower = match self.query(&below, v, ty) {
+            Answer::Sat(value) => disproved(value),
+            Answer::Unknown => BoundCheck::Unknown,
+            Answer::Unsat => self.tighten_lower(v, ty, lower, witness),
+        };
+        let above = self.compare(BinOp::Gt, v, &self.constant(upper, ty), ty);
+        let upper = match self.query(&above, v, ty) {
+            Answer::Sat(value) => disproved(value),
+            Answer::Unknown => BoundCheck::Unknown,
+            Answer::Unsat => self.tighten_upper(v, ty, upper, witness),
+        };
+        report(lower, upper)
+    }
+
+    /// Searches the smallest value of `v`, knowing that none is below
+    /// `lower` and that `witness` is one.
+    fn tighten_lower<T: PrimInt>(
+        &self,
+        v: &BV<'ctx>,
+        ty: IntType,
+        lower: T,
+        witness: T,
+    ) -> BoundCheck<T> {
+        // No value is below `lo`, and `hi` is a value.
+        let (mut lo, mut hi) = (lower, witness);
+        while lo < hi {
+            let mid = floor_mid(lo, hi);
+            let at_most = self.compare(BinOp::Le, v, &self.constant(mid, ty), ty);
+            match self.query(&at_most, v, ty) {
+                Answer::Sat(value) => hi = value,
+                Answer::Unsat => lo = mid + T::one(),
+                Answer::Unknown => break,
+            }
+        }
+        match (lo == hi, lo == lower) {
+            (_, false) => BoundCheck::Tightened(lo),
+            (true, true) => BoundCheck::Exact,
+            (false, true) => BoundCheck::Confirmed,
+        }
+    }
+
+    /// Searches the largest value of `v`, knowing that none is above `upper`
+    /// and that `witness` is one.
+    fn tighten_upper<T: PrimInt>(
+        &self,
+        v: &BV<'ctx>,
+        ty: IntType,
+        upper: T,
+        witness: T,
+    ) -> BoundCheck<T> {
+        // `lo` is a value, and no value is above `hi`.
+        let (mut lo, mut hi) = (witness, upper);
+        while lo < hi {
+            let mid = ceil_mid(lo, hi);
+            let at_least = self.compare(BinOp::Ge, v, &self.constant(mid, ty), ty);
+            match self.query(&at_least, v, ty) {
+                Answer::Sat(value) => lo = value,
+                Answer::Unsat => hi = mid - T::one(),
+                Answer::Unknown => break,
+            }
+        }
+        match (lo == hi, hi == upper) {
+            (_, false) => BoundCheck::Tightened(hi),
+            (true, true) => BoundCheck::Exact,
+            (false, true) => BoundCheck::Confirmed,
+        }
+    }
+}
+
+impl<T> ConstraintGraph<'_, '_, T>
+where
+    T: PrimInt,
+{
+    /// Checks the range of every variable some operation defines, and
+    /// tightens the ranges the solver can. Every query gives up after
+    /// `timeout`.
+    pub fn check_with_smt(&mut self, timeout: Duration) -> Vec<RangeCheck<T>> {
+        let ctx = Context::new(&Config::new());
+        let checker = SmtChecker::new(&ctx, &self.vars, &self.oprs, timeout);
+        let mut checks = Vec::new();
+        for op in self.oprs.iter() {
+            let sink = op.get_sink();
+            checks.push(checker.check(sink, self.vars[sink].get_range()));
+        }
+        for check in &checks {
+            self.vars[check.var].set_range(check.refined_range());
+        }
+        checks
+    }
+}
@@ -13,6 +13,8 @@
 #![feature(never_type)]
 #![feature(option_get_or_insert_default)]
 #![feature(round_char_boundary)]
+#![feature(rustc_attrs)]
+#![feature(step_trait)]
 #![feature(try_blocks)]
 #![feature(yeet_expr)]
 // tidy-alphabetical-end
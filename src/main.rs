@@ -44,7 +44,7 @@ use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::graph::{dominators, Predecessors};
 use rustc_driver::Compilation;
 use rustc_driver::{Callbacks, RunCompiler};
-use rustc_hir::def_id::LocalDefId;
+use rustc_hir::def_id::{LocalDefId, LOCAL_CRATE};
 use rustc_index::IndexVec;
 use rustc_interface::{interface::Compiler, Queries};
 use rustc_middle::mir::pretty::*;
@@ -59,25 +59,15 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{debug, error, info, warn};
+use RAP_interval_demo::domain::export;
 use RAP_interval_demo::domain::ConstraintGraph::ConstraintGraph;
 use RAP_interval_demo::SSA::{PassRunner::*, SSATransformer::*};
 
-
-pub struct MyVisitor<'tcx> {
-    body_test: HashMap<LocalDefId, Option<bool>>,
-    body:  &'tcx Body<'tcx>,
-}
-
-impl<'tcx> MyVisitor<'tcx> {
-pub fn new(body:  &'tcx Body<'tcx>, def_id: LocalDefId) -> MyVisitor<'tcx> {
-    let mut body_test = HashMap::new();
-    body_test.insert(def_id, None); // 或 Some(true)/Some(false)
-    MyVisitor { body_test, body }
-}}
-
-fn analyze_mir<'tcx>(tcx: TyCtxt<'tcx>, def_id: LocalDefId) {
+/// Runs the analysis on `def_id` and returns its results. The constraint
+/// graph is also written to `dot` as a Graphviz digraph, if given.
+fn analyze_mir(tcx: TyCtxt<'_>, def_id: LocalDefId, dot: Option<&Path>) -> serde_json::Value {
     // let mir_built = tcx.mir_built(def_id);
     // let body = mir_built.borrow();
     // let mut body_steal  = tcx.mir_promoted(def_id).0.steal();
@@ -92,24 +82,63 @@ fn analyze_mir<'tcx>(tcx: TyCtxt<'tcx>, def_id: LocalDefId) {
 
     // cg.build_graph(&body_tcx);
     // cg.build_graph(&body_mut);
-    // !bug  
     let mut body = tcx.optimized_mir(def_id).clone();
-    {
-        let body_mut_ref: &mut Body<'tcx> = unsafe {
-            // 强制转换为更长的生命周期
-            &mut *(&mut body as *mut Body<'tcx>)
-        };        
-        let passrunner = PassRunner::new(tcx);
-        passrunner.run_pass(body_mut_ref);
-        passrunner.print_diff(body_mut_ref);
+    let passrunner = PassRunner::new(tcx);
+    let ssa_origin = passrunner.run_pass(&mut body);
 
-        let mut visitor = MyVisitor::new(body_mut_ref, def_id);
-        // let mut cg: ConstraintGraph<'tcx, u32> = ConstraintGraph::new();
-        // cg.build_graph(body_mut_ref);
+    let mut cg: ConstraintGraph<i128> = ConstraintGraph::new(tcx, &body);
+    cg.build_graph();
+    if cg.num_unsupported > 0 {
+        info!("{} unsupported assignments to integers", cg.num_unsupported);
+    }
+    cg.find_intervals();
+    info!(
+        "{} components ({} alone, largest {}), {} widening and {} narrowing steps",
+        cg.num_sccs,
+        cg.num_alone_sccs,
+        cg.size_max_scc,
+        cg.num_widening_steps,
+        cg.num_narrowing_steps
+    );
+    if let Some(path) = dot {
+        let name = tcx.def_path_str(def_id);
+        if let Err(err) = File::create(path).and_then(|mut file| cg.print_dot(&mut file, &name)) {
+            error!("failed to write {}: {}", path.display(), err);
+        }
+    }
+    let results = cg.results(ssa_origin.clone());
+    for (block, data) in body.basic_blocks.iter_enumerated() {
+        if let TerminatorKind::Return = data.terminator().kind {
+            let location = body.terminator_loc(block);
+            if let Some(range) = results.range_at(RETURN_PLACE, location) {
+                info!("{:?} returns {}", block, range);
+            }
+        }
     }
+    #[cfg(feature = "smt")]
+    for check in cg.check_with_smt(SMT_TIMEOUT) {
+        if !check.is_sound() {
+            error!(
+                "unsound range {} for {:?}: {:?}, {:?}",
+                check.range,
+                cg.vars.place(check.var),
+                check.lower,
+                check.upper
+            );
+        }
+    }
+    export::function_to_json(tcx, def_id.to_def_id(), &body, &ssa_origin, &cg)
 }
 
-struct MyDataflowCallbacks;
+/// How long the solver may spend on each query of the SMT pass.
+#[cfg(feature = "smt")]
+const SMT_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+struct MyDataflowCallbacks {
+    /// Functions, such as `main` or `m::f`, whose constraint graph is
+    /// written to `<crate>.<function>.dot`.
+    dot_functions: Vec<String>,
+}
 
 impl Callbacks for MyDataflowCallbacks {
     fn after_analysis<'tcx>(
@@ -119,13 +148,25 @@ impl Callbacks for MyDataflowCallbacks {
     ) -> Compilation {
         let mut tcx = queries.global_ctxt().unwrap();
         tcx.enter(|tcx| {
-            // 获取 main 函数对应的LocalDefId，仅做示例
-            if let Some(def_id) = tcx
-                .hir()
-                .body_owners()
-                .find(|id| tcx.def_path_str(*id) == "main")
-            {
-                analyze_mir(tcx, def_id);
+            let crate_name = tcx.crate_name(LOCAL_CRATE).to_string();
+            let mut found = HashSet::new();
+            // 分析每个函数，并导出需要的约束图
+            let results = export::crate_results(tcx, |def_id| {
+                let name = tcx.def_path_str(def_id);
+                let dot = self.dot_functions.contains(&name).then(|| {
+                    PathBuf::from(format!("{}.{}.dot", crate_name, name.replace("::", ".")))
+                });
+                found.insert(name);
+                analyze_mir(tcx, def_id, dot.as_deref())
+            });
+            for name in &self.dot_functions {
+                if !found.contains(name) {
+                    warn!("no function {} to write the constraint graph of", name);
+                }
+            }
+            let path = PathBuf::from(format!("{}.ranges.json", crate_name));
+            if let Err(err) = export::write_results(&path, &results) {
+                error!("failed to write {}: {}", path.display(), err);
             }
         });
         Compilation::Continue
@@ -136,6 +177,19 @@ impl Callbacks for MyDataflowCallbacks {
 fn main() {
     std::env::set_var("RUST_BACKTRACE", "full");
 
+    // `--dot <function>` writes the constraint graph of the function.
+    let mut dot_functions = Vec::new();
+    let mut cli = std::env::args().skip(1);
+    while let Some(arg) = cli.next() {
+        match (arg.as_str(), cli.next()) {
+            ("--dot", Some(function)) => dot_functions.push(function),
+            _ => {
+                eprintln!("usage: --dot <function>, repeated for each function");
+                std::process::exit(1);
+            }
+        }
+    }
+
     let args = vec![
         String::from("rustc"),
         String::from("tests/test1.rs"),
@@ -143,7 +197,7 @@ fn main() {
         String::from("-Zalways-encode-mir"),
     ];
 
-    RunCompiler::new(&args, &mut MyDataflowCallbacks)
+    RunCompiler::new(&args, &mut MyDataflowCallbacks { dot_functions })
         .run()
         .unwrap();
 }
@@ -0,0 +1,148 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::{BinOp, Local, Place};
+use RAP_interval_demo::domain::domain::{
+    BasicOpKind, BinaryOp, OpSource, UnaryOp, UnaryOpKind, VarId, VarNode, VarNodes,
+};
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+
+const I8: IntType = IntType::new(8, true);
+const U8: IntType = IntType::new(8, false);
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// Evaluates `_3 = op(_1, rhs)` with `_1: ty` in `lhs`.
+fn binary(op: BinOp, ty: IntType, lhs: Range<i128>, rhs: Range<i128>) -> Range<i128> {
+    let mut node = VarNode::new(Place::from(Local::from_usize(1)), Some(ty));
+    node.set_range(lhs);
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let lhs = vars.insert(node);
+    let sink = vars.insert(VarNode::new(Place::from(Local::from_usize(3)), Some(ty)));
+    let op = BinaryOp::new(sink, lhs, OpSource::Const(rhs), op, ty);
+    BasicOpKind::Binary(op).eval(&vars)
+}
+
+#[test]
+fn test_constant_operands() {
+    // `_3 = Add(copy _1, const 1_i8)`.
+    assert_eq!(
+        binary(BinOp::Add, I8, regular(0, 9), regular(1, 1)),
+        regular(1, 10)
+    );
+    assert_eq!(
+        binary(BinOp::Mul, I8, regular(-2, 3), regular(4, 4)),
+        regular(-8, 12)
+    );
+    assert_eq!(
+        binary(BinOp::BitAnd, U8, regular(0, 255), regular(15, 15)),
+        regular(0, 15)
+    );
+}
+
+#[test]
+fn test_checked_operations_do_not_wrap() {
+    // `_3 = Add(..)` leaves the type, to be wrapped when it is stored.
+    assert_eq!(
+        binary(BinOp::Add, I8, regular(0, 127), regular(1, 1)),
+        regular(1, 128)
+    );
+    // `_3 = AddWithOverflow(..)` is only read once it did not overflow.
+    assert_eq!(
+        binary(BinOp::AddWithOverflow, I8, regular(0, 127), regular(1, 1)),
+        regular(1, 127)
+    );
+    assert_eq!(
+        binary(BinOp::SubWithOverflow, U8, regular(0, 9), regular(1, 1)),
+        regular(0, 8)
+    );
+    assert!(binary(BinOp::MulWithOverflow, U8, regular(200, 255), regular(2, 2)).is_empty());
+}
+
+#[test]
+fn test_comparisons() {
+    assert_eq!(
+        binary(BinOp::Lt, I8, regular(0, 9), regular(10, 10)),
+        regular(1, 1)
+    );
+    assert_eq!(
+        binary(BinOp::Ge, I8, regular(0, 9), regular(10, 10)),
+        regular(0, 0)
+    );
+    assert_eq!(
+        binary(BinOp::Lt, I8, regular(0, 10), regular(10, 10)),
+        regular(0, 1)
+    );
+    assert_eq!(
+        binary(BinOp::Eq, I8, regular(3, 3), regular(3, 3)),
+        regular(1, 1)
+    );
+    assert_eq!(
+        binary(BinOp::Ne, I8, regular(0, 2), regular(3, 3)),
+        regular(1, 1)
+    );
+    assert_eq!(
+        binary(BinOp::Cmp, I8, regular(0, 2), regular(3, 3)),
+        regular(-1, -1)
+    );
+    assert_eq!(
+        binary(BinOp::Cmp, I8, regular(0, 5), regular(3, 3)),
+        regular(-1, 1)
+    );
+}
+
+#[test]
+fn test_shift_amount_is_masked() {
+    assert_eq!(
+        binary(BinOp::Shl, U8, regular(1, 1), regular(3, 3)),
+        regular(8, 8)
+    );
+    // `1_u8 << 9` shifts by `9 & 7`; an amount out of range may be any.
+    assert_eq!(
+        binary(BinOp::Shl, U8, regular(1, 1), regular(9, 9)),
+        regular(1, 128)
+    );
+    assert_eq!(
+        binary(BinOp::Shr, U8, regular(128, 128), regular(8, 8)),
+        regular(1, 128)
+    );
+}
+
+#[test]
+fn test_unknown_variable_is_unconstrained() {
+    // `v0` has no node, so its range is the full range of the carrier.
+    let op = BinaryOp::new(
+        VarId::from_usize(1),
+        VarId::from_usize(0),
+        OpSource::Const(regular(1, 1)),
+        BinOp::Lt,
+        I8,
+    );
+    assert_eq!(
+        BasicOpKind::Binary(op).eval(&VarNodes::new()),
+        regular(0, 1)
+    );
+}
+
+#[test]
+fn test_constant_source_display() {
+    let (lhs, sink) = (VarId::from_usize(0), VarId::from_usize(1));
+    let add: BasicOpKind<i128> = BasicOpKind::Binary(BinaryOp::new(
+        sink,
+        lhs,
+        OpSource::Const(regular(1, 1)),
+        BinOp::Add,
+        I8,
+    ));
+    assert_eq!(add.to_string(), "v1 = Add(v0, [1, 1]) ∩ ⊤");
+    assert_eq!(add.get_sources(), vec![lhs]);
+    let size: BasicOpKind<i128> = BasicOpKind::Unary(UnaryOp::new(
+        sink,
+        OpSource::Const(regular(4, 4)),
+        UnaryOpKind::Copy,
+    ));
+    assert_eq!(size.to_string(), "v1 = copy([4, 4]) ∩ ⊤");
+    assert!(size.get_sources().is_empty());
+}
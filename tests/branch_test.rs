@@ -0,0 +1,77 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::BinOp;
+use RAP_interval_demo::domain::range::{Range, RangeType};
+use RAP_interval_demo::domain::ConstraintGraph::ConstraintGraph;
+
+const MIN: i128 = i128::MIN;
+const MAX: i128 = i128::MAX;
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// The ranges of the variable on the true and false edges of a comparison
+/// with `constant`.
+fn refine(constant: i128, cmp_op: BinOp, const_in_left: bool) -> (Range<i128>, Range<i128>) {
+    let edge = |is_true_branch| {
+        ConstraintGraph::<i128>::apply_comparison(constant, cmp_op, is_true_branch, const_in_left)
+    };
+    (edge(true), edge(false))
+}
+
+#[test]
+fn test_variable_on_the_left() {
+    let table = [
+        (BinOp::Lt, regular(MIN, 9), regular(10, MAX)),
+        (BinOp::Le, regular(MIN, 10), regular(11, MAX)),
+        (BinOp::Gt, regular(11, MAX), regular(MIN, 10)),
+        (BinOp::Ge, regular(10, MAX), regular(MIN, 9)),
+        (BinOp::Eq, regular(10, 10), Range::default()),
+        (BinOp::Ne, Range::default(), regular(10, 10)),
+    ];
+    for (cmp_op, taken, not_taken) in table {
+        assert_eq!(
+            refine(10, cmp_op, false),
+            (taken, not_taken),
+            "x {:?} 10",
+            cmp_op
+        );
+    }
+}
+
+#[test]
+fn test_variable_on_the_right() {
+    let table = [
+        (BinOp::Lt, regular(11, MAX), regular(MIN, 10)),
+        (BinOp::Le, regular(10, MAX), regular(MIN, 9)),
+        (BinOp::Gt, regular(MIN, 9), regular(10, MAX)),
+        (BinOp::Ge, regular(MIN, 10), regular(11, MAX)),
+        (BinOp::Eq, regular(10, 10), Range::default()),
+        (BinOp::Ne, Range::default(), regular(10, 10)),
+    ];
+    for (cmp_op, taken, not_taken) in table {
+        assert_eq!(
+            refine(10, cmp_op, true),
+            (taken, not_taken),
+            "10 {:?} x",
+            cmp_op
+        );
+    }
+}
+
+#[test]
+fn test_constant_at_the_ends() {
+    // Nothing is below the minimum, and only it is not above it.
+    let (taken, not_taken) = refine(MIN, BinOp::Lt, false);
+    assert!(taken.is_empty());
+    assert_eq!(not_taken, Range::default());
+    let (taken, not_taken) = refine(MIN, BinOp::Gt, false);
+    assert_eq!(taken, regular(MIN + 1, MAX));
+    assert_eq!(not_taken, regular(MIN, MIN));
+    assert!(refine(MAX, BinOp::Lt, true).0.is_empty());
+    // A single value at an end of the carrier can be cut out.
+    assert_eq!(refine(MIN, BinOp::Ne, false).0, regular(MIN + 1, MAX));
+    assert_eq!(refine(MAX, BinOp::Ne, true).0, regular(MIN, MAX - 1));
+}
@@ -0,0 +1,99 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::{Local, Place};
+use RAP_interval_demo::domain::congruence::{Congruence, RangeCongruence};
+use RAP_interval_demo::domain::domain::{BasicOpKind, UnaryOp, UnaryOpKind, VarNode, VarNodes};
+use RAP_interval_demo::domain::lattice::Lattice;
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+
+const BOOL: IntType = IntType::new(1, false);
+const CHAR: IntType = IntType::new(32, false);
+const U8: IntType = IntType::new(8, false);
+const I8: IntType = IntType::new(8, true);
+const U16: IntType = IntType::new(16, false);
+const I32: IntType = IntType::new(32, true);
+const U32: IntType = IntType::new(32, false);
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// Evaluates `sink = source as target` through the constraint graph operation.
+fn cast(source_ty: IntType, source: Range<i128>, target: IntType) -> Range<i128> {
+    let mut node = VarNode::new(Place::from(Local::from_usize(1)), Some(source_ty));
+    node.set_range(source);
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let source = vars.insert(node);
+    let sink = vars.insert(VarNode::new(
+        Place::from(Local::from_usize(2)),
+        Some(target),
+    ));
+    let op = UnaryOp::new(sink, source, UnaryOpKind::Cast(target));
+    BasicOpKind::Unary(op).eval(&vars)
+}
+
+#[test]
+fn test_truncation() {
+    assert_eq!(cast(I32, regular(-1, 300), U8), regular(0, 255));
+    assert_eq!(cast(I32, regular(0, 200), U8), regular(0, 200));
+    assert_eq!(cast(U32, regular(256, 260), U8), regular(0, 4));
+    assert_eq!(cast(U16, regular(0, 65535), I8), regular(-128, 127));
+    assert_eq!(cast(I32, regular(200, 255), I8), regular(-56, -1));
+}
+
+#[test]
+fn test_extension() {
+    // Sign extension keeps the value, then reinterpreting it as unsigned wraps.
+    assert_eq!(cast(I8, regular(-1, -1), U16), regular(65535, 65535));
+    assert_eq!(cast(I8, regular(-128, 127), I32), regular(-128, 127));
+    assert_eq!(cast(U8, regular(0, 255), I32), regular(0, 255));
+    assert_eq!(cast(I8, regular(-2, 3), U32), regular(0, u32::MAX as i128));
+}
+
+#[test]
+fn test_bool_and_char() {
+    assert_eq!(cast(BOOL, regular(0, 1), I32), regular(0, 1));
+    assert_eq!(cast(CHAR, regular(97, 122), U32), regular(97, 122));
+    assert_eq!(cast(U8, regular(0, 255), CHAR), regular(0, 255));
+    assert_eq!(cast(CHAR, regular(0, 0x10ffff), U8), regular(0, 255));
+}
+
+#[test]
+fn test_source_range_is_wrapped_into_its_type() {
+    // A u8 variable can never hold 300, whatever the carrier computed.
+    assert_eq!(cast(U8, regular(250, 260), U16), regular(0, 255));
+}
+
+#[test]
+fn test_congruence_through_cast() {
+    // `x as u8` with `x` a multiple of 4 in `[0, 400]`: truncation wraps
+    // modulo 256, which 4 divides, so the stride survives.
+    let mut node = VarNode::new(Place::from(Local::from_usize(1)), Some(I32));
+    node.set_range(regular(0, 400));
+    node.set_congruence(Congruence::new(4, 0));
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let source = vars.insert(node);
+    let sink = vars.insert(VarNode::new(Place::from(Local::from_usize(2)), Some(U8)));
+    let op = UnaryOp::new(sink, source, UnaryOpKind::Cast(U8));
+    assert_eq!(
+        BasicOpKind::Unary(op).eval_congruence(&vars),
+        RangeCongruence::new(regular(0, 252), Congruence::new(4, 0))
+    );
+}
+
+#[test]
+fn test_var_node_reduces_with_congruence() {
+    let mut node: VarNode<i128> = VarNode::new(Place::from(Local::from_usize(1)), Some(I32));
+    node.set_range(regular(1, 10));
+    assert_eq!(node.get_congruence(), &Congruence::top());
+    node.set_congruence(Congruence::new(4, 0));
+    node.reduce_congruence();
+    assert_eq!(node.get_range(), &regular(4, 8));
+    // A range holding a single value of the congruence makes it a constant.
+    node.set_range(regular(5, 7));
+    node.set_congruence(Congruence::new(3, 0));
+    node.reduce_congruence();
+    assert_eq!(node.get_range(), &regular(6, 6));
+    assert_eq!(node.get_congruence(), &Congruence::constant(6));
+}
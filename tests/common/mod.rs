@@ -0,0 +1,185 @@
+//! Helpers shared by the integration tests.
+
+// Every test uses only some of the helpers.
+#![allow(dead_code, unused_macros)]
+
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate rustc_middle;
+
+use rustc_driver::{Callbacks, Compilation, RunCompiler};
+use rustc_interface::{interface::Compiler, Queries};
+use rustc_middle::mir::Body;
+use rustc_middle::ty::TyCtxt;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Expands to a `#[test]` per range operation, checking it against the
+/// concrete operation on a type `T` in scope, with Rust's wrapping semantics.
+///
+/// `$check_binary` and `$check_unary` take the name of an operation, its
+/// transfer function on ranges and the concrete operation, which is `None`
+/// where it panics: division by zero or an out-of-range shift. Unary
+/// operations ignore their second operand.
+macro_rules! range_op_tests {
+    ($check_binary:expr, $check_unary:expr) => {
+        fn shift_amount(y: T) -> Option<u32> {
+            u32::try_from(y).ok().filter(|&k| k < T::BITS)
+        }
+
+        #[test]
+        fn add() {
+            ($check_binary)("+", |a, b| a + b, |x, y| Some(x.wrapping_add(y)));
+        }
+
+        #[test]
+        fn sub() {
+            ($check_binary)("-", |a, b| a - b, |x, y| Some(x.wrapping_sub(y)));
+        }
+
+        #[test]
+        fn mul() {
+            ($check_binary)("*", |a, b| a * b, |x, y| Some(x.wrapping_mul(y)));
+        }
+
+        #[test]
+        fn div() {
+            ($check_binary)(
+                "/",
+                |a, b| a / b,
+                |x, y| (y != 0).then(|| x.wrapping_div(y)),
+            );
+        }
+
+        #[test]
+        fn rem() {
+            ($check_binary)(
+                "%",
+                |a, b| a % b,
+                |x, y| (y != 0).then(|| x.wrapping_rem(y)),
+            );
+        }
+
+        #[test]
+        fn shl() {
+            ($check_binary)(
+                "<<",
+                |a, b| a << b,
+                |x, y| shift_amount(y).map(|k| x.wrapping_shl(k)),
+            );
+        }
+
+        #[test]
+        fn shr() {
+            ($check_binary)(
+                ">>",
+                |a, b| a >> b,
+                |x, y| shift_amount(y).map(|k| x.wrapping_shr(k)),
+            );
+        }
+
+        #[test]
+        fn bitand() {
+            ($check_binary)("&", |a, b| a & b, |x, y| Some(x & y));
+        }
+
+        #[test]
+        fn bitor() {
+            ($check_binary)("|", |a, b| a | b, |x, y| Some(x | y));
+        }
+
+        #[test]
+        fn bitxor() {
+            ($check_binary)("^", |a, b| a ^ b, |x, y| Some(x ^ y));
+        }
+
+        #[test]
+        fn neg() {
+            ($check_unary)("-", |a, _| -a, |x, _| Some(x.wrapping_neg()));
+        }
+
+        #[test]
+        fn not() {
+            ($check_unary)("!", |a, _| !a, |x, _| Some(!x));
+        }
+    };
+}
+
+/// Runs `f` once the compiler has analysed the crate, and stops it there.
+struct TcxCallbacks<F, R> {
+    f: Option<F>,
+    output: Option<R>,
+}
+
+impl<F, R> Callbacks for TcxCallbacks<F, R>
+where
+    F: for<'tcx> FnOnce(TyCtxt<'tcx>) -> R + Send,
+    R: Send,
+{
+    fn after_analysis<'tcx>(
+        &mut self,
+        _compiler: &Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        queries.global_ctxt().unwrap().enter(|tcx| {
+            self.output = Some((self.f.take().unwrap())(tcx));
+        });
+        Compilation::Stop
+    }
+}
+
+/// Compiles the binary crate at `path` and returns what `f` returns for
+/// its type context.
+pub fn with_tcx_of_file<F, R>(path: &Path, f: F) -> R
+where
+    F: for<'tcx> FnOnce(TyCtxt<'tcx>) -> R + Send,
+    R: Send,
+{
+    let args = vec![
+        String::from("rustc"),
+        path.display().to_string(),
+        String::from("--crate-type=bin"),
+        String::from("--edition=2021"),
+        String::from("-Zalways-encode-mir"),
+    ];
+    let mut callbacks = TcxCallbacks {
+        f: Some(f),
+        output: None,
+    };
+    RunCompiler::new(&args, &mut callbacks).run().unwrap();
+    callbacks.output.unwrap()
+}
+
+/// Like `with_tcx_of_file`, for a crate whose only file is `source`.
+pub fn with_tcx<F, R>(source: &str, f: F) -> R
+where
+    F: for<'tcx> FnOnce(TyCtxt<'tcx>) -> R + Send,
+    R: Send,
+{
+    // Tests run in parallel, so every crate gets a file of its own.
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let run = RUNS.fetch_add(1, Ordering::Relaxed);
+    let path = std::env::temp_dir().join(format!("test_{}_{}.rs", std::process::id(), run));
+    fs::write(&path, source).unwrap();
+    let output = with_tcx_of_file(&path, f);
+    fs::remove_file(&path).unwrap();
+    output
+}
+
+/// Returns what `f` returns for the optimized MIR of `function`, such as
+/// `main` or `m::f`, in the crate whose only file is `source`.
+pub fn with_mir<F, R>(source: &str, function: &str, f: F) -> R
+where
+    F: for<'tcx> FnOnce(TyCtxt<'tcx>, &Body<'tcx>) -> R + Send,
+    R: Send,
+{
+    with_tcx(source, |tcx| {
+        let def_id = tcx
+            .hir()
+            .body_owners()
+            .find(|id| tcx.def_path_str(*id) == function)
+            .unwrap_or_else(|| panic!("no function {}", function));
+        f(tcx, tcx.optimized_mir(def_id))
+    })
+}
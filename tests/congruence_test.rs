@@ -0,0 +1,188 @@
+#![feature(rustc_private)]
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use RAP_interval_demo::domain::congruence::{Congruence, RangeCongruence};
+use RAP_interval_demo::domain::lattice::Lattice;
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+
+const ITERATIONS: usize = 300;
+
+fn regular(lb: i8, ub: i8) -> Range<i8> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+fn random_congruence(rng: &mut StdRng) -> Congruence<i8> {
+    match rng.random_range(0..12) {
+        0 => Congruence::Bottom,
+        1 => Congruence::constant(rng.random()),
+        2 => Congruence::new(rng.random_range(1..=63) * 2, rng.random()),
+        _ => Congruence::new(rng.random_range(1..=12), rng.random()),
+    }
+}
+
+fn random_product(rng: &mut StdRng) -> RangeCongruence<i8> {
+    let (a, b) = (rng.random::<i8>(), rng.random::<i8>());
+    let range = match rng.random_range(0..6) {
+        0 => Range::default(),
+        _ => regular(a.min(b), a.max(b)),
+    };
+    RangeCongruence::new(range, random_congruence(rng))
+}
+
+fn members(c: &Congruence<i8>) -> Vec<i8> {
+    (i8::MIN..=i8::MAX).filter(|&x| c.contains(x)).collect()
+}
+
+fn product_members(p: &RangeCongruence<i8>) -> Vec<i8> {
+    if !p.range.is_regular() {
+        return Vec::new();
+    }
+    (p.range.get_lower()..=p.range.get_upper())
+        .filter(|&x| p.cong.contains(x))
+        .collect()
+}
+
+fn for_all(check: impl Fn(&Congruence<i8>, &Congruence<i8>, &[i8], &[i8])) {
+    let mut rng = StdRng::seed_from_u64(0xc0de);
+    for _ in 0..ITERATIONS {
+        let a = random_congruence(&mut rng);
+        let b = random_congruence(&mut rng);
+        check(&a, &b, &members(&a), &members(&b));
+    }
+}
+
+#[test]
+fn test_arithmetic_soundness() {
+    // Without wrapping, the congruence alone must hold every result.
+    for_all(|a, b, xs, ys| {
+        for &x in xs {
+            for &y in ys {
+                if let Some(z) = x.checked_add(y) {
+                    assert!((a + b).contains(z), "{} + {} ∌ {}", a, b, z);
+                }
+                if let Some(z) = x.checked_sub(y) {
+                    assert!((a - b).contains(z), "{} - {} ∌ {}", a, b, z);
+                }
+                if let Some(z) = x.checked_mul(y) {
+                    assert!((a * b).contains(z), "{} * {} ∌ {}", a, b, z);
+                }
+                assert!((a & b).contains(x & y), "{} & {} ∌ {}", a, b, x & y);
+            }
+        }
+    });
+}
+
+#[test]
+fn test_shl_soundness() {
+    for_all(|a, _, xs, _| {
+        for k in 0..8 {
+            let amount = Congruence::constant(k as i8);
+            for &x in xs {
+                if let Some(z) = x.checked_mul(1 << k) {
+                    assert!((a << &amount).contains(z), "{} << {} ∌ {}", a, k, z);
+                }
+            }
+        }
+    });
+}
+
+#[test]
+fn test_lattice_soundness() {
+    for_all(|a, b, xs, ys| {
+        let join = a.join(b);
+        assert!(xs.iter().chain(ys).all(|&x| join.contains(x)));
+        // The meet is exact unless its stride does not fit, and then it is
+        // the more precise operand.
+        let meet = a.meet(b);
+        for x in i8::MIN..=i8::MAX {
+            if a.contains(x) && b.contains(x) {
+                assert!(meet.contains(x), "{} ⊓ {} ∌ {}", a, b, x);
+            }
+        }
+        assert!(meet.is_subset(a) || meet.is_subset(b));
+        if a.is_subset(b) {
+            assert!(xs.iter().all(|&x| b.contains(x)));
+        }
+    });
+    let (a, b) = (Congruence::new(4, 1), Congruence::new(6, 3));
+    assert_eq!(a.meet(&b), Congruence::new(12, 9));
+    assert_eq!(
+        Congruence::new(4, 0).meet(&Congruence::new(6, 1)),
+        Congruence::Bottom
+    );
+}
+
+#[test]
+fn test_product_soundness() {
+    // The product follows the wrapping semantics of `i8`.
+    let mut rng = StdRng::seed_from_u64(0xc0de);
+    for _ in 0..ITERATIONS {
+        let a = random_product(&mut rng);
+        let b = random_product(&mut rng);
+        let (sum, mul, and, shl) = (&a + &b, &a * &b, &a & &b, &a << &b);
+        for &x in &product_members(&a) {
+            for &y in &product_members(&b) {
+                for (result, z) in [
+                    (&sum, x.wrapping_add(y)),
+                    (&mul, x.wrapping_mul(y)),
+                    (&and, x & y),
+                ] {
+                    assert!(product_members(result).contains(&z), "{} {} ∌ {}", a, b, z);
+                }
+                if (0..8).contains(&y) {
+                    let z = x << y;
+                    assert!(product_members(&shl).contains(&z), "{} << {} ∌ {}", a, b, z);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_reduction() {
+    let four = Congruence::new(4, 0);
+    assert_eq!(
+        RangeCongruence::new(regular(1, 10), four),
+        RangeCongruence {
+            range: regular(4, 8),
+            cong: four
+        }
+    );
+    // A single value left becomes a constant.
+    assert_eq!(
+        RangeCongruence::new(regular(1, 6), four),
+        RangeCongruence::constant(4)
+    );
+    // No value left.
+    assert!(RangeCongruence::new(regular(5, 7), four).range.is_empty());
+}
+
+#[test]
+fn test_masks() {
+    // The low bits of `16ℤ + 5` are known: `x & 0x0F` is 5.
+    let x = Congruence::new(16, 5);
+    assert_eq!(&x & &Congruence::constant(0x0F), Congruence::constant(5));
+    // Clearing the low bits keeps the stride when it is aligned.
+    let y: Congruence<i32> = Congruence::new(24, 13);
+    assert_eq!(&y & &Congruence::constant(!7), Congruence::new(24, 8));
+    // Shifting by at least 3 makes every value a multiple of 8.
+    let shifted = RangeCongruence::new(
+        Range::new(0, 100, RangeType::Regular),
+        Congruence::new(1, 0),
+    );
+    let amount = RangeCongruence::new(Range::new(3, 5, RangeType::Regular), Congruence::new(1, 0));
+    assert_eq!((&shifted << &amount).cong, Congruence::new(8, 0));
+}
+
+#[test]
+fn test_fit() {
+    // Wrapping into `u8` only keeps the power-of-two part of the stride.
+    let p = RangeCongruence::new(
+        Range::new(0, 1000, RangeType::Regular),
+        Congruence::new(12, 0),
+    );
+    let fitted = p.fit(IntType::new(8, false));
+    assert_eq!(fitted.cong, Congruence::new(4, 0));
+    assert_eq!(fitted.range, Range::new(0, 252, RangeType::Regular));
+}
@@ -0,0 +1,147 @@
+#![feature(box_patterns)]
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+mod common;
+
+use common::with_tcx;
+use rustc_middle::mir::{Const, Operand, Rvalue, StatementKind};
+use std::collections::HashMap;
+use RAP_interval_demo::domain::const_eval::{const_range, const_value};
+use RAP_interval_demo::domain::range::{Range, RangeType};
+
+const SOURCE: &str = "
+fn big() -> u128 {
+    u128::MAX
+}
+
+fn small() -> i128 {
+    i128::MIN
+}
+
+fn main() {
+    big();
+    small();
+}
+";
+
+/// What the evaluator makes of the constant a function returns.
+#[derive(Debug, PartialEq)]
+struct Evaluated {
+    /// Whether the constant is still unevaluated in the MIR.
+    unevaluated: bool,
+    value: Option<i128>,
+    range: Range<i128>,
+    wide_value: Option<u128>,
+}
+
+fn evaluate(source: &str) -> HashMap<String, Evaluated> {
+    with_tcx(source, |tcx| {
+        let mut evaluated = HashMap::new();
+        for def_id in tcx.hir().body_owners() {
+            if !tcx.def_kind(def_id).is_fn_like() {
+                continue;
+            }
+            let body = tcx.optimized_mir(def_id);
+            for data in body.basic_blocks.iter() {
+                for stmt in &data.statements {
+                    let StatementKind::Assign(box (_, Rvalue::Use(Operand::Constant(c)))) =
+                        &stmt.kind
+                    else {
+                        continue;
+                    };
+                    evaluated.insert(
+                        tcx.def_path_str(def_id),
+                        Evaluated {
+                            unevaluated: matches!(c.const_, Const::Unevaluated(..)),
+                            value: const_value(tcx, c),
+                            range: const_range(tcx, c),
+                            wide_value: const_value(tcx, c),
+                        },
+                    );
+                }
+            }
+        }
+        evaluated
+    })
+}
+
+#[test]
+fn test_ends_of_128_bit_types() {
+    let evaluated = evaluate(SOURCE);
+    // `u128::MAX` does not fit in an i128 carrier, so its range is top
+    // rather than a clamped `[0, i128::MAX]`.
+    assert_eq!(
+        evaluated["big"],
+        Evaluated {
+            unevaluated: true,
+            value: None,
+            range: Range::default(),
+            wide_value: Some(u128::MAX),
+        }
+    );
+    assert_eq!(
+        evaluated["small"],
+        Evaluated {
+            unevaluated: true,
+            value: Some(i128::MIN),
+            range: Range::new(i128::MIN, i128::MIN, RangeType::Regular),
+            wide_value: None,
+        }
+    );
+}
+
+#[test]
+fn test_generic_constant_has_no_value() {
+    let evaluated = evaluate(
+        "
+fn generic<const N: u8>() -> u8 {
+    N
+}
+
+fn main() {
+    generic::<3>();
+}
+",
+    );
+    // `N` is only known once `generic` is instantiated.
+    assert_eq!(
+        evaluated["generic"],
+        Evaluated {
+            unevaluated: false,
+            value: None,
+            range: Range::new(0, 255, RangeType::Regular),
+            wide_value: None,
+        }
+    );
+}
+
+#[test]
+fn test_associated_constant_is_evaluated() {
+    let evaluated = evaluate(
+        "
+struct Limits;
+
+impl Limits {
+    const LOW: i16 = -300;
+}
+
+fn low() -> i16 {
+    Limits::LOW
+}
+
+fn main() {
+    low();
+}
+",
+    );
+    assert_eq!(
+        evaluated["low"],
+        Evaluated {
+            unevaluated: true,
+            value: Some(-300),
+            range: Range::new(-300, -300, RangeType::Regular),
+            wide_value: None,
+        }
+    );
+}
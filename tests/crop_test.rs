@@ -0,0 +1,74 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::{Local, Place};
+use RAP_interval_demo::domain::domain::VarNode;
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+
+const I32: IntType = IntType::new(32, true);
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// A node that went from `before` to `widened` during the widening phase.
+fn widened(place: Place<'_>, before: Range<i128>, widened: Range<i128>) -> VarNode<'_, i128> {
+    let mut node = VarNode::new(place, Some(I32));
+    node.set_range(widened);
+    node.store_abstract_state(&before);
+    node
+}
+
+#[test]
+fn test_abstract_state() {
+    let i = Place::from(Local::from_usize(1));
+    let max = i32::MAX as i128;
+    let min = i32::MIN as i128;
+    let state = |before, after| widened(i, before, after).get_abstract_state();
+    assert_eq!(state(regular(0, 0), regular(0, max)), '+');
+    assert_eq!(state(regular(0, 0), regular(min, 0)), '-');
+    assert_eq!(state(regular(0, 0), regular(min, max)), '?');
+    assert_eq!(state(regular(0, 9), regular(0, 9)), '0');
+    assert_eq!(state(Range::unknown(), regular(0, max)), '?');
+}
+
+#[test]
+fn test_crop_only_widened_bounds() {
+    let i = Place::from(Local::from_usize(1));
+    let max = i32::MAX as i128;
+    // The upper bound was widened: only it may come back down.
+    let mut node = widened(i, regular(0, 0), regular(0, max));
+    assert!(node.crop(&regular(5, 100)));
+    assert_eq!(node.get_range(), &regular(0, 100));
+
+    // Nothing was widened: nothing is narrowed.
+    let mut node = widened(i, regular(0, 9), regular(0, 9));
+    assert!(!node.crop(&regular(2, 5)));
+    assert_eq!(node.get_range(), &regular(0, 9));
+}
+
+#[test]
+fn test_crop_never_grows() {
+    let i = Place::from(Local::from_usize(1));
+    let mut node = widened(i, Range::unknown(), regular(0, 100));
+    assert!(!node.crop(&regular(-5, 200)));
+    assert_eq!(node.get_range(), &regular(0, 100));
+}
+
+#[test]
+fn test_crop_terminates() {
+    // `while i < j { i += 1; j -= 1; }` with `i = 0` and `j = 100`: once
+    // widened, `i` and `j` each recover their precise bound in one step and
+    // later crops leave them alone.
+    let i = Place::from(Local::from_usize(1));
+    let j = Place::from(Local::from_usize(2));
+    let (min, max) = (i32::MIN as i128, i32::MAX as i128);
+    let mut i_node = widened(i, regular(0, 0), regular(0, max));
+    let mut j_node = widened(j, regular(100, 100), regular(min, 100));
+    assert!(i_node.crop(&regular(0, 100)));
+    assert!(j_node.crop(&regular(0, 100)));
+    assert_eq!(i_node.get_range(), &regular(0, 100));
+    assert_eq!(j_node.get_range(), &regular(0, 100));
+    assert!(!i_node.crop(&regular(0, 99)));
+    assert!(!j_node.crop(&regular(1, 100)));
+}
@@ -0,0 +1,42 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::{Local, Place};
+use RAP_interval_demo::domain::domain::{BasicOpKind, UnaryOp, UnaryOpKind, VarId, VarNode};
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+
+#[test]
+fn test_range_display() {
+    assert_eq!(Range::new(-3, 7, RangeType::Regular).to_string(), "[-3, 7]");
+    assert_eq!(Range::new(5u8, 5, RangeType::Regular).to_string(), "[5, 5]");
+    assert_eq!(Range::<i32>::empty().to_string(), "∅");
+    assert_eq!(Range::<i32>::default().to_string(), "⊤");
+    assert_eq!(Range::<i32>::unknown().to_string(), "⊥");
+}
+
+#[test]
+fn test_int_type_display() {
+    assert_eq!(IntType::new(8, false).to_string(), "u8");
+    assert_eq!(IntType::new(64, true).to_string(), "i64");
+}
+
+#[test]
+fn test_var_node_display() {
+    let place = Place::from(Local::from_usize(1));
+    let mut node: VarNode<i128> = VarNode::new(place, Some(IntType::new(8, false)));
+    assert_eq!(node.to_string(), "_1: [0, 255]");
+    node.set_range(Range::new(3, 4, RangeType::Regular));
+    assert_eq!(node.to_string(), "_1: [3, 4]");
+
+    let mut out = Vec::new();
+    node.print(&mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "_1: [3, 4]\n");
+}
+
+#[test]
+fn test_operation_display() {
+    let (source, sink) = (VarId::from_usize(0), VarId::from_usize(1));
+    let cast = UnaryOpKind::Cast(IntType::new(8, false));
+    let op: BasicOpKind<i128> = BasicOpKind::Unary(UnaryOp::new(sink, source, cast));
+    assert_eq!(op.to_string(), "v1 = cast<u8>(v0) ∩ ⊤");
+}
@@ -0,0 +1,92 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::{BinOp, Local, Place};
+use RAP_interval_demo::domain::domain::{
+    BasicOpKind, BinaryOp, GenOprs, IntervalType, OpSource, PhiOp, SigmaOp, SymbInterval, SymbMap,
+    UnaryOp, UnaryOpKind, UseMap, VarNode, VarNodes,
+};
+use RAP_interval_demo::domain::dot::write_dot;
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+use RAP_interval_demo::domain::ConstraintGraph::Nuutila;
+
+const I32: IntType = IntType::new(32, true);
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+#[test]
+fn test_counting_loop() {
+    // `_1 = 0; _6 = 100; loop { _2 = phi(_1, _4); if _2 < _6 { _3 = sigma(_2);
+    // _4 = _3 + 1 } else { _5 = sigma(_2); break } }`.
+    let place = |i: usize| Place::from(Local::from_usize(i));
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    for i in 1..7 {
+        vars.insert(VarNode::new(place(i), Some(I32)));
+    }
+    let p = |i: usize| vars.id_of(&place(i)).unwrap();
+    let symb = |op| IntervalType::Symb(SymbInterval::new(Range::default(), p(6), op));
+    let oprs: GenOprs<i128> = GenOprs::from_raw(vec![
+        BasicOpKind::Unary(UnaryOp::new(
+            p(1),
+            OpSource::Const(regular(0, 0)),
+            UnaryOpKind::Copy,
+        )),
+        BasicOpKind::Phi(PhiOp::new(p(2), vec![p(1), p(4)])),
+        BasicOpKind::Sigma(SigmaOp::new(p(3), p(2), symb(BinOp::Lt))),
+        BasicOpKind::Binary(BinaryOp::new(
+            p(4),
+            p(3),
+            OpSource::Const(regular(1, 1)),
+            BinOp::Add,
+            I32,
+        )),
+        BasicOpKind::Sigma(SigmaOp::new(p(5), p(2), symb(BinOp::Ge))),
+        BasicOpKind::Unary(UnaryOp::new(
+            p(6),
+            OpSource::Const(regular(100, 100)),
+            UnaryOpKind::Copy,
+        )),
+    ]);
+    let mut usemap = UseMap::new();
+    let mut symbmap = SymbMap::new();
+    for (id, op) in oprs.iter_enumerated() {
+        for source in op.get_sources() {
+            usemap.entry(source).or_default().insert(id);
+        }
+        if let IntervalType::Symb(symb) = op.get_intersect() {
+            symbmap.entry(symb.get_bound()).or_default().insert(id);
+        }
+    }
+    vars.get_mut(&place(1)).unwrap().set_range(regular(0, 0));
+    let components = Nuutila::new(&vars, &oprs, &usemap, &symbmap).into_components();
+
+    let mut out = Vec::new();
+    write_dot(&mut out, "count", &vars, &oprs, &symbmap, &components).unwrap();
+    let dot = String::from_utf8(out).unwrap();
+
+    assert!(dot.starts_with("digraph \"count\" {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("v0 [shape = box, label = \"_1\\n[0, 0]\"];"));
+    assert!(dot.contains("op3 [shape = ellipse, label = \"Add(_3, [1, 1])\\n∩ ⊤\"];"));
+    assert!(dot.contains("op2 [shape = ellipse, label = \"sigma\\n∩ Lt(_6) ∩ ⊤\"];"));
+    assert!(dot.contains("v2 -> op3;\n"));
+    assert!(dot.contains("op3 -> v3;\n"));
+    // `_6` bounds both sigmas of `_2`.
+    assert!(dot.contains("v5 -> op2 [style = dashed];\n"));
+    assert!(dot.contains("v5 -> op4 [style = dashed];\n"));
+    assert_eq!(dot.matches("[style = dashed]").count(), 2);
+
+    // Only the loop `_2 -> _3 -> _4 -> _2` is a cluster, with the
+    // operations that define its variables.
+    assert_eq!(dot.matches("subgraph cluster_").count(), 1);
+    let start = dot.find("subgraph cluster_").unwrap();
+    let cluster = &dot[start..start + dot[start..].find("\n    }\n").unwrap()];
+    for id in ["v1 ", "v2 ", "v3 ", "op1 ", "op2 ", "op3 "] {
+        assert!(cluster.contains(id), "{} is not in {}", id, cluster);
+    }
+    for id in ["v0 ", "v4 ", "op0 ", "op4 "] {
+        assert!(!cluster.contains(id), "{} is in {}", id, cluster);
+    }
+}
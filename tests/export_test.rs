@@ -0,0 +1,118 @@
+#![feature(rustc_private)]
+
+mod common;
+
+use common::with_tcx;
+use serde_json::{json, Value};
+use RAP_interval_demo::domain::export::{
+    crate_results, crate_to_json, function_to_json, range_to_json, SCHEMA_VERSION,
+};
+use RAP_interval_demo::domain::range::{Range, RangeType};
+use RAP_interval_demo::domain::ConstraintGraph::ConstraintGraph;
+use RAP_interval_demo::SSA::PassRunner::PassRunner;
+
+const SOURCE: &str = "
+const LIMIT: i32 = 10;
+
+fn count() -> i32 {
+    let mut i = 0;
+    while i < LIMIT {
+        i += 1;
+    }
+    i
+}
+
+fn clamp(x: u8) -> u8 {
+    if x > 100 {
+        100
+    } else {
+        x
+    }
+}
+
+fn main() {
+    count();
+    clamp(7);
+}
+";
+
+#[test]
+fn test_range_to_json() {
+    assert_eq!(
+        range_to_json(&Range::new(0i128, 100, RangeType::Regular)),
+        json!({ "kind": "regular", "lo": "0", "hi": "100" })
+    );
+    assert_eq!(
+        range_to_json(&Range::<i32>::empty()),
+        json!({ "kind": "empty" })
+    );
+    assert_eq!(
+        range_to_json(&Range::<i32>::unknown()),
+        json!({ "kind": "unknown" })
+    );
+}
+
+#[test]
+fn test_wide_bounds_are_exact() {
+    let range = Range::new(i128::MIN, u64::MAX as i128 + 1, RangeType::Regular);
+    let value = range_to_json(&range);
+    assert_eq!(value["lo"], "-170141183460469231731687303715884105728");
+    assert_eq!(value["hi"], "18446744073709551616");
+}
+
+#[test]
+fn test_crate_envelope_is_versioned() {
+    let results = crate_to_json("test1", vec![json!({ "name": "main" })]);
+    assert_eq!(results["schema_version"], SCHEMA_VERSION);
+    assert_eq!(results["crate"], "test1");
+    assert_eq!(results["functions"][0]["name"], "main");
+}
+
+/// The results file of the crate whose only file is `source`.
+fn export(source: &str) -> Value {
+    with_tcx(source, |tcx| {
+        crate_results(tcx, |def_id| {
+            let mut body = tcx.optimized_mir(def_id).clone();
+            let ssa_origin = PassRunner::new(tcx).run_pass(&mut body);
+            let mut cg: ConstraintGraph<i128> = ConstraintGraph::new(tcx, &body);
+            cg.build_graph();
+            cg.find_intervals();
+            function_to_json(tcx, def_id.to_def_id(), &body, &ssa_origin, &cg)
+        })
+    })
+}
+
+/// The ranges of the SSA versions of `source_name` in the function `name`.
+fn ranges_of(results: &Value, name: &str, source_name: &str) -> Vec<Value> {
+    let function = results["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|function| function["name"] == name)
+        .unwrap();
+    function["variables"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|variable| variable["source_name"] == source_name)
+        .map(|variable| variable["range"].clone())
+        .collect()
+}
+
+#[test]
+fn test_every_function_is_exported() {
+    let results = export(SOURCE);
+    assert_eq!(results["schema_version"], SCHEMA_VERSION);
+    let mut names: Vec<_> = results["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|function| function["name"].as_str().unwrap())
+        .collect();
+    names.sort_unstable();
+    assert_eq!(names, ["clamp", "count", "main"]);
+    // `LIMIT` is only evaluated at compile time, but `count` reads it.
+    assert!(ranges_of(&results, "count", "i")
+        .contains(&json!({ "kind": "regular", "lo": "10", "hi": "10" })));
+    assert!(!ranges_of(&results, "clamp", "x").is_empty());
+}
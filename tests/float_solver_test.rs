@@ -0,0 +1,78 @@
+#![cfg(feature = "float")]
+#![feature(rustc_private)]
+
+mod common;
+
+use common::with_mir;
+use std::collections::HashMap;
+use RAP_interval_demo::domain::float::{FloatRange, FloatType};
+use RAP_interval_demo::domain::ConstraintGraph::ConstraintGraph;
+use RAP_interval_demo::SSA::PassRunner::PassRunner;
+
+// In e-SSA form `grow` is
+//
+//   bb0: _1 = const 0f64; goto -> bb1
+//   bb1: _4 = (copy _1, copy _6); _3 = copy _4; _2 = Lt(move _3, const 10f64);
+//        switchInt(move _2) -> [0: bb3, otherwise: bb2]
+//   bb2: _5 = (copy _4, const 10f64, const 1_usize); _6 = Add(copy _5, const 1.5f64)
+//   bb3: _7 = (copy _4, const 10f64, const 2_usize); _8 = copy _7; return
+const SOURCE: &str = "
+fn grow() -> f64 {
+    let mut x = 0.0;
+    while x < 10.0 {
+        x += 1.5;
+    }
+    x
+}
+
+fn main() {
+    grow();
+}
+";
+
+/// The largest `f64` below 11.5, which the loop can reach from the largest
+/// value below 10.
+const TOP: f64 = 11.499999999999998;
+
+/// The range of every float variable of `grow`, by name.
+fn analyze(source: &str) -> HashMap<String, FloatRange> {
+    with_mir(source, "grow", |tcx, body| {
+        let mut body = body.clone();
+        PassRunner::new(tcx).run_pass(&mut body);
+        let mut cg: ConstraintGraph<i128> = ConstraintGraph::new(tcx, &body);
+        cg.build_graph();
+        cg.find_intervals();
+        cg.float_vars
+            .iter()
+            .map(|node| (format!("{:?}", node.get_value()), *node.get_range()))
+            .collect()
+    })
+}
+
+#[test]
+fn test_counting_loop_is_solved() {
+    let ranges = analyze(SOURCE);
+    let expected = [
+        ("_1", FloatRange::new(0.0, 0.0)),
+        // The phi at the loop head, after narrowing.
+        ("_4", FloatRange::new(0.0, TOP)),
+        // The sigmas of `x < 10` on the edges into and out of the loop.
+        ("_5", FloatRange::new(0.0, FloatType::F64.next_down(10.0))),
+        ("_7", FloatRange::new(10.0, TOP)),
+        ("_6", FloatRange::new(1.5, TOP)),
+        // The returned value.
+        ("_8", FloatRange::new(10.0, TOP)),
+    ];
+    for (name, range) in expected {
+        assert_eq!(ranges.get(name), Some(&range), "{}", name);
+    }
+}
+
+#[test]
+fn test_named_constant_bound_is_evaluated() {
+    let source = SOURCE
+        .replace("x < 10.0", "x < LIMIT")
+        .replace("fn main", "const LIMIT: f64 = 10.0;\n\nfn main");
+    let ranges = analyze(&source);
+    assert_eq!(ranges.get("_7"), Some(&FloatRange::new(10.0, TOP)));
+}
@@ -0,0 +1,248 @@
+#![cfg(feature = "float")]
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rustc_middle::mir::BinOp;
+use RAP_interval_demo::domain::float::{FloatRange, FloatType};
+use RAP_interval_demo::domain::lattice::Lattice;
+
+const ITERATIONS: usize = 2000;
+
+/// Values around zero, both zeros, infinities, NaN and values that overflow
+/// or round when combined.
+fn random_value(rng: &mut StdRng) -> f64 {
+    match rng.random_range(0..12) {
+        0 => f64::INFINITY,
+        1 => f64::NEG_INFINITY,
+        2 => 0.0,
+        3 => -0.0,
+        4 => f64::NAN,
+        5 => f64::MAX,
+        6 => f64::MIN_POSITIVE / 3.0,
+        7 => 1.0 / 3.0,
+        _ => rng.random_range(-8.0..8.0),
+    }
+}
+
+fn random_range(rng: &mut StdRng) -> FloatRange {
+    let (a, b) = (random_value(rng), random_value(rng));
+    let range = match (a.is_nan(), b.is_nan()) {
+        (true, true) => FloatRange::nan(),
+        (true, false) | (false, true) => FloatRange::singleton(a.min(b)).with_nan(),
+        (false, false) => FloatRange::new(a.min(b), a.max(b)),
+    };
+    if rng.random_range(0..4) == 0 {
+        range.with_nan()
+    } else {
+        range
+    }
+}
+
+/// A value of the range, or `None` if it has none.
+fn sample(rng: &mut StdRng, range: &FloatRange) -> Option<f64> {
+    let Some((lo, hi)) = range.bounds() else {
+        return range.may_be_nan().then_some(f64::NAN);
+    };
+    Some(match rng.random_range(0..5) {
+        0 => lo,
+        1 => hi,
+        2 if range.may_be_nan() => f64::NAN,
+        2 if range.contains(0.0) => 0.0,
+        _ => {
+            let x = rng.random_range(-8.0..8.0f64).clamp(lo, hi);
+            if range.contains(x) {
+                x
+            } else {
+                lo
+            }
+        }
+    })
+}
+
+fn for_all(check: impl Fn(FloatRange, f64, FloatRange, f64)) {
+    let mut rng = StdRng::seed_from_u64(0xf10a7);
+    for _ in 0..ITERATIONS {
+        let a = random_range(&mut rng);
+        let b = random_range(&mut rng);
+        for _ in 0..8 {
+            if let (Some(x), Some(y)) = (sample(&mut rng, &a), sample(&mut rng, &b)) {
+                check(a, x, b, y);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_arithmetic_soundness_f64() {
+    for_all(|a, x, b, y| {
+        let ty = FloatType::F64;
+        assert!(
+            a.add(&b, ty).contains(x + y),
+            "{} + {} ∌ {} + {}",
+            a,
+            b,
+            x,
+            y
+        );
+        assert!(
+            a.sub(&b, ty).contains(x - y),
+            "{} - {} ∌ {} - {}",
+            a,
+            b,
+            x,
+            y
+        );
+        assert!(
+            a.mul(&b, ty).contains(x * y),
+            "{} * {} ∌ {} * {}",
+            a,
+            b,
+            x,
+            y
+        );
+        assert!(
+            a.div(&b, ty).contains(x / y),
+            "{} / {} ∌ {} / {}",
+            a,
+            b,
+            x,
+            y
+        );
+    });
+}
+
+#[test]
+fn test_arithmetic_soundness_f32() {
+    let to_f32 = |r: FloatRange| match r.bounds() {
+        Some((lo, hi)) => {
+            let range = FloatRange::new(lo as f32 as f64, hi as f32 as f64);
+            if r.may_be_nan() {
+                range.with_nan()
+            } else {
+                range
+            }
+        }
+        None => r,
+    };
+    for_all(|a, x, b, y| {
+        let (a, b) = (to_f32(a), to_f32(b));
+        let (x, y) = (x as f32, y as f32);
+        if !a.contains(x as f64) || !b.contains(y as f64) {
+            return;
+        }
+        let ty = FloatType::F32;
+        assert!(a.add(&b, ty).contains((x + y) as f64));
+        assert!(a.sub(&b, ty).contains((x - y) as f64));
+        assert!(a.mul(&b, ty).contains((x * y) as f64));
+        assert!(a.div(&b, ty).contains((x / y) as f64));
+    });
+}
+
+#[test]
+fn test_outward_rounding() {
+    let third = FloatRange::singleton(1.0).div(&FloatRange::singleton(3.0), FloatType::F64);
+    let (lo, hi) = third.bounds().unwrap();
+    assert!(lo < hi);
+    assert!(lo <= 1.0 / 3.0 && 1.0 / 3.0 <= hi);
+    // Exact results stay exact.
+    let sum = FloatRange::singleton(0.5).add(&FloatRange::singleton(0.25), FloatType::F64);
+    assert_eq!(sum, FloatRange::singleton(0.75));
+    // A finite overflow keeps `MAX` as its lower bound.
+    let big = FloatRange::singleton(f64::MAX).add(&FloatRange::singleton(f64::MAX), FloatType::F64);
+    assert_eq!(big, FloatRange::new(f64::MAX, f64::INFINITY));
+}
+
+#[test]
+fn test_division_by_range_with_zero() {
+    let x = FloatRange::new(1.0, 2.0);
+    let ty = FloatType::F64;
+    // `+0.0` as the lower bound: only the positive zero.
+    assert_eq!(
+        x.div(&FloatRange::new(0.0, 1.0), ty),
+        FloatRange::new(1.0, f64::INFINITY)
+    );
+    // `-0.0` divides into `-inf`.
+    let both = x.div(&FloatRange::new(-0.0, 1.0), ty);
+    assert!(both.contains(f64::NEG_INFINITY) && !both.may_be_nan());
+    // `0 / 0` is NaN.
+    assert!(FloatRange::new(0.0, 1.0)
+        .div(&FloatRange::new(0.0, 1.0), ty)
+        .may_be_nan());
+}
+
+#[test]
+fn test_refine_soundness() {
+    let ops = [
+        BinOp::Lt,
+        BinOp::Le,
+        BinOp::Gt,
+        BinOp::Ge,
+        BinOp::Eq,
+        BinOp::Ne,
+    ];
+    for_all(|a, x, b, y| {
+        for op in ops {
+            let holds = match op {
+                BinOp::Lt => x < y,
+                BinOp::Le => x <= y,
+                BinOp::Gt => x > y,
+                BinOp::Ge => x >= y,
+                BinOp::Eq => x == y,
+                _ => x != y,
+            };
+            let refined = a.refine(op, &b, holds, FloatType::F64);
+            assert!(
+                refined.contains(x),
+                "{:?} {} {} {}: {} ∌ {}",
+                op,
+                holds,
+                a,
+                b,
+                refined,
+                x
+            );
+            assert!(refined.is_subset(&a));
+        }
+    });
+}
+
+#[test]
+fn test_refine_nan() {
+    let x = FloatRange::new(f64::NEG_INFINITY, f64::INFINITY).with_nan();
+    let c = FloatRange::singleton(1.0);
+    let ty = FloatType::F64;
+    // `x < 1.0` excludes NaN, `!(x < 1.0)` keeps it.
+    assert_eq!(
+        x.refine(BinOp::Lt, &c, true, ty),
+        FloatRange::new(f64::NEG_INFINITY, 1.0 - f64::EPSILON / 2.0)
+    );
+    assert_eq!(
+        x.refine(BinOp::Lt, &c, false, ty),
+        FloatRange::new(1.0, f64::INFINITY).with_nan()
+    );
+    // `x == 0.0` holds for both zeros.
+    let zero = FloatRange::singleton(0.0);
+    assert_eq!(
+        x.refine(BinOp::Eq, &zero, true, ty),
+        FloatRange::new(-0.0, 0.0)
+    );
+    // Nothing is learned against a NaN bound when the comparison failed.
+    assert_eq!(x.refine(BinOp::Lt, &FloatRange::nan(), false, ty), x);
+    assert!(x.refine(BinOp::Lt, &FloatRange::nan(), true, ty).is_empty());
+}
+
+#[test]
+fn test_lattice() {
+    let a = FloatRange::new(0.0, 1.0);
+    let b = FloatRange::new(0.5, 2.0).with_nan();
+    assert_eq!(a.join(&b), FloatRange::new(0.0, 2.0).with_nan());
+    assert_eq!(a.meet(&b), FloatRange::new(0.5, 1.0));
+    assert_eq!(
+        a.widen(&a.join(&b)),
+        FloatRange::new(0.0, f64::INFINITY).with_nan()
+    );
+    assert_eq!(FloatRange::new(0.0, f64::INFINITY).narrow(&a), a);
+    assert!(FloatRange::bottom().is_subset(&a) && a.is_subset(&FloatRange::top()));
+}
@@ -0,0 +1,135 @@
+#![feature(rustc_private)]
+
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+#[test]
+fn test_type_bounds() {
+    let u8_ty = IntType::new(8, false);
+    let i8_ty = IntType::new(8, true);
+    let usize_ty = IntType::new(64, false);
+    assert_eq!(
+        (u8_ty.min_value::<i128>(), u8_ty.max_value::<i128>()),
+        (0, 255)
+    );
+    assert_eq!(
+        (i8_ty.min_value::<i128>(), i8_ty.max_value::<i128>()),
+        (-128, 127)
+    );
+    assert_eq!(usize_ty.max_value::<i128>(), u64::MAX as i128);
+    assert_eq!(IntType::new(1, false).max_value::<i128>(), 1);
+    assert_eq!(IntType::new(128, true).min_value::<i128>(), i128::MIN);
+    assert_eq!(IntType::new(128, false).max_value::<u128>(), u128::MAX);
+}
+
+#[test]
+fn test_narrow_carrier_saturates() {
+    // A u64 range cannot be represented in an i32 carrier.
+    assert_eq!(IntType::new(64, false).max_value::<i32>(), i32::MAX);
+    assert_eq!(IntType::new(64, true).min_value::<i32>(), i32::MIN);
+}
+
+#[test]
+fn test_full_range_beyond_the_carrier_is_top() {
+    let u128_ty = IntType::new(128, false);
+    let i128_ty = IntType::new(128, true);
+    assert!(!u128_ty.fits_in::<i128>());
+    assert!(u128_ty.fits_in::<u128>());
+    assert!(i128_ty.fits_in::<i128>());
+    assert!(!IntType::new(64, false).fits_in::<i32>());
+    // A u128 cannot be clamped to `[0, i128::MAX]`, which misses half of it.
+    assert_eq!(u128_ty.full_range::<i128>(), Range::default());
+    assert_eq!(
+        IntType::new(64, false).full_range::<i32>(),
+        Range::default()
+    );
+    assert_eq!(i128_ty.full_range::<i128>(), regular(i128::MIN, i128::MAX));
+    assert_eq!(
+        u128_ty.full_range::<u128>(),
+        Range::new(0, u128::MAX, RangeType::Regular)
+    );
+}
+
+#[test]
+fn test_fit_keeps_ranges_inside_the_type() {
+    let u8_ty = IntType::new(8, false);
+    assert_eq!(u8_ty.fit(&regular(3, 200)), regular(3, 200));
+    assert_eq!(u8_ty.fit(&regular(-5, 300)), regular(0, 255));
+}
+
+#[test]
+fn test_fit_wraps_around() {
+    let u8_ty = IntType::new(8, false);
+    let i8_ty = IntType::new(8, true);
+    // u8 [250, 255] + 10 wraps to [4, 9].
+    assert_eq!(u8_ty.fit(&regular(260, 265)), regular(4, 9));
+    assert_eq!(u8_ty.fit(&regular(-3, -1)), regular(253, 255));
+    assert_eq!(i8_ty.fit(&regular(128, 129)), regular(-128, -127));
+    // Straddling the wrapping point loses everything.
+    assert_eq!(i8_ty.fit(&regular(120, 130)), regular(-128, 127));
+    assert_eq!(u8_ty.fit(&regular(0, 256)), regular(0, 255));
+}
+
+#[test]
+fn test_fit_leaves_unknown_and_empty_alone() {
+    let u8_ty = IntType::new(8, false);
+    assert!(u8_ty.fit(&Range::<i128>::unknown()).is_unknown());
+    assert!(u8_ty.fit(&Range::<i128>::empty()).is_empty());
+}
+
+#[test]
+fn test_fit_in_a_wide_carrier() {
+    let u128_ty = IntType::new(128, false);
+    let i64_ty = IntType::new(64, true);
+    assert_eq!(u128_ty.fit(&regular(-1, 5)), u128_ty.full_range());
+    assert_eq!(
+        i64_ty.fit(&regular(i64::MAX as i128 + 1, i64::MAX as i128 + 1)),
+        regular(i64::MIN as i128, i64::MIN as i128)
+    );
+    assert_eq!(
+        i64_ty.fit(&regular(i128::MIN, i128::MAX)),
+        i64_ty.full_range()
+    );
+}
+
+#[test]
+fn test_value_from_bits_sign_extends() {
+    let i8_ty = IntType::new(8, true);
+    let u8_ty = IntType::new(8, false);
+    assert_eq!(i8_ty.value_from_bits::<i128>(0xff), Some(-1));
+    assert_eq!(i8_ty.value_from_bits::<i128>(0x80), Some(-128));
+    assert_eq!(u8_ty.value_from_bits::<i128>(0xff), Some(255));
+    assert_eq!(IntType::new(1, false).value_from_bits::<i128>(1), Some(1));
+    assert_eq!(
+        IntType::new(128, true).value_from_bits::<i128>(i128::MIN as u128),
+        Some(i128::MIN)
+    );
+    assert_eq!(
+        IntType::new(128, false).value_from_bits::<i128>(u128::MAX),
+        None
+    );
+    assert_eq!(
+        IntType::new(32, false).value_from_bits::<i128>('\u{10ffff}' as u128),
+        Some(0x10ffff)
+    );
+}
+
+#[test]
+fn test_value_from_bits_at_the_extremes() {
+    let i128_ty = IntType::new(128, true);
+    let u128_ty = IntType::new(128, false);
+    assert_eq!(
+        i128_ty.value_from_bits::<i128>(i128::MIN as u128),
+        Some(i128::MIN)
+    );
+    assert_eq!(u128_ty.value_from_bits::<u128>(u128::MAX), Some(u128::MAX));
+    // Values the carrier cannot hold are reported instead of truncated.
+    assert_eq!(u128_ty.value_from_bits::<i128>(u128::MAX), None);
+    assert_eq!(
+        IntType::new(64, true).value_from_bits::<u64>(u64::MAX as u128),
+        None
+    );
+}
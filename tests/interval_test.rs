@@ -1,3 +1,4 @@
+#![feature(rustc_private)]
 #[cfg(test)]
 use intervals::{bounds, Interval};
 
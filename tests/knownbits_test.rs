@@ -0,0 +1,169 @@
+#![feature(rustc_private)]
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use RAP_interval_demo::domain::knownbits::KnownBits;
+use RAP_interval_demo::domain::lattice::Lattice;
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+
+const ITERATIONS: usize = 300;
+
+fn regular<T: num_traits::PrimInt>(lb: T, ub: T) -> Range<T> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// Known bits of an `i8`: a random mask of known bits taken from a random
+/// value, sign extended.
+fn random_known(rng: &mut StdRng) -> KnownBits {
+    let value: i8 = rng.random();
+    let known: u8 = match rng.random_range(0..4) {
+        0 => 0,
+        1 => 0xFF,
+        _ => rng.random(),
+    };
+    KnownBits {
+        zeros: (!value as u8 & known) as i8 as u128,
+        ones: (value as u8 & known) as i8 as u128,
+    }
+    .fit(IntType::new(8, true))
+}
+
+fn contains(k: &KnownBits, x: i8) -> bool {
+    let x = x as i128 as u128;
+    x & k.zeros == 0 && !x & k.ones == 0
+}
+
+fn members(k: &KnownBits) -> Vec<i8> {
+    (i8::MIN..=i8::MAX).filter(|&x| contains(k, x)).collect()
+}
+
+fn for_all(check: impl Fn(&KnownBits, &KnownBits, &[i8], &[i8])) {
+    let mut rng = StdRng::seed_from_u64(0xb175);
+    for _ in 0..ITERATIONS {
+        let a = random_known(&mut rng);
+        let b = random_known(&mut rng);
+        check(&a, &b, &members(&a), &members(&b));
+    }
+}
+
+#[test]
+fn test_transfer_soundness() {
+    let i8_ty = IntType::new(8, true);
+    for_all(|&a, &b, xs, ys| {
+        let results = [
+            (a & b).fit(i8_ty),
+            (a | b).fit(i8_ty),
+            (a ^ b).fit(i8_ty),
+            (a + b).fit(i8_ty),
+            (a - b).fit(i8_ty),
+            (a * b).fit(i8_ty),
+        ];
+        for &x in xs {
+            for &y in ys {
+                let values = [
+                    x & y,
+                    x | y,
+                    x ^ y,
+                    x.wrapping_add(y),
+                    x.wrapping_sub(y),
+                    x.wrapping_mul(y),
+                ];
+                for (k, z) in results.iter().zip(values) {
+                    assert!(contains(k, z), "{} {} ∌ {}", a, b, z);
+                }
+            }
+            assert!(contains(&(!a), !x));
+            assert!(contains(&(-a).fit(i8_ty), x.wrapping_neg()));
+        }
+    });
+}
+
+#[test]
+fn test_shift_soundness() {
+    let i8_ty = IntType::new(8, true);
+    for_all(|&a, _, xs, _| {
+        for lo in 0..8 {
+            for hi in lo..8 {
+                let amounts = regular(lo as i128, hi as i128);
+                let shl = a.shl(&amounts).fit(i8_ty);
+                let sar = a.shr(&amounts, true).fit(i8_ty);
+                for &x in xs {
+                    for k in lo..=hi {
+                        assert!(contains(&shl, x << k), "{} << {} ∌ {}", a, k, x << k);
+                        assert!(contains(&sar, x >> k), "{} >> {} ∌ {}", a, k, x >> k);
+                    }
+                }
+            }
+        }
+    });
+    // Logical shifts, on the `u8` pattern.
+    let u8_ty = IntType::new(8, false);
+    for_all(|&a, _, xs, _| {
+        let a = a.fit(u8_ty);
+        for k in 0..8 {
+            let shr = a.shr_const(k, false);
+            for &x in xs {
+                let z = (x as u8 >> k) as u128;
+                assert!(z & shr.zeros == 0 && !z & shr.ones == 0);
+            }
+        }
+    });
+}
+
+#[test]
+fn test_lattice_soundness() {
+    for_all(|a, b, xs, ys| {
+        let join = a.join(b);
+        assert!(xs.iter().chain(ys).all(|&x| contains(&join, x)));
+        let meet = a.meet(b);
+        for x in i8::MIN..=i8::MAX {
+            assert_eq!(contains(&meet, x), contains(a, x) && contains(b, x));
+        }
+    });
+}
+
+#[test]
+fn test_range_conversions() {
+    // Every range of `i8` converts to bits holding all of its values, and
+    // back to a range holding all values of the bits.
+    for lo in (i8::MIN..=i8::MAX).step_by(7) {
+        for hi in (lo..=i8::MAX).step_by(5) {
+            let k = KnownBits::from_range(&regular(lo, hi));
+            assert!((lo..=hi).all(|x| contains(&k, x)));
+            let back: Range<i8> = k.to_range();
+            assert!(back.get_lower() <= lo && hi <= back.get_upper());
+        }
+    }
+    for_all(|a, _, xs, _| {
+        let range: Range<i8> = a.to_range();
+        assert!(xs
+            .iter()
+            .all(|&x| range.get_lower() <= x && x <= range.get_upper()));
+    });
+    // Unsigned carriers read the pattern as unsigned.
+    let k = KnownBits::from_range(&regular(0x40u8, 0x7F));
+    assert_eq!(k.to_range::<u8>(), regular(0x40, 0x7F));
+}
+
+#[test]
+fn test_masks() {
+    // `x & 0x0F` is in `[0, 15]` whatever `x` is, so `(x & 0x0F) < 16`.
+    let x = KnownBits::from_range(&Range::<i128>::default());
+    let masked = x & KnownBits::constant(0x0F);
+    assert_eq!(masked.to_range::<i128>(), regular(0, 15));
+    // Setting bits raises the lower bound.
+    let set = KnownBits::from_range(&regular(0i128, 255)) | KnownBits::constant(0x80);
+    assert_eq!(set.to_range::<i128>(), regular(128, 255));
+    // Shifting a byte left by 4 clears its low nibble.
+    let byte = KnownBits::from_range(&regular(0i128, 255));
+    let shifted = byte.shl_const(4);
+    assert_eq!(shifted.to_range::<i128>(), regular(0, 4080));
+    assert_eq!(shifted.zeros & 0x0F, 0x0F);
+    // And shifting it back right by 4 leaves the high nibble.
+    assert_eq!(
+        shifted.shr_const(8, false).to_range::<i128>(),
+        regular(0, 15)
+    );
+    assert_eq!(KnownBits::constant(12).as_constant(), Some(12));
+    assert_eq!(format!("{}", masked), "0b0????");
+}
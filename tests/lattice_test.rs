@@ -0,0 +1,175 @@
+#![feature(rustc_private)]
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use RAP_interval_demo::domain::lattice::Lattice;
+use RAP_interval_demo::domain::range::{Range, RangeType};
+
+const ITERATIONS: usize = 2000;
+
+/// Random ranges with bounds drawn from a small window around zero and the
+/// ends of `i32`, so that overlaps, extremes and equal bounds are common.
+fn random_bound(rng: &mut StdRng) -> i32 {
+    match rng.random_range(0..10) {
+        0 => i32::MIN,
+        1 => i32::MAX,
+        _ => rng.random_range(-20..=20),
+    }
+}
+
+fn random_range(rng: &mut StdRng) -> Range<i32> {
+    match rng.random_range(0..10) {
+        0 => Range::unknown(),
+        1 => Range::empty(),
+        _ => {
+            let a = random_bound(rng);
+            let b = random_bound(rng);
+            Range::new(a.min(b), a.max(b), RangeType::Regular)
+        }
+    }
+}
+
+fn for_all(check: impl Fn(Range<i32>, Range<i32>, Range<i32>)) {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    for _ in 0..ITERATIONS {
+        let a = random_range(&mut rng);
+        let b = random_range(&mut rng);
+        let c = random_range(&mut rng);
+        check(a, b, c);
+    }
+}
+
+#[test]
+fn test_bottom_and_top() {
+    for_all(|a, _, _| {
+        assert!(Range::bottom().is_subset(&a));
+        assert!(a.is_subset(&Range::top()));
+        assert_eq!(a.join(&Range::bottom()), a);
+        assert_eq!(a.meet(&Range::top()), a);
+    });
+}
+
+#[test]
+fn test_commutativity() {
+    for_all(|a, b, _| {
+        assert_eq!(a.join(&b), b.join(&a));
+        assert_eq!(a.meet(&b), b.meet(&a));
+    });
+}
+
+#[test]
+fn test_associativity() {
+    for_all(|a, b, c| {
+        assert_eq!(a.join(&b).join(&c), a.join(&b.join(&c)));
+        assert_eq!(a.meet(&b).meet(&c), a.meet(&b.meet(&c)));
+    });
+}
+
+#[test]
+fn test_idempotence() {
+    for_all(|a, _, _| {
+        assert_eq!(a.join(&a), a);
+        assert_eq!(a.meet(&a), a);
+    });
+}
+
+#[test]
+fn test_absorption() {
+    for_all(|a, b, _| {
+        assert_eq!(a.join(&a.meet(&b)), a);
+        assert_eq!(a.meet(&a.join(&b)), a);
+    });
+}
+
+#[test]
+fn test_order_agrees_with_join_and_meet() {
+    for_all(|a, b, _| {
+        assert_eq!(a.is_subset(&b), a.join(&b) == b);
+        assert_eq!(a.is_subset(&b), a.meet(&b) == a);
+    });
+}
+
+#[test]
+fn test_widen_is_upper_bound() {
+    for_all(|a, b, _| {
+        let w = a.widen(&b);
+        assert!(a.is_subset(&w), "{:?} widen {:?} = {:?}", a, b, w);
+        assert!(b.is_subset(&w), "{:?} widen {:?} = {:?}", a, b, w);
+    });
+}
+
+#[test]
+fn test_narrow_stays_between() {
+    for_all(|a, b, _| {
+        let b = a.meet(&b);
+        let n = a.narrow(&b);
+        assert!(b.is_subset(&n), "{:?} narrow {:?} = {:?}", a, b, n);
+        assert!(n.is_subset(&a), "{:?} narrow {:?} = {:?}", a, b, n);
+    });
+}
+
+#[test]
+fn test_widening_terminates() {
+    let mut rng = StdRng::seed_from_u64(0x5eed);
+    for _ in 0..ITERATIONS / 10 {
+        let mut current = Range::bottom();
+        let mut changes = 0;
+        for _ in 0..50 {
+            let next = current.widen(&random_range(&mut rng));
+            if next != current {
+                changes += 1;
+            }
+            current = next;
+        }
+        // Unknown -> Empty -> regular, then each bound jumps at most once.
+        assert!(changes <= 4, "widening changed {} times", changes);
+    }
+}
+
+#[test]
+fn test_meet_of_disjoint_ranges_is_empty() {
+    let a = Range::new(1, 3, RangeType::Regular);
+    let b = Range::new(5, 8, RangeType::Regular);
+    assert!(a.meet(&b).is_empty());
+    assert_eq!(a.join(&b), Range::new(1, 8, RangeType::Regular));
+}
+
+#[test]
+fn test_threshold_widening_stops_at_constants() {
+    // k = phi(0, k + 1) with `k < 100` on the loop edge: the constants of
+    // the loop are 0, 1 (the increment), 99 and 100 (the comparison).
+    let thresholds = [0, 1, 99, 100];
+    let guard = Range::new(i32::MIN, 99, RangeType::Regular);
+    let one = Range::new(1, 1, RangeType::Regular);
+    let mut k = Range::new(0, 0, RangeType::Regular);
+    loop {
+        let next = &k.meet(&guard) + &one;
+        let widened = k.widen_with_thresholds(&k.join(&next), &thresholds);
+        if widened == k {
+            break;
+        }
+        k = widened;
+    }
+    assert_eq!(k, Range::new(0, 100, RangeType::Regular));
+}
+
+#[test]
+fn test_threshold_widening_falls_back_to_full_range() {
+    let thresholds = [-10, 10];
+    let a = Range::new(0, 5, RangeType::Regular);
+    let grown = Range::new(-3, 20, RangeType::Regular);
+    assert_eq!(
+        a.widen_with_thresholds(&grown, &thresholds),
+        Range::new(-10, i32::MAX, RangeType::Regular)
+    );
+    assert_eq!(a.widen_with_thresholds(&grown, &[]), a.widen(&grown));
+}
+
+#[test]
+fn test_threshold_widening_is_upper_bound() {
+    let thresholds = [-7, -1, 0, 3, 15];
+    for_all(|a, b, _| {
+        let w = a.widen_with_thresholds(&b, &thresholds);
+        assert!(a.is_subset(&w) && b.is_subset(&w));
+    });
+}
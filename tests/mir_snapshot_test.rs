@@ -0,0 +1,157 @@
+#![feature(rustc_private)]
+extern crate rustc_hir;
+
+mod common;
+
+use common::with_tcx_of_file;
+use rustc_hir::def::DefKind;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use RAP_interval_demo::SSA::PassRunner::PassRunner;
+
+const STAGES: [&str; 3] = ["phi", "essa", "renamed"];
+const SNAPSHOT_DIR: &str = "tests/snapshots";
+
+/// Every `tests/*.rs` that is a program rather than a test is a fixture.
+fn fixtures() -> Vec<PathBuf> {
+    let mut fixtures: Vec<PathBuf> = fs::read_dir("tests")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "rs"))
+        .filter(|path| {
+            let source = fs::read_to_string(path).unwrap();
+            source.contains("fn main") && !source.contains("#[test]")
+        })
+        .collect();
+    fixtures.sort();
+    fixtures
+}
+
+/// The MIR of every function of `path` after each e-SSA stage.
+fn run_fixture(path: &Path) -> BTreeMap<String, String> {
+    with_tcx_of_file(path, |tcx| {
+        let mut def_ids: Vec<_> = tcx
+            .hir()
+            .body_owners()
+            .filter(|def_id| matches!(tcx.def_kind(*def_id), DefKind::Fn | DefKind::AssocFn))
+            .collect();
+        def_ids.sort_by_key(|def_id| tcx.def_path_str(*def_id));
+        let mut outputs: BTreeMap<String, String> = BTreeMap::new();
+        for def_id in def_ids {
+            let mut body = tcx.optimized_mir(def_id).clone();
+            let passrunner = PassRunner::new(tcx);
+            passrunner.run_pass_with_stages(&mut body, |stage, body| {
+                let text = passrunner.get_final_ssa_as_string(body);
+                outputs
+                    .entry(stage.to_string())
+                    .or_default()
+                    .push_str(&text);
+            });
+        }
+        outputs
+    })
+}
+
+/// Replaces the parts of a MIR dump that change between compilers and
+/// machines: crate disambiguators such as `[7f36]` and DefId indices.
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(['[', 'D']) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(len) = disambiguator_len(rest) {
+            out.push_str("[HASH]");
+            rest = &rest[len..];
+        } else if let Some(len) = def_id_index_len(rest) {
+            out.push_str("DefId(N:N");
+            rest = &rest[len..];
+        } else {
+            out.push_str(&rest[..1]);
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Length of a `[hex]` crate disambiguator at the start of `text`.
+fn disambiguator_len(text: &str) -> Option<usize> {
+    let close = text.find(']')?;
+    let inner = &text[1..close];
+    let is_hash = (4..=16).contains(&inner.len())
+        && inner
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase());
+    is_hash.then_some(close + 1)
+}
+
+/// Length of a `DefId(krate:index` prefix at the start of `text`.
+fn def_id_index_len(text: &str) -> Option<usize> {
+    let digits = text.strip_prefix("DefId(")?;
+    let krate = digits.find(|c: char| !c.is_ascii_digit())?;
+    let index = digits[krate..].strip_prefix(':')?;
+    let index_len = index.find(|c: char| !c.is_ascii_digit())?;
+    (krate > 0 && index_len > 0).then_some("DefId(".len() + krate + 1 + index_len)
+}
+
+fn snapshot_path(fixture: &Path, stage: &str) -> PathBuf {
+    let name = fixture.file_stem().unwrap().to_string_lossy();
+    Path::new(SNAPSHOT_DIR).join(format!("{}.{}.mir", name, stage))
+}
+
+fn first_difference(expected: &str, actual: &str) -> String {
+    for (line, (e, a)) in expected.lines().zip(actual.lines()).enumerate() {
+        if e != a {
+            return format!("line {}:\n  expected: {}\n  actual:   {}", line + 1, e, a);
+        }
+    }
+    format!(
+        "expected {} lines, got {}",
+        expected.lines().count(),
+        actual.lines().count()
+    )
+}
+
+#[test]
+fn essa_snapshots() {
+    let bless = std::env::var("BLESS").map_or(false, |v| v == "1");
+    let mut failures = Vec::new();
+    for fixture in fixtures() {
+        let outputs = run_fixture(&fixture);
+        for stage in STAGES {
+            let actual = normalize(outputs.get(stage).map_or("", |s| s.as_str()));
+            let path = snapshot_path(&fixture, stage);
+            if bless {
+                fs::create_dir_all(SNAPSHOT_DIR).unwrap();
+                fs::write(&path, &actual).unwrap();
+                continue;
+            }
+            match fs::read_to_string(&path) {
+                Ok(expected) if expected == actual => {}
+                Ok(expected) => failures.push(format!(
+                    "{} differs, {}",
+                    path.display(),
+                    first_difference(&expected, &actual)
+                )),
+                Err(_) => failures.push(format!("{} is missing", path.display())),
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\nrerun with BLESS=1 to update the snapshots",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn normalize_strips_unstable_ids() {
+    assert_eq!(
+        normalize("mir_DefId(0:3 ~ test1[7f36]::main)"),
+        "mir_DefId(N:N ~ test1[HASH]::main)"
+    );
+    assert_eq!(normalize("_2 = [copy _1; 4]"), "_2 = [copy _1; 4]");
+    assert_eq!(normalize("Drop([0, 1])"), "Drop([0, 1])");
+}
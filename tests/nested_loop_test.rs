@@ -0,0 +1,74 @@
+#![feature(box_patterns)]
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+mod common;
+
+use common::with_mir;
+use rustc_middle::mir::{BinOp, Location, Operand, Rvalue, StatementKind, VarDebugInfoContents};
+use std::fs;
+use RAP_interval_demo::domain::range::{Range, RangeType};
+use RAP_interval_demo::domain::ConstraintGraph::ConstraintGraph;
+use RAP_interval_demo::SSA::PassRunner::PassRunner;
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// For every checked addition in `main` of `tests/test1.rs`, the name of the
+/// variable it increments and the range of that variable where it is
+/// incremented.
+fn increments() -> Vec<(String, Option<Range<i128>>)> {
+    let source = fs::read_to_string("tests/test1.rs").unwrap();
+    with_mir(&source, "main", |tcx, body| {
+        let mut body = body.clone();
+        let ssa_origin = PassRunner::new(tcx).run_pass(&mut body);
+        let mut cg: ConstraintGraph<i128> = ConstraintGraph::new(tcx, &body);
+        cg.build_graph();
+        cg.find_intervals();
+        let results = cg.results(ssa_origin.clone());
+
+        let mut increments = Vec::new();
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            for (index, stmt) in data.statements.iter().enumerate() {
+                let StatementKind::Assign(box (
+                    _,
+                    Rvalue::BinaryOp(BinOp::AddWithOverflow, box (Operand::Copy(lhs), _)),
+                )) = &stmt.kind
+                else {
+                    continue;
+                };
+                let origin = *ssa_origin.get(&lhs.local).unwrap_or(&lhs.local);
+                let name = body
+                    .var_debug_info
+                    .iter()
+                    .find_map(|info| match info.value {
+                        VarDebugInfoContents::Place(place) if place.local == origin => {
+                            Some(info.name.to_string())
+                        }
+                        _ => None,
+                    })
+                    .unwrap();
+                let location = Location {
+                    block,
+                    statement_index: index,
+                };
+                increments.push((name, results.range_at(origin, location).cloned()));
+            }
+        }
+        increments
+    })
+}
+
+#[test]
+fn test_nested_loop_of_test1() {
+    // `i < j` with `j` at most `k`, itself below 100, bounds `i` by 98 in
+    // the inner loop once the bound of `j` is resolved.
+    assert_eq!(
+        increments(),
+        vec![
+            (String::from("i"), Some(regular(0, 98))),
+            (String::from("k"), Some(regular(0, 99))),
+        ]
+    );
+}
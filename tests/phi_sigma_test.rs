@@ -0,0 +1,65 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::{BinOp, Local, Place};
+use RAP_interval_demo::domain::domain::{
+    BasicInterval, BasicOpKind, IntervalType, PhiOp, SigmaOp, SymbInterval, VarNode, VarNodes,
+};
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+
+const I32: IntType = IntType::new(32, true);
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+fn node(local: usize, range: Range<i128>) -> VarNode<'static, i128> {
+    let mut node = VarNode::new(Place::from(Local::from_usize(local)), Some(I32));
+    node.set_range(range);
+    node
+}
+
+#[test]
+fn test_phi_joins_its_sources() {
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let a = vars.insert(node(1, regular(0, 3)));
+    let b = vars.insert(node(2, regular(10, 12)));
+    // Not evaluated yet, as the value coming round a loop.
+    let c = vars.insert(node(3, Range::unknown()));
+    let sink = vars.insert(node(4, Range::unknown()));
+    let phi = BasicOpKind::Phi(PhiOp::new(sink, vec![a, b, c]));
+    assert_eq!(phi.eval(&vars), regular(0, 12));
+    assert_eq!(phi.get_sources(), vec![a, b, c]);
+    assert_eq!(phi.to_string(), "v3 = phi(v0, v1, v2) ∩ ⊤");
+
+    vars.insert(node(1, Range::unknown()));
+    vars.insert(node(2, Range::unknown()));
+    assert!(phi.eval(&vars).is_unknown());
+}
+
+#[test]
+fn test_sigma_meets_its_branch_interval() {
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let x = vars.insert(node(1, regular(-5, 100)));
+    let n = vars.insert(node(2, Range::unknown()));
+    let sink = vars.insert(node(3, Range::unknown()));
+
+    // The true edge of `x < 10`.
+    let below = IntervalType::Basic(BasicInterval::new(regular(i128::MIN, 9)));
+    let sigma = BasicOpKind::Sigma(SigmaOp::new(sink, x, below));
+    assert_eq!(sigma.eval(&vars), regular(-5, 9));
+    assert_eq!(
+        sigma.to_string(),
+        format!("v2 = sigma(v0) ∩ [{}, 9]", i128::MIN)
+    );
+
+    // The true edge of `x < n`, before and after future resolution.
+    let symb = IntervalType::Symb(SymbInterval::new(Range::default(), n, BinOp::Lt));
+    let mut sigma = BasicOpKind::Sigma(SigmaOp::new(sink, x, symb));
+    assert_eq!(sigma.eval(&vars), regular(-5, 100));
+    assert_eq!(sigma.get_sources(), vec![x]);
+    assert_eq!(sigma.to_string(), "v2 = sigma(v0) ∩ Lt(v1) ∩ ⊤");
+    assert_eq!(sigma.get_intersect().label(&vars), "Lt(_2) ∩ ⊤");
+    sigma.get_intersect_mut().set_range(regular(i128::MIN, 19));
+    assert_eq!(sigma.eval(&vars), regular(-5, 19));
+}
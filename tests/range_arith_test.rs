@@ -0,0 +1,170 @@
+#![feature(rustc_private)]
+
+#[macro_use]
+mod common;
+
+use RAP_interval_demo::domain::range::{Range, RangeType};
+
+fn regular<T: num_traits::PrimInt>(lb: T, ub: T) -> Range<T> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+fn bounds<T: num_traits::PrimInt>(range: &Range<T>) -> (T, T) {
+    assert!(range.is_regular(), "expected a regular range");
+    (range.get_lower(), range.get_upper())
+}
+
+#[test]
+fn test_add_sub_mul() {
+    assert_eq!(bounds(&(&regular(1, 10) + &regular(2, 3))), (3, 13));
+    assert_eq!(bounds(&(&regular(1, 10) - &regular(2, 3))), (-2, 8));
+    assert_eq!(bounds(&(&regular(-2, 3) * &regular(-4, 5))), (-12, 15));
+}
+
+#[test]
+fn test_overflow_widens_to_full_range() {
+    assert_eq!(
+        bounds(&(&regular(100i8, 120) + &regular(10, 10))),
+        (i8::MIN, i8::MAX)
+    );
+    assert_eq!(
+        bounds(&(&regular(0u8, 5) - &regular(1, 1))),
+        (u8::MIN, u8::MAX)
+    );
+    assert_eq!(
+        bounds(&(&regular(i8::MIN, 0) / &regular(-1, -1))),
+        (i8::MIN, i8::MAX)
+    );
+}
+
+#[test]
+fn test_division_by_range_containing_zero() {
+    assert_eq!(bounds(&(&regular(10, 100) / &regular(0, 10))), (1, 100));
+    assert_eq!(bounds(&(&regular(10, 100) / &regular(-2, 5))), (-100, 100));
+    assert!((&regular(10, 100) / &regular(0, 0)).is_empty());
+    assert!((&regular(10, 100) % &regular(0, 0)).is_empty());
+    assert_eq!(bounds(&(&regular(0, 100) % &regular(0, 8))), (0, 7));
+    assert_eq!(bounds(&(&regular(3, 5) % &regular(8, 8))), (3, 5));
+}
+
+#[test]
+fn test_masks_and_shifts() {
+    assert_eq!(
+        bounds(&(&regular(0u32, 1000) & &regular(0x0F, 0x0F))),
+        (0, 15)
+    );
+    assert_eq!(bounds(&(&regular(1u32, 4) << &regular(2, 2))), (4, 16));
+    assert_eq!(bounds(&(&regular(-64i32, 64) >> &regular(1, 3))), (-32, 32));
+    assert!((&regular(1u8, 4) << &regular(8, 9)).is_empty());
+}
+
+#[test]
+fn test_unknown_and_empty_propagation() {
+    let unknown = Range::<i32>::unknown();
+    let empty = Range::<i32>::empty();
+    assert!((&unknown + &regular(1, 2)).is_unknown());
+    assert!((&empty * &regular(1, 2)).is_empty());
+    assert!((&unknown - &empty).is_unknown());
+    assert!((-&empty).is_empty());
+    assert!((!&unknown).is_unknown());
+}
+
+/// Every concrete result of an operation, evaluated with Rust's wrapping
+/// semantics, must lie in the abstract result. Operations that panic
+/// (division by zero, out-of-range shifts) have no result to check.
+macro_rules! exhaustive_tests {
+    ($name:ident, $t:ty) => {
+        mod $name {
+            use super::regular;
+            use RAP_interval_demo::domain::range::Range;
+
+            type T = $t;
+
+            fn values(range: &Range<T>) -> std::ops::RangeInclusive<T> {
+                range.get_lower()..=range.get_upper()
+            }
+
+            fn ranges() -> Vec<Range<T>> {
+                let mut ranges = Vec::new();
+                for lb in (T::MIN..=T::MAX).step_by(7) {
+                    for width in [0, 3, 12] {
+                        ranges.push(regular(lb, lb.saturating_add(width)));
+                    }
+                }
+                let zero: T = 0;
+                ranges.push(regular(T::MIN, T::MAX));
+                ranges.push(regular(T::MIN, T::MIN));
+                ranges.push(regular(T::MAX, T::MAX));
+                ranges.push(regular(zero, T::MAX));
+                ranges.push(regular(T::MIN, zero.saturating_add(5)));
+                ranges.push(regular(zero.saturating_sub(1), 1));
+                ranges.push(regular(zero.saturating_sub(1), zero.saturating_sub(1)));
+                ranges
+            }
+
+            fn check_binary(
+                name: &str,
+                abstract_op: fn(&Range<T>, &Range<T>) -> Range<T>,
+                concrete_op: fn(T, T) -> Option<T>,
+            ) {
+                let ranges = ranges();
+                for a in &ranges {
+                    for b in &ranges {
+                        let result = abstract_op(a, b);
+                        for x in values(a) {
+                            for y in values(b) {
+                                let Some(z) = concrete_op(x, y) else {
+                                    continue;
+                                };
+                                assert!(
+                                    result.is_regular()
+                                        && result.get_lower() <= z
+                                        && z <= result.get_upper(),
+                                    "{} {:?} {:?} = {:?} does not contain {} {} {} = {}",
+                                    name,
+                                    a.range,
+                                    b.range,
+                                    result,
+                                    x,
+                                    name,
+                                    y,
+                                    z
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+
+            fn check_unary(
+                name: &str,
+                abstract_op: fn(&Range<T>, &Range<T>) -> Range<T>,
+                concrete_op: fn(T, T) -> Option<T>,
+            ) {
+                for a in &ranges() {
+                    let result = abstract_op(a, a);
+                    for x in values(a) {
+                        let Some(z) = concrete_op(x, x) else {
+                            continue;
+                        };
+                        assert!(
+                            result.is_regular()
+                                && result.get_lower() <= z
+                                && z <= result.get_upper(),
+                            "{} {:?} = {:?} does not contain {}",
+                            name,
+                            a.range,
+                            result,
+                            z
+                        );
+                    }
+                }
+            }
+
+            range_op_tests!(check_binary, check_unary);
+        }
+    };
+}
+
+exhaustive_tests!(exhaustive_i8, i8);
+exhaustive_tests!(exhaustive_u8, u8);
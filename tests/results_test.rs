@@ -0,0 +1,127 @@
+#![feature(box_patterns)]
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+mod common;
+
+use common::with_mir;
+use rustc_middle::mir::{
+    BinOp, Location, Rvalue, StatementKind, TerminatorKind, VarDebugInfoContents, RETURN_PLACE,
+};
+use RAP_interval_demo::domain::range::{Range, RangeType};
+use RAP_interval_demo::domain::ConstraintGraph::ConstraintGraph;
+use RAP_interval_demo::SSA::PassRunner::PassRunner;
+
+const SOURCE: &str = "
+fn count() -> i32 {
+    let mut i = 0;
+    while i < 10 {
+        i += 1;
+    }
+    i
+}
+
+fn main() {
+    count();
+}
+";
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// The answers to the queries on `count`, gathered while the compiler runs.
+#[derive(Default)]
+struct Answers {
+    /// `i` and the return place when `count` returns.
+    returned: Vec<Option<Range<i128>>>,
+    /// The operands of `i < 10`.
+    compared: Vec<Option<Range<i128>>>,
+    /// Places whose range differs from the one read right after their
+    /// assignment, and blocks whose ranges miss some of those places.
+    inconsistent: Vec<String>,
+}
+
+fn analyze(source: &str) -> Answers {
+    with_mir(source, "count", |tcx, body| {
+        let mut answers = Answers::default();
+        let mut body = body.clone();
+        let ssa_origin = PassRunner::new(tcx).run_pass(&mut body);
+        let mut cg: ConstraintGraph<i128> = ConstraintGraph::new(tcx, &body);
+        cg.build_graph();
+        cg.find_intervals();
+        let results = cg.results(ssa_origin);
+
+        let i = body
+            .var_debug_info
+            .iter()
+            .find_map(|info| match info.value {
+                VarDebugInfoContents::Place(place) if info.name.as_str() == "i" => {
+                    Some(place.local)
+                }
+                _ => None,
+            })
+            .unwrap();
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            if let TerminatorKind::Return = data.terminator().kind {
+                let location = body.terminator_loc(block);
+                for local in [i, RETURN_PLACE] {
+                    answers
+                        .returned
+                        .push(results.range_at(local, location).cloned());
+                }
+            }
+            let mut assigned = Vec::new();
+            for (index, stmt) in data.statements.iter().enumerate() {
+                let location = Location {
+                    block,
+                    statement_index: index,
+                };
+                let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                    continue;
+                };
+                if let Rvalue::BinaryOp(BinOp::Lt, box (a, b)) = rvalue {
+                    for operand in [a, b] {
+                        answers
+                            .compared
+                            .push(results.range_of_operand(operand, location));
+                    }
+                }
+                let Some(range) = results.range_of(place) else {
+                    continue;
+                };
+                assigned.push((place, range));
+                let after = location.successor_within_block();
+                if place.projection.is_empty()
+                    && results.range_at(place.local, after) != Some(range)
+                {
+                    answers.inconsistent.push(format!("{:?}", place));
+                }
+            }
+            if results.ranges_in_block(block) != assigned {
+                answers.inconsistent.push(format!("{:?}", block));
+            }
+        }
+        answers
+    })
+}
+
+#[test]
+fn test_queries_on_a_counting_loop() {
+    let answers = analyze(SOURCE);
+    // Past the loop, the exit sigma of `i` reaches the return.
+    assert_eq!(
+        answers.returned,
+        vec![Some(regular(10, 10)), Some(regular(10, 10))]
+    );
+    // The loop head reads the phi of `i`, and the bound is a constant.
+    assert_eq!(
+        answers.compared,
+        vec![Some(regular(0, 10)), Some(regular(10, 10))]
+    );
+    assert!(
+        answers.inconsistent.is_empty(),
+        "{:?}",
+        answers.inconsistent
+    );
+}
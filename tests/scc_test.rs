@@ -0,0 +1,99 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use rustc_middle::mir::{BinOp, Local, Place};
+use std::collections::HashSet;
+use RAP_interval_demo::domain::domain::{
+    BasicOpKind, BinaryOp, GenOprs, IntervalType, OpSource, PhiOp, SigmaOp, SymbInterval, SymbMap,
+    UnaryOp, UnaryOpKind, UseMap, VarId, VarNode, VarNodes,
+};
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+use RAP_interval_demo::domain::ConstraintGraph::Nuutila;
+
+const I32: IntType = IntType::new(32, true);
+
+fn place(local: usize) -> Place<'static> {
+    Place::from(Local::from_usize(local))
+}
+
+/// Indexes the components by the local of each variable.
+fn component_of(vars: &VarNodes<i128>, components: &[HashSet<VarId>], local: usize) -> usize {
+    components
+        .iter()
+        .position(|c| c.iter().any(|&id| vars.place(id).local.as_usize() == local))
+        .unwrap()
+}
+
+#[test]
+fn test_loop_is_one_component_in_topological_order() {
+    // _2 = phi(_1, _4); _3 = _2 + 1; _4 = sigma(_3) ∩ (_4 < _5); _6 = _2.
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    for i in 1..7 {
+        vars.insert(VarNode::new(place(i), Some(I32)));
+    }
+    let p = |i: usize| vars.id_of(&place(i)).unwrap();
+    let one = Range::new(1, 1, RangeType::Regular);
+    let bound = IntervalType::Symb(SymbInterval::new(Range::default(), p(5), BinOp::Lt));
+    let oprs: GenOprs<i128> = GenOprs::from_raw(vec![
+        BasicOpKind::Phi(PhiOp::new(p(2), vec![p(1), p(4)])),
+        BasicOpKind::Binary(BinaryOp::new(
+            p(3),
+            p(2),
+            OpSource::Const(one),
+            BinOp::Add,
+            I32,
+        )),
+        BasicOpKind::Sigma(SigmaOp::new(p(4), p(3), bound)),
+        BasicOpKind::Unary(UnaryOp::new(p(6), p(2), UnaryOpKind::Copy)),
+    ]);
+    let mut usemap = UseMap::new();
+    let mut symbmap = SymbMap::new();
+    for (id, op) in oprs.iter_enumerated() {
+        for source in op.get_sources() {
+            usemap.entry(source).or_default().insert(id);
+        }
+        if let IntervalType::Symb(symb) = op.get_intersect() {
+            symbmap.entry(symb.get_bound()).or_default().insert(id);
+        }
+    }
+
+    let components = Nuutila::new(&vars, &oprs, &usemap, &symbmap).into_components();
+    assert_eq!(components.len(), 4);
+    let component_of = |local| component_of(&vars, &components, local);
+    let cycle = component_of(2);
+    assert_eq!(component_of(3), cycle);
+    assert_eq!(component_of(4), cycle);
+    assert_eq!(components[cycle].len(), 3);
+    // The inputs of the loop, including its symbolic bound, come before it,
+    // and its users after.
+    assert!(component_of(1) < cycle);
+    assert!(component_of(5) < cycle);
+    assert!(cycle < component_of(6));
+}
+
+#[test]
+fn test_long_chain_does_not_overflow() {
+    // _{i+1} = _i for a chain far deeper than the call stack could recurse.
+    const LEN: usize = 200_000;
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let ids: Vec<VarId> = (0..=LEN)
+        .map(|i| vars.insert(VarNode::new(place(i), Some(I32))))
+        .collect();
+    let mut oprs: GenOprs<i128> = GenOprs::new();
+    let mut usemap = UseMap::new();
+    for i in 0..LEN {
+        usemap.entry(ids[i]).or_default().insert(oprs.next_index());
+        oprs.push(BasicOpKind::Unary(UnaryOp::new(
+            ids[i + 1],
+            ids[i],
+            UnaryOpKind::Copy,
+        )));
+    }
+
+    let components = Nuutila::new(&vars, &oprs, &usemap, &SymbMap::new()).into_components();
+    assert_eq!(components.len(), LEN + 1);
+    for (i, component) in components.iter().enumerate() {
+        assert!(component.contains(&ids[i]));
+    }
+}
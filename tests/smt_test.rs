@@ -0,0 +1,98 @@
+#![cfg(feature = "smt")]
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::{BinOp, Local, Place};
+use std::time::Duration;
+use z3::{Config, Context};
+use RAP_interval_demo::domain::domain::{
+    BasicInterval, BasicOpKind, GenOprs, IntervalType, SymbInterval, UnaryOp, UnaryOpKind, VarNode,
+    VarNodes,
+};
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+use RAP_interval_demo::domain::smt::{BoundCheck, SmtChecker};
+
+const I8: IntType = IntType::new(8, true);
+const U8: IntType = IntType::new(8, false);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+fn node(local: usize, ty: IntType, range: Range<i128>) -> VarNode<'static, i128> {
+    let mut node = VarNode::new(Place::from(Local::from_usize(local)), Some(ty));
+    node.set_range(range);
+    node
+}
+
+#[test]
+fn test_cast_bounds() {
+    // `y = x as u8` with `x: i8` in `[-1, 3]`: `y` is 255 or in `[0, 3]`.
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let x = vars.insert(node(1, I8, regular(-1, 3)));
+    let y = vars.insert(node(2, U8, regular(0, 255)));
+    let mut oprs: GenOprs<i128> = GenOprs::new();
+    oprs.push(BasicOpKind::Unary(UnaryOp::new(
+        y,
+        x,
+        UnaryOpKind::Cast(U8),
+    )));
+
+    let ctx = Context::new(&Config::new());
+    let checker = SmtChecker::new(&ctx, &vars, &oprs, TIMEOUT);
+    // Both bounds are reached.
+    let check = checker.check(y, &regular(0, 255));
+    assert_eq!(check.lower, BoundCheck::Exact);
+    assert_eq!(check.upper, BoundCheck::Exact);
+    assert!(check.is_sound());
+    // A range without 255 is wrong.
+    let check = checker.check(y, &regular(0, 100));
+    assert_eq!(check.lower, BoundCheck::Exact);
+    assert_eq!(check.upper, BoundCheck::Disproved(255));
+    assert!(!check.is_sound());
+    assert_eq!(check.refined_range(), regular(0, 100));
+}
+
+#[test]
+fn test_tighten_with_intersections() {
+    // `y = (x as u8) ∩ [0, 10]` and `z = y as i8 ∩ (z < w)`, `w` in `[0, 5]`.
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let x = vars.insert(node(1, I8, regular(-128, 127)));
+    let y = vars.insert(node(2, U8, regular(0, 255)));
+    let z = vars.insert(node(3, I8, regular(-128, 127)));
+    let w = vars.insert(node(4, I8, regular(0, 5)));
+    let mut to_y = UnaryOp::new(y, x, UnaryOpKind::Cast(U8));
+    to_y.intersect = IntervalType::Basic(BasicInterval::new(regular(0, 10)));
+    let mut to_z = UnaryOp::new(z, y, UnaryOpKind::Cast(I8));
+    to_z.intersect = IntervalType::Symb(SymbInterval::new(Range::default(), w, BinOp::Lt));
+    let oprs: GenOprs<i128> =
+        GenOprs::from_raw(vec![BasicOpKind::Unary(to_y), BasicOpKind::Unary(to_z)]);
+
+    let ctx = Context::new(&Config::new());
+    let checker = SmtChecker::new(&ctx, &vars, &oprs, TIMEOUT);
+    let check = checker.check(y, &regular(0, 255));
+    assert_eq!(check.upper, BoundCheck::Tightened(10));
+    assert_eq!(check.refined_range(), regular(0, 10));
+    let check = checker.check(z, &regular(-128, 127));
+    assert_eq!(check.lower, BoundCheck::Tightened(0));
+    assert_eq!(check.upper, BoundCheck::Tightened(4));
+    assert_eq!(check.refined_range(), regular(0, 4));
+}
+
+#[test]
+fn test_unreachable() {
+    // `y = x as u8 ∩ [200, 255]` with `x` in `[0, 100]` has no solution.
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let x = vars.insert(node(1, I8, regular(0, 100)));
+    let y = vars.insert(node(2, U8, regular(0, 255)));
+    let mut op = UnaryOp::new(y, x, UnaryOpKind::Cast(U8));
+    op.intersect = IntervalType::Basic(BasicInterval::new(regular(200, 255)));
+    let oprs: GenOprs<i128> = GenOprs::from_raw(vec![BasicOpKind::Unary(op)]);
+
+    let ctx = Context::new(&Config::new());
+    let checker = SmtChecker::new(&ctx, &vars, &oprs, TIMEOUT);
+    let check = checker.check(y, &regular(0, 255));
+    assert_eq!(check.lower, BoundCheck::Unreachable);
+    assert!(check.refined_range().is_empty());
+}
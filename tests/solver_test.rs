@@ -0,0 +1,225 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::{BinOp, Local, Place};
+use RAP_interval_demo::domain::domain::{
+    BasicInterval, BasicOpKind, BinaryOp, DefMap, GenOprs, IntervalType, OpSource, PhiOp, SigmaOp,
+    SymbInterval, SymbMap, UnaryOp, UnaryOpKind, UseMap, VarId, VarNode, VarNodes,
+};
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+use RAP_interval_demo::domain::ConstraintGraph::{Nuutila, Solver};
+
+const I32: IntType = IntType::new(32, true);
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// The variable of `_i`, as `solve` numbers them.
+fn p(i: usize) -> VarId {
+    VarId::from_usize(i)
+}
+
+/// The ranges of `_1` to `_{len-1}` once `oprs` are solved, and the number
+/// of widening steps it took.
+fn solve(len: usize, oprs: GenOprs<i128>, thresholds: &[i128]) -> (Vec<Range<i128>>, usize) {
+    let mut oprs = oprs;
+    let mut defmap = DefMap::new();
+    let mut usemap = UseMap::new();
+    let mut symbmap = SymbMap::new();
+    for (id, op) in oprs.iter_enumerated() {
+        defmap.insert(op.get_sink(), id);
+        for source in op.get_sources() {
+            usemap.entry(source).or_default().insert(id);
+        }
+        if let IntervalType::Symb(symb) = op.get_intersect() {
+            symbmap.entry(symb.get_bound()).or_default().insert(id);
+        }
+    }
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    for i in 0..len {
+        let mut node = VarNode::new(Place::from(Local::from_usize(i)), Some(I32));
+        node.init(!defmap.contains_key(&p(i)));
+        vars.insert(node);
+    }
+    let components = Nuutila::new(&vars, &oprs, &usemap, &symbmap).into_components();
+    let mut steps = 0;
+    for component in &components {
+        let mut solver = Solver::new(&mut vars, &mut oprs, &defmap, &usemap, &symbmap, thresholds);
+        solver.solve(component);
+        steps += solver.widening_steps;
+    }
+    let ranges = (1..len).map(|i| vars[p(i)].get_range().clone()).collect();
+    (ranges, steps)
+}
+
+/// `_1 = 0; loop { _2 = phi(_1, _4); if _2 < bound { _3 = sigma(_2);
+/// _4 = _3 + step } else { _5 = sigma(_2); break } }`.
+fn counting_loop(taken: IntervalType<i128>, exit: IntervalType<i128>, step: i128) -> GenOprs<i128> {
+    GenOprs::from_raw(vec![
+        BasicOpKind::Unary(UnaryOp::new(
+            p(1),
+            OpSource::Const(regular(0, 0)),
+            UnaryOpKind::Copy,
+        )),
+        BasicOpKind::Phi(PhiOp::new(p(2), vec![p(1), p(4)])),
+        BasicOpKind::Sigma(SigmaOp::new(p(3), p(2), taken)),
+        BasicOpKind::Binary(BinaryOp::new(
+            p(4),
+            p(3),
+            OpSource::Const(regular(step, step)),
+            BinOp::Add,
+            I32,
+        )),
+        BasicOpKind::Sigma(SigmaOp::new(p(5), p(2), exit)),
+    ])
+}
+
+#[test]
+fn test_loop_with_constant_bound() {
+    // `while i < 10 { i += 1 }`.
+    let max = i32::MAX as i128;
+    let min = i32::MIN as i128;
+    let oprs = || {
+        counting_loop(
+            IntervalType::Basic(BasicInterval::new(regular(i128::MIN, 9))),
+            IntervalType::Basic(BasicInterval::new(regular(10, i128::MAX))),
+            1,
+        )
+    };
+    let expected = vec![
+        regular(0, 0),
+        regular(0, 10),
+        regular(0, 9),
+        regular(1, 10),
+        regular(10, 10),
+    ];
+    // Plain widening throws the bounds to the ends of `i32` and narrowing
+    // brings them back; jumping to the constant needs no narrowing.
+    let (ranges, _) = solve(6, oprs(), &[]);
+    assert_eq!(ranges, expected);
+    let (ranges, _) = solve(6, oprs(), &[min, 9, 10, max]);
+    assert_eq!(ranges, expected);
+}
+
+#[test]
+fn test_loop_with_stride() {
+    // `while i < 10 { i += 4 }`: the congruence `4ℤ` of the loop moves the
+    // bounds to its values, and leaves a single value on exit.
+    let oprs = counting_loop(
+        IntervalType::Basic(BasicInterval::new(regular(i128::MIN, 9))),
+        IntervalType::Basic(BasicInterval::new(regular(10, i128::MAX))),
+        4,
+    );
+    let (ranges, _) = solve(6, oprs, &[]);
+    assert_eq!(
+        ranges,
+        vec![
+            regular(0, 0),
+            regular(0, 12),
+            regular(0, 8),
+            regular(4, 12),
+            regular(12, 12),
+        ]
+    );
+}
+
+#[test]
+fn test_stride_survives_wrapping() {
+    // `loop { i += step }` wraps around `i32`: a stride of 4 divides 2^32
+    // and still holds, one of 3 does not.
+    let oprs = |step| -> GenOprs<i128> {
+        GenOprs::from_raw(vec![
+            BasicOpKind::Unary(UnaryOp::new(
+                p(1),
+                OpSource::Const(regular(0, 0)),
+                UnaryOpKind::Copy,
+            )),
+            BasicOpKind::Phi(PhiOp::new(p(2), vec![p(1), p(3)])),
+            BasicOpKind::Binary(BinaryOp::new(
+                p(3),
+                p(2),
+                OpSource::Const(regular(step, step)),
+                BinOp::Add,
+                I32,
+            )),
+        ])
+    };
+    let (min, max) = (i32::MIN as i128, i32::MAX as i128);
+    let (ranges, _) = solve(4, oprs(4), &[]);
+    assert_eq!(
+        ranges,
+        vec![regular(0, 0), regular(min, max - 3), regular(min, max - 3)]
+    );
+    let (ranges, _) = solve(4, oprs(3), &[]);
+    assert_eq!(
+        ranges,
+        vec![regular(0, 0), regular(min, max), regular(min, max)]
+    );
+}
+
+#[test]
+fn test_loop_with_symbolic_bound() {
+    // `_6 = 100; while i < _6 { i += 1 }`: the bound is solved first, and
+    // the sigmas of the loop see its range.
+    let mut oprs = counting_loop(
+        IntervalType::Symb(SymbInterval::new(Range::default(), p(6), BinOp::Lt)),
+        IntervalType::Symb(SymbInterval::new(Range::default(), p(6), BinOp::Ge)),
+        1,
+    );
+    oprs.push(BasicOpKind::Unary(UnaryOp::new(
+        p(6),
+        OpSource::Const(regular(100, 100)),
+        UnaryOpKind::Copy,
+    )));
+    let (ranges, _) = solve(7, oprs, &[]);
+    assert_eq!(
+        ranges,
+        vec![
+            regular(0, 0),
+            regular(0, 100),
+            regular(0, 99),
+            regular(1, 100),
+            regular(100, 100),
+            regular(100, 100),
+        ]
+    );
+}
+
+#[test]
+fn test_unbounded_loop_terminates() {
+    // `_1 = 0; loop { _2 = phi(_1, _3); _3 = _2 + 1 }` wraps around `i32`.
+    let oprs: GenOprs<i128> = GenOprs::from_raw(vec![
+        BasicOpKind::Unary(UnaryOp::new(
+            p(1),
+            OpSource::Const(regular(0, 0)),
+            UnaryOpKind::Copy,
+        )),
+        BasicOpKind::Phi(PhiOp::new(p(2), vec![p(1), p(3)])),
+        BasicOpKind::Binary(BinaryOp::new(
+            p(3),
+            p(2),
+            OpSource::Const(regular(1, 1)),
+            BinOp::Add,
+            I32,
+        )),
+    ]);
+    let (ranges, steps) = solve(4, oprs, &[]);
+    let full = I32.full_range();
+    assert_eq!(ranges, vec![regular(0, 0), full.clone(), full]);
+    assert!(steps < 10, "{} widening steps", steps);
+}
+
+#[test]
+fn test_variable_without_definition_is_unconstrained() {
+    // `_2 = _1 & 1` with `_1` an argument.
+    let oprs: GenOprs<i128> = GenOprs::from_raw(vec![BasicOpKind::Binary(BinaryOp::new(
+        p(2),
+        p(1),
+        OpSource::Const(regular(1, 1)),
+        BinOp::BitAnd,
+        I32,
+    ))]);
+    let (ranges, _) = solve(3, oprs, &[]);
+    assert_eq!(ranges, vec![I32.full_range(), regular(0, 1)]);
+}
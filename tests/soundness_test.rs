@@ -0,0 +1,298 @@
+//! Randomised soundness checks of the range transfer functions: for random
+//! operand ranges, every concrete result of the operation on values of the
+//! ranges must lie in the abstract result. A failure is shrunk to a minimal
+//! counterexample before it is reported.
+
+#![feature(rustc_private)]
+
+#[macro_use]
+mod common;
+
+use num_traits::PrimInt;
+use rand::distr::uniform::SampleUniform;
+use rand::distr::{Distribution, StandardUniform};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::Display;
+use RAP_interval_demo::domain::range::{Range, RangeType};
+
+/// Pairs of operand ranges tried per operation and type.
+const CASES: usize = 500;
+/// Pairs of concrete operands sampled per pair of ranges.
+const SAMPLES: usize = 16;
+
+type AbstractOp<T> = fn(&Range<T>, &Range<T>) -> Range<T>;
+/// The concrete operation, or `None` where it panics.
+type ConcreteOp<T> = fn(T, T) -> Option<T>;
+
+/// Operand ranges `a` and `b`, and operands `x ∈ a` and `y ∈ b`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Case<T> {
+    a: (T, T),
+    b: (T, T),
+    x: T,
+    y: T,
+}
+
+fn range<T: PrimInt>((lb, ub): (T, T)) -> Range<T> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+fn contains<T: PrimInt>(range: &Range<T>, z: T) -> bool {
+    range.is_regular() && range.get_lower() <= z && z <= range.get_upper()
+}
+
+/// `floor((v + t) / 2)`, a step from `v` towards `t` that cannot overflow.
+fn halfway<T: PrimInt>(v: T, t: T) -> T {
+    (v >> 1) + (t >> 1) + (v & t & T::one())
+}
+
+fn random_bounds<T>(rng: &mut StdRng) -> (T, T)
+where
+    T: PrimInt + SampleUniform,
+    StandardUniform: Distribution<T>,
+{
+    let small = |rng: &mut StdRng| T::from(rng.random_range(-20..=20)).unwrap_or(T::zero());
+    let (a, b): (T, T) = match rng.random_range(0..5) {
+        0 => (rng.random(), rng.random()),
+        1 => (small(rng), small(rng)),
+        2 => {
+            let a = rng.random();
+            (a, a)
+        }
+        // Touching an end of the type.
+        3 => {
+            let a = rng.random();
+            if rng.random::<bool>() {
+                (T::min_value(), a)
+            } else {
+                (a, T::max_value())
+            }
+        }
+        _ => {
+            let a = rng.random();
+            let width = T::from(rng.random_range(0..16)).unwrap();
+            (a, a.saturating_add(width))
+        }
+    };
+    (a.min(b), a.max(b))
+}
+
+fn sample<T: PrimInt + SampleUniform>(rng: &mut StdRng, (lb, ub): (T, T)) -> T {
+    match rng.random_range(0..4) {
+        0 => lb,
+        1 => ub,
+        2 if lb <= T::zero() && T::zero() <= ub => T::zero(),
+        _ => rng.random_range(lb..=ub),
+    }
+}
+
+/// Whether the concrete result of `case` is missing from the abstract one.
+fn fails<T: PrimInt>(
+    case: &Case<T>,
+    abstract_op: AbstractOp<T>,
+    concrete_op: ConcreteOp<T>,
+) -> bool {
+    match concrete_op(case.x, case.y) {
+        Some(z) => !contains(&abstract_op(&range(case.a), &range(case.b)), z),
+        None => false,
+    }
+}
+
+/// `t`, halfway to `t` and one step towards `t`, leaving out `v` itself.
+fn toward<T: PrimInt>(v: T, t: T) -> impl Iterator<Item = T> {
+    let step = if v < t { v + T::one() } else { v - T::one() };
+    let steps = if v == t {
+        vec![]
+    } else {
+        vec![t, halfway(v, t), step]
+    };
+    steps.into_iter().filter(move |&w| w != v)
+}
+
+/// The cases one step smaller than `case`: a bound moved towards the operand
+/// in it, or an operand moved towards zero, within its range or along with
+/// it if the range is a single value. No step grows a range or moves an
+/// operand away from zero, so shrinking terminates.
+fn candidates<T: PrimInt>(case: &Case<T>) -> Vec<Case<T>> {
+    let mut smaller = Vec::new();
+    let zero_in = |(lb, ub): (T, T)| {
+        if lb == ub {
+            T::zero()
+        } else {
+            std::cmp::min(std::cmp::max(T::zero(), lb), ub)
+        }
+    };
+    let follow = |(lb, ub): (T, T), v: T| if lb == ub { (v, v) } else { (lb, ub) };
+    for x in toward(case.x, zero_in(case.a)) {
+        smaller.push(Case {
+            a: follow(case.a, x),
+            x,
+            ..*case
+        });
+    }
+    for y in toward(case.y, zero_in(case.b)) {
+        smaller.push(Case {
+            b: follow(case.b, y),
+            y,
+            ..*case
+        });
+    }
+    for lb in toward(case.a.0, case.x) {
+        smaller.push(Case {
+            a: (lb, case.a.1),
+            ..*case
+        });
+    }
+    for ub in toward(case.a.1, case.x) {
+        smaller.push(Case {
+            a: (case.a.0, ub),
+            ..*case
+        });
+    }
+    for lb in toward(case.b.0, case.y) {
+        smaller.push(Case {
+            b: (lb, case.b.1),
+            ..*case
+        });
+    }
+    for ub in toward(case.b.1, case.y) {
+        smaller.push(Case {
+            b: (case.b.0, ub),
+            ..*case
+        });
+    }
+    smaller
+}
+
+/// Takes smaller failing cases until none is left.
+fn shrink<T: PrimInt>(
+    mut case: Case<T>,
+    abstract_op: AbstractOp<T>,
+    concrete_op: ConcreteOp<T>,
+) -> Case<T> {
+    while let Some(smaller) = candidates(&case)
+        .into_iter()
+        .find(|c| fails(c, abstract_op, concrete_op))
+    {
+        case = smaller;
+    }
+    case
+}
+
+/// Searches a failing case with ranges from `random_bounds`, and returns it
+/// shrunk.
+fn find_counterexample<T>(
+    seed: u64,
+    abstract_op: AbstractOp<T>,
+    concrete_op: ConcreteOp<T>,
+) -> Option<Case<T>>
+where
+    T: PrimInt + SampleUniform,
+    StandardUniform: Distribution<T>,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..CASES {
+        let (a, b) = (random_bounds(&mut rng), random_bounds(&mut rng));
+        for _ in 0..SAMPLES {
+            let case = Case {
+                a,
+                b,
+                x: sample(&mut rng, a),
+                y: sample(&mut rng, b),
+            };
+            if fails(&case, abstract_op, concrete_op) {
+                return Some(shrink(case, abstract_op, concrete_op));
+            }
+        }
+    }
+    None
+}
+
+fn check_binary<T>(name: &str, abstract_op: AbstractOp<T>, concrete_op: ConcreteOp<T>)
+where
+    T: PrimInt + SampleUniform + Display,
+    StandardUniform: Distribution<T>,
+{
+    if let Some(c) = find_counterexample(0x5eed, abstract_op, concrete_op) {
+        let (a, b) = (range(c.a), range(c.b));
+        panic!(
+            "{} {} {} = {} ∌ {} {} {} = {}",
+            a,
+            name,
+            b,
+            abstract_op(&a, &b),
+            c.x,
+            name,
+            c.y,
+            concrete_op(c.x, c.y).unwrap()
+        );
+    }
+}
+
+/// A unary operation is checked as a binary one ignoring its second operand,
+/// which then shrinks to `[0, 0]`.
+fn check_unary<T>(name: &str, abstract_op: AbstractOp<T>, concrete_op: ConcreteOp<T>)
+where
+    T: PrimInt + SampleUniform + Display,
+    StandardUniform: Distribution<T>,
+{
+    if let Some(c) = find_counterexample(0x5eed, abstract_op, concrete_op) {
+        let a = range(c.a);
+        panic!(
+            "{}{} = {} ∌ {}{} = {}",
+            name,
+            a,
+            abstract_op(&a, &a),
+            name,
+            c.x,
+            concrete_op(c.x, c.y).unwrap()
+        );
+    }
+}
+
+macro_rules! soundness_tests {
+    ($name:ident, $t:ty) => {
+        mod $name {
+            use super::{check_binary, check_unary};
+
+            type T = $t;
+
+            range_op_tests!(check_binary::<T>, check_unary::<T>);
+        }
+    };
+}
+
+soundness_tests!(soundness_i8, i8);
+soundness_tests!(soundness_i16, i16);
+soundness_tests!(soundness_i32, i32);
+soundness_tests!(soundness_i64, i64);
+soundness_tests!(soundness_i128, i128);
+soundness_tests!(soundness_u8, u8);
+soundness_tests!(soundness_u16, u16);
+soundness_tests!(soundness_u32, u32);
+soundness_tests!(soundness_u64, u64);
+soundness_tests!(soundness_u128, u128);
+
+#[test]
+fn test_shrinks_to_minimal_counterexample() {
+    // Saturating bounds miss the values that wrap around.
+    let saturating: AbstractOp<i8> = |a, b| {
+        Range::new(
+            a.get_lower().saturating_add(b.get_lower()),
+            a.get_upper().saturating_add(b.get_upper()),
+            RangeType::Regular,
+        )
+    };
+    let c = find_counterexample(1, saturating, |x, y| Some(x.wrapping_add(y)))
+        .expect("the saturating sum is unsound");
+    // Single values, and just past an end of the type.
+    assert_eq!((c.a, c.b), ((c.x, c.x), (c.y, c.y)));
+    let sum = c.x as i16 + c.y as i16;
+    assert!(sum == 128 || sum == -129, "{:?}", c);
+    // The transfer function of the crate has no counterexample.
+    assert_eq!(
+        find_counterexample::<i8>(1, |a, b| a + b, |x, y| Some(x.wrapping_add(y))),
+        None
+    );
+}
@@ -0,0 +1,258 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+mod common;
+
+use common::with_mir;
+use rustc_middle::mir::{BasicBlock, Local, Location, Operand, Place, Rvalue};
+use RAP_interval_demo::SSA::SsaBody::SsaBody;
+
+// In e-SSA form `count` is
+//
+//   bb0: _1 = const 0_i32; goto -> bb1
+//   bb1: _5 = (copy _1, copy _7); _3 = copy _5; _2 = Lt(move _3, const 10_i32);
+//        switchInt(move _2) -> [0: bb4, otherwise: bb2]
+//   bb2: _6 = (copy _5, const 10_i32, const 1_usize);
+//        _4 = AddWithOverflow(copy _6, const 1_i32); assert(..) -> bb3
+//   bb3: _7 = move (_4.0: i32); goto -> bb1
+//   bb4: _8 = (copy _5, const 10_i32, const 2_usize); _9 = copy _8; return
+//
+// and `branch` continues the same loop, whose exit sigma of `i` is `_14`,
+// with `bb4: switchInt(i > 5) -> [0: bb6, otherwise: bb5]`, the arms
+// `bb5: _15 = sigma(_14); _5 = const 1_i32` and
+// `bb6: _16 = sigma(_14); _17 = const 2_i32` defining `y`, and the join
+// `bb7: _18 = (copy _5, copy _17); _8 = copy _18; _9 = copy _16; ..`.
+const SOURCE: &str = "
+fn count() -> i32 {
+    let mut i = 0;
+    while i < 10 {
+        i += 1;
+    }
+    i
+}
+
+fn branch() -> i32 {
+    let mut i = 0;
+    while i < 10 {
+        i += 1;
+    }
+    let y;
+    if i > 5 {
+        y = 1;
+    } else {
+        y = 2;
+    }
+    y + i
+}
+
+fn main() {
+    count();
+    branch();
+}
+";
+
+/// `i` in both functions.
+const I: usize = 1;
+/// `y` in `branch`.
+const Y: usize = 5;
+
+fn local(index: usize) -> Local {
+    Local::from_usize(index)
+}
+
+fn bb(index: usize) -> BasicBlock {
+    BasicBlock::from_usize(index)
+}
+
+fn loc(block: usize, statement_index: usize) -> Location {
+    Location {
+        block: bb(block),
+        statement_index,
+    }
+}
+
+fn copy<'tcx>(index: usize) -> Rvalue<'tcx> {
+    Rvalue::Use(Operand::Copy(Place::from(local(index))))
+}
+
+/// Every block of the edited body, as its statements followed by its
+/// terminator.
+type Blocks = Vec<Vec<String>>;
+
+/// Converts `function` to e-SSA form, applies `edit` to it and returns what
+/// `edit` returned with the edited body.
+fn edit<F, R>(function: &str, edit: F) -> (R, Blocks)
+where
+    F: for<'tcx> FnOnce(&mut SsaBody<'tcx>) -> R + Send,
+    R: Send,
+{
+    with_mir(SOURCE, function, |tcx, body| {
+        let mut ssa = SsaBody::new(tcx, body.clone());
+        let result = edit(&mut ssa);
+        let blocks = ssa
+            .body()
+            .basic_blocks
+            .iter()
+            .map(|data| {
+                let statements = data.statements.iter().map(|stmt| format!("{:?}", stmt));
+                let terminator = format!("{:?}", data.terminator().kind);
+                statements.chain([terminator]).collect()
+            })
+            .collect();
+        (result, blocks)
+    })
+}
+
+#[test]
+fn test_add_definition_rebinds_later_uses() {
+    let (new_local, blocks) = edit("count", |ssa| {
+        ssa.add_definition(loc(2, 1), local(I), copy(6))
+    });
+    assert_eq!(new_local, local(10));
+    assert_eq!(blocks[2][0], "_6 = (copy _5, const 10_i32, const 1_usize)");
+    assert_eq!(blocks[2][1], "_10 = copy _6");
+    assert_eq!(blocks[2][2], "_4 = AddWithOverflow(copy _10, const 1_i32)");
+    // `bb3` still defines the version reaching the back edge.
+    assert_eq!(blocks[1][0], "_5 = (copy _1, copy _7)");
+}
+
+#[test]
+fn test_add_definition_inserts_phi_at_join() {
+    let (new_local, blocks) = edit("branch", |ssa| {
+        ssa.add_definition(loc(5, 1), local(I), copy(15))
+    });
+    assert_eq!(new_local, local(20));
+    assert_eq!(blocks[5][1], "_20 = copy _15");
+    assert_eq!(blocks[7][0], "_21 = (copy _20, copy _16)");
+    assert_eq!(blocks[7][1], "_18 = (copy _5, copy _17)");
+    assert_eq!(blocks[7][3], "_9 = copy _21");
+}
+
+#[test]
+fn test_repair_variable_merges_sigmas() {
+    // The sigmas of `i` in the arms of `branch` both reach the join.
+    let ((), blocks) = edit("branch", |ssa| ssa.repair_variable(local(I)));
+    assert_eq!(blocks[7][0], "_20 = (copy _15, copy _16)");
+    assert_eq!(blocks[7][2], "_8 = copy _18");
+    assert_eq!(blocks[7][3], "_9 = copy _20");
+}
+
+#[test]
+fn test_remove_statement_folds_trivial_phi() {
+    let (removed, blocks) = edit("branch", |ssa| {
+        let y = ssa.add_definition(loc(4, 1), local(Y), copy(14));
+        let removed = [
+            ssa.remove_statement(loc(5, 1)),
+            ssa.remove_statement(loc(6, 1)),
+        ];
+        (y, removed.map(|stmt| format!("{:?}", stmt)))
+    });
+    assert_eq!(
+        removed,
+        (
+            local(20),
+            [
+                String::from("_5 = const 1_i32"),
+                String::from("_17 = const 2_i32")
+            ]
+        )
+    );
+    assert_eq!(
+        blocks[5],
+        [
+            "_15 = (copy _14, const 5_i32, const 4_usize)",
+            "goto -> bb7"
+        ]
+    );
+    // Both arms now pass `_20` to the join, so its phi of `y` is folded.
+    assert_eq!(blocks[7][0], "_8 = copy _20");
+}
+
+#[test]
+fn test_insert_and_fold_phi() {
+    let (folded, blocks) = edit("count", |ssa| {
+        let phi = ssa.insert_phi(local(I), bb(4));
+        let inserted = format!("{:?}", ssa.body().basic_blocks[bb(4)].statements[0]);
+        (
+            phi,
+            inserted,
+            ssa.fold_trivial_phi(local(I)),
+            ssa.fold_trivial_phi(local(I)),
+        )
+    });
+    assert_eq!(
+        folded,
+        (local(10), String::from("_10 = (copy _1,)"), true, false)
+    );
+    assert_eq!(blocks[4][0], "_8 = (copy _5, const 10_i32, const 2_usize)");
+    assert_eq!(blocks[1][0], "_5 = (copy _1, copy _7)");
+}
+
+#[test]
+fn test_split_block_keeps_sigmas() {
+    let (new_blocks, blocks) = edit("count", |ssa| {
+        (ssa.split_block(loc(2, 1)), ssa.split_block(loc(3, 0)))
+    });
+    assert_eq!(new_blocks, (bb(5), bb(6)));
+    assert_eq!(
+        blocks[2],
+        ["_6 = (copy _5, const 10_i32, const 1_usize)", "goto -> bb5"]
+    );
+    assert_eq!(blocks[5][0], "_4 = AddWithOverflow(copy _6, const 1_i32)");
+    assert_eq!(blocks[3], ["goto -> bb6"]);
+    assert_eq!(blocks[6], ["_7 = move (_4.0: i32)", "goto -> bb1"]);
+    // The back edge now leaves `bb6`, which still carries `_7`.
+    assert_eq!(blocks[1][0], "_5 = (copy _1, copy _7)");
+}
+
+#[test]
+fn test_add_definition_after_split_rebinds_moved_uses() {
+    // The use of `_6` moved to the new block, where the definition lands.
+    let (new_local, blocks) = edit("count", |ssa| {
+        let new_bb = ssa.split_block(loc(2, 1));
+        ssa.add_definition(
+            Location {
+                block: new_bb,
+                statement_index: 0,
+            },
+            local(I),
+            copy(6),
+        )
+    });
+    assert_eq!(new_local, local(10));
+    assert_eq!(blocks[5][0], "_10 = copy _6");
+    assert_eq!(blocks[5][1], "_4 = AddWithOverflow(copy _10, const 1_i32)");
+    assert_eq!(blocks[1][0], "_5 = (copy _1, copy _7)");
+}
+
+#[test]
+fn test_redirect_edge_repairs_both_targets() {
+    // `bb6` skips the join and goes straight to the return.
+    let (landing, blocks) = edit("branch", |ssa| ssa.redirect_edge(bb(6), bb(7), bb(8)));
+    assert_eq!(landing, None);
+    assert_eq!(blocks[6][2], "goto -> bb8");
+    // Only `bb5` enters `bb7`: the phi of `y` is folded and its sigma of `i`
+    // is the one read.
+    assert_eq!(blocks[7][0], "_8 = copy _5");
+    assert_eq!(blocks[7][1], "_9 = copy _15");
+    assert_eq!(blocks[8][0], "_21 = (copy _17, copy _5)");
+    assert_eq!(blocks[8][1], "_20 = (copy _16, copy _15)");
+}
+
+#[test]
+fn test_redirect_switch_edge_moves_sigmas() {
+    // The loop is never entered: the true edge of `i < 10` goes to the exit.
+    let (landing, blocks) = edit("count", |ssa| ssa.redirect_edge(bb(1), bb(2), bb(4)));
+    assert_eq!(landing, Some(bb(5)));
+    assert_eq!(
+        blocks[1][3],
+        "switchInt(move _2) -> [0: bb4, otherwise: bb5]"
+    );
+    assert_eq!(
+        blocks[5],
+        ["_6 = (copy _5, const 10_i32, const 1_usize)", "goto -> bb4"]
+    );
+    // Both edges of the switch meet at `bb4`, which needs a phi of `i`.
+    assert_eq!(blocks[4][0], "_10 = (copy _5, copy _6)");
+    assert_eq!(blocks[4][1], "_8 = (copy _10, const 10_i32, const 2_usize)");
+}
@@ -0,0 +1,50 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::BinOp;
+use RAP_interval_demo::domain::domain::{negate_predicate, swap_predicate, SymbInterval, VarId};
+use RAP_interval_demo::domain::range::{Range, RangeType};
+
+fn regular(lb: i32, ub: i32) -> Range<i32> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+/// Resolves `x predicate j` once `j` is known to lie in `bound`.
+fn resolve(predicate: BinOp, bound: Range<i32>) -> Range<i32> {
+    let symb = SymbInterval::new(Range::default(), VarId::from_usize(0), predicate);
+    symb.fix_intersects(&bound)
+}
+
+#[test]
+fn test_strict_and_non_strict() {
+    assert_eq!(resolve(BinOp::Lt, regular(0, 99)), regular(i32::MIN, 98));
+    assert_eq!(resolve(BinOp::Le, regular(0, 99)), regular(i32::MIN, 99));
+    assert_eq!(resolve(BinOp::Gt, regular(0, 99)), regular(1, i32::MAX));
+    assert_eq!(resolve(BinOp::Ge, regular(0, 99)), regular(0, i32::MAX));
+    assert_eq!(resolve(BinOp::Eq, regular(0, 99)), regular(0, 99));
+    assert_eq!(resolve(BinOp::Ne, regular(0, 99)), Range::default());
+}
+
+#[test]
+fn test_unsatisfiable() {
+    assert!(resolve(BinOp::Lt, regular(i32::MIN, i32::MIN)).is_empty());
+    assert!(resolve(BinOp::Gt, regular(i32::MAX, i32::MAX)).is_empty());
+    assert!(resolve(BinOp::Lt, Range::empty()).is_empty());
+}
+
+#[test]
+fn test_unknown_bound() {
+    assert_eq!(resolve(BinOp::Lt, Range::unknown()), Range::default());
+}
+
+#[test]
+fn test_predicates() {
+    // `i < j` is false exactly when `i >= j`, and reads `j > i` from `j`.
+    assert_eq!(negate_predicate(BinOp::Lt), BinOp::Ge);
+    assert_eq!(negate_predicate(BinOp::Le), BinOp::Gt);
+    assert_eq!(negate_predicate(BinOp::Eq), BinOp::Ne);
+    assert_eq!(swap_predicate(BinOp::Lt), BinOp::Gt);
+    assert_eq!(swap_predicate(BinOp::Ge), BinOp::Le);
+    assert_eq!(swap_predicate(BinOp::Eq), BinOp::Eq);
+    assert_eq!(negate_predicate(swap_predicate(BinOp::Lt)), BinOp::Le);
+}
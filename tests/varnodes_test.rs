@@ -0,0 +1,52 @@
+#![feature(rustc_private)]
+extern crate rustc_middle;
+
+use rustc_middle::mir::{Local, Place};
+use RAP_interval_demo::domain::domain::{VarNode, VarNodes};
+use RAP_interval_demo::domain::range::{IntType, Range, RangeType};
+
+const I32: IntType = IntType::new(32, true);
+
+fn regular(lb: i128, ub: i128) -> Range<i128> {
+    Range::new(lb, ub, RangeType::Regular)
+}
+
+#[test]
+fn test_nodes_are_indexed_by_place_and_id() {
+    let places: Vec<Place> = (0..3).map(|i| Place::from(Local::from_usize(i))).collect();
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    let ids: Vec<_> = places
+        .iter()
+        .map(|&place| vars.insert(VarNode::new(place, Some(I32))))
+        .collect();
+    assert_eq!(vars.len(), 3);
+    for (place, &id) in places.iter().zip(&ids) {
+        assert_eq!(vars.id_of(place), Some(id));
+        assert_eq!(&vars.place(id), place);
+    }
+    // A place equal to a stored one finds its node.
+    let copy = Place::from(Local::from_usize(1));
+    assert_eq!(vars.id_of(&copy), Some(ids[1]));
+    assert_eq!(vars.id_of(&Place::from(Local::from_usize(7))), None);
+
+    // Inserting a place again replaces its node and keeps its index.
+    let mut node = VarNode::new(places[1], Some(I32));
+    node.set_range(regular(0, 9));
+    assert_eq!(vars.insert(node), ids[1]);
+    assert_eq!(vars.len(), 3);
+    assert_eq!(vars[ids[1]].get_range(), &regular(0, 9));
+    assert_eq!(vars[&copy].get_range(), &regular(0, 9));
+    let order: Vec<_> = vars.iter().map(|(_, node)| *node.get_value()).collect();
+    assert_eq!(order, places);
+}
+
+#[test]
+fn test_clone_is_independent() {
+    let x = Place::from(Local::from_usize(1));
+    let mut vars: VarNodes<i128> = VarNodes::new();
+    vars.insert(VarNode::new(x, Some(I32)));
+    let mut copy = vars.clone();
+    copy.get_mut(&x).unwrap().set_range(regular(1, 2));
+    assert_eq!(copy[&x].get_range(), &regular(1, 2));
+    assert_eq!(vars[&x].get_range(), &I32.full_range());
+}